@@ -0,0 +1,227 @@
+//! The append only file, every write applied to the databases is appended to it in the RESP
+//! encoding replicas receive, so that replaying it rebuilds the data after a restart. A rewrite
+//! replaces the log with a snapshot of the data followed by the writes applied since.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tokio::time;
+use crate::utils;
+
+// the log is rewritten once it grew to twice its size after the last rewrite, and past this size
+const REWRITE_MIN_SIZE: u64 = 64 * 1024 * 1024;
+
+/// When the appended writes are flushed to the disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fsync {
+    /// After every write.
+    Always,
+    /// Once a second, by a background task.
+    EverySec,
+    /// Whenever the operating system does it.
+    No,
+}
+
+impl Fsync {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Fsync::Always => "always",
+            Fsync::EverySec => "everysec",
+            Fsync::No => "no",
+        }
+    }
+}
+
+impl FromStr for Fsync {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "always" => Ok(Fsync::Always),
+            "everysec" => Ok(Fsync::EverySec),
+            "no" => Ok(Fsync::No),
+            _ => Err(format!("invalid appendfsync policy '{}', expected always, everysec or no", s)),
+        }
+    }
+}
+
+/// The settings of the append only file, which lives next to the RDB file.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub file_name: String,
+    pub fsync: Fsync,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Aof {
+    shard: Arc<Shard>,
+}
+
+#[derive(Debug)]
+struct Shard {
+    path: PathBuf,
+    fsync: Fsync,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    file: fs::File,
+    size: u64,
+    // the size of the file right after the last rewrite
+    base_size: u64,
+    // the writes appended while a rewrite runs, they follow the snapshot in the rewritten file
+    rewrite: Option<Vec<u8>>,
+    // tells a finished rewrite apart from the ones aborted and started again since it began
+    generation: u64,
+    // writes not flushed to the disk yet
+    dirty: bool,
+    // set when a write failed, the file may end with a partial command until it is rewritten
+    failed: bool,
+}
+
+impl Aof {
+    /// Open the file at `path` to append to it, creating it when missing.
+    pub(crate) async fn open(path: PathBuf, fsync: Fsync) -> crate::Result<Aof> {
+        let file = OpenOptions::new().create(true).append(true).open(&path).await?;
+        let size = file.metadata().await?.len();
+        let shard = Arc::new(Shard {
+            path,
+            fsync,
+            state: Mutex::new(State {
+                file,
+                size,
+                base_size: size,
+                rewrite: None,
+                generation: 0,
+                dirty: false,
+                failed: false,
+            }),
+        });
+        if fsync == Fsync::EverySec {
+            tokio::spawn(fsync_every_second(Arc::downgrade(&shard)));
+        }
+        Ok(Aof { shard })
+    }
+
+    pub(crate) fn config(&self) -> Config {
+        Config {
+            file_name: self.shard.path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            fsync: self.shard.fsync,
+        }
+    }
+
+    /// Append the encoded commands of a write.
+    pub(crate) async fn append(&self, data: &[u8]) {
+        let mut state = self.shard.state.lock().await;
+        if let Some(rewrite) = state.rewrite.as_mut() {
+            rewrite.extend_from_slice(data);
+        }
+        let written = match state.file.write_all(data).await {
+            Ok(()) => state.file.flush().await,
+            Err(e) => Err(e),
+        };
+        let synced = match (written, self.shard.fsync) {
+            (Ok(()), Fsync::Always) => state.file.sync_data().await,
+            (written, _) => written,
+        };
+        match synced {
+            Ok(()) => {
+                state.size += data.len() as u64;
+                state.dirty = self.shard.fsync == Fsync::EverySec;
+            }
+            Err(_) => state.failed = true,
+        }
+    }
+
+    /// Whether the file grew enough since the last rewrite, or has to be rewritten after a failed write.
+    pub(crate) async fn wants_rewrite(&self) -> bool {
+        let state = self.shard.state.lock().await;
+        if state.rewrite.is_some() {
+            return false;
+        }
+        state.failed || (state.size >= REWRITE_MIN_SIZE && state.size >= 2 * state.base_size)
+    }
+
+    /// Start buffering the appended writes for a rewrite, returns the generation to finish it with,
+    /// `None` if a rewrite is already running unless `restart` drops it.
+    pub(crate) async fn begin_rewrite(&self, restart: bool) -> Option<u64> {
+        let mut state = self.shard.state.lock().await;
+        if state.rewrite.is_some() && !restart {
+            return None;
+        }
+        state.rewrite = Some(Vec::new());
+        state.generation += 1;
+        Some(state.generation)
+    }
+
+    /// Drop the rewrite of `generation` if it is still running, its snapshot is never installed.
+    pub(crate) async fn abort_rewrite(&self, generation: u64) {
+        let mut state = self.shard.state.lock().await;
+        if state.generation == generation {
+            state.rewrite = None;
+        }
+    }
+
+    /// Replace the file with `base`, the snapshot taken when the rewrite began, followed by the writes
+    /// appended since.
+    pub(crate) async fn finish_rewrite(&self, generation: u64, base: Vec<u8>) -> crate::Result<()> {
+        let temp = utils::fs::temp_path(&self.shard.path);
+        let result = self.install(generation, &temp, base).await;
+        if result.is_err() {
+            let _ = fs::remove_file(&temp).await;
+            self.abort_rewrite(generation).await;
+        }
+        result
+    }
+
+    async fn install(&self, generation: u64, temp: &Path, base: Vec<u8>) -> crate::Result<()> {
+        // most of the file is written before taking the lock, so that writes are not held up
+        let mut file = fs::File::create(temp).await?;
+        file.write_all(&base).await?;
+        let mut state = self.shard.state.lock().await;
+        if state.generation != generation || state.rewrite.is_none() {
+            return Err("the rewrite was aborted".into());
+        }
+        let appended = state.rewrite.take().unwrap_or_default();
+        file.write_all(&appended).await?;
+        file.flush().await?;
+        file.sync_all().await?;
+        fs::rename(temp, &self.shard.path).await?;
+        let size = (base.len() + appended.len()) as u64;
+        state.file = file;
+        state.size = size;
+        state.base_size = size;
+        state.dirty = false;
+        state.failed = false;
+        utils::fs::sync_dir(&self.shard.path).await
+    }
+}
+
+/// Cut the file at `path` to its first `len` bytes, dropping a partial command left by a crash.
+pub(crate) async fn truncate(path: &Path, len: u64) -> crate::Result<()> {
+    let file = OpenOptions::new().write(true).open(path).await?;
+    file.set_len(len).await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
+/// Flush the writes of the last second to the disk, until the file is dropped.
+async fn fsync_every_second(shard: Weak<Shard>) {
+    let mut interval = time::interval(Duration::from_secs(1));
+    loop {
+        interval.tick().await;
+        let shard = match shard.upgrade() {
+            Some(shard) => shard,
+            None => return,
+        };
+        let mut state = shard.state.lock().await;
+        if state.dirty && state.file.sync_data().await.is_ok() {
+            state.dirty = false;
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct BgRewriteAof {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for BgRewriteAof {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(BgRewriteAof { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for BgRewriteAof {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().bgrewriteaof().await {
+            Ok(()) => Type::SimpleString("Background append only file rewriting started".to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::db::{Blocking, Served};
+use crate::encoder::Encoder;
+use crate::engine::list::Direction;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct BPop {
+    command_size: u64,
+    keys: Vec<String>,
+    direction: Direction,
+    timeout: Option<Duration>,
+}
+
+impl BPop {
+    /// Parse BLPOP or BRPOP, the timeout comes after the keys.
+    pub(crate) fn parse_frames(parse: &mut Parse, direction: Direction) -> crate::Result<Self> {
+        let mut args = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(arg) => args.push(arg),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let timeout = match args.pop() {
+            Some(timeout) if !args.is_empty() => parse_timeout(&timeout)?,
+            _ => return Err(parser::Error::EndOfStream.into()),
+        };
+        Ok(BPop { command_size: parse.command_size(), keys: args, direction, timeout })
+    }
+}
+
+/// Parse a blocking timeout in seconds, 0 meaning forever.
+pub(crate) fn parse_timeout(timeout: &str) -> crate::Result<Option<Duration>> {
+    let seconds: f64 = timeout.parse().map_err(|_| "timeout is not a float or out of range")?;
+    if !seconds.is_finite() {
+        return Err("timeout is not a float or out of range".into());
+    }
+    if seconds < 0.0 {
+        return Err("timeout is negative".into());
+    }
+    if seconds == 0.0 {
+        return Ok(None);
+    }
+    Ok(Some(Duration::from_secs_f64(seconds)))
+}
+
+#[async_trait]
+impl Applicable for BPop {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let (db, closed) = dst.blocking_db();
+        let resp = match db.block(self.keys, Blocking::Pop(self.direction, 1), true, self.timeout, closed).await {
+            Ok(Some(Served::Popped(key, values))) => {
+                let mut arr = vec![Type::BulkString(key.into())];
+                arr.extend(values.into_iter().map(Type::BulkString));
+                Type::Array(arr)
+            }
+            Ok(_) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
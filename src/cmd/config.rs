@@ -2,22 +2,48 @@ use async_trait::async_trait;
 use tokio::io::AsyncWriteExt;
 use crate::connection::{Applicable, Connection};
 use crate::encoder::Encoder;
-use crate::parser::Parse;
+use crate::parser::{self, Parse};
 use crate::resp::Type;
+use crate::utils::glob;
 
 #[derive(Debug, PartialEq)]
 pub struct Config {
     command_size: u64,
-    ask: Option<String>,
+    subcommand: Subcommand,
 }
 
+#[derive(Debug, PartialEq)]
+enum Subcommand {
+    Get(String),
+    Set(Vec<(String, String)>),
+}
+
+// the parameters CONFIG SET can change at runtime
+const SETTABLE: [&str; 1] = ["notify-keyspace-events"];
+
 impl TryFrom<&mut Parse> for Config {
     type Error = crate::Error;
     fn try_from(parse: &mut Parse) -> crate::Result<Self> {
-        match parse.next_string()?.to_uppercase().as_str() {
-            "GET" => Ok(Config { command_size: parse.command_size(), ask: Some(parse.next_string()?) }),
-            _ => Err("CONFIG subcommand not supported".into()),
-        }
+        let subcommand = match parse.next_string()?.to_uppercase().as_str() {
+            "GET" => Subcommand::Get(parse.next_string()?),
+            "SET" => {
+                let mut pairs = vec![(parse.next_string()?.to_lowercase(), parse.next_string()?)];
+                loop {
+                    let name = match parse.next_string() {
+                        Ok(name) => name.to_lowercase(),
+                        Err(parser::Error::EndOfStream) => break,
+                        Err(err) => return Err(err.into()),
+                    };
+                    pairs.push((name, parse.next_string()?));
+                }
+                if let Some((name, _)) = pairs.iter().find(|(name, _)| !SETTABLE.contains(&name.as_str())) {
+                    return Err(format!("Unknown option or number of arguments for CONFIG SET - '{}'", name).into());
+                }
+                Subcommand::Set(pairs)
+            }
+            _ => return Err("CONFIG subcommand not supported".into()),
+        };
+        Ok(Config { command_size: parse.command_size(), subcommand })
     }
 }
 
@@ -27,22 +53,48 @@ impl Applicable for Config {
         if dst.need_update_offset().await {
             dst.db().role().await.add_offset(self.command_size);
         }
-        let mut resp = vec![Type::BulkString("unsupported CONFIG subcommand".into())];
-        if let Some(ask) = self.ask {
-            match ask.to_lowercase().as_str() {
-                "dir" => {
-                    resp = vec![Type::BulkString("dir".into()),
-                                Type::BulkString(dst.db().dir().await.into())];
+        let resp = match self.subcommand {
+            Subcommand::Get(ask) => {
+                let mut resp = vec![];
+                let aof = dst.db().aof_config().await;
+                for name in ["dir", "dbfilename", "databases", "notify-keyspace-events", "save", "appendonly", "appendfilename", "appendfsync"] {
+                    if !glob::matches_nocase(ask.as_bytes(), name.as_bytes()) {
+                        continue;
+                    }
+                    let value = match (name, &aof) {
+                        ("dir", _) => dst.db().dir().await,
+                        ("dbfilename", _) => dst.db().file_name().await,
+                        ("databases", _) => dst.db().databases().to_string(),
+                        ("notify-keyspace-events", _) => dst.db().pubsub().keyspace_events(),
+                        ("save", _) => dst.db().save_points().to_string(),
+                        ("appendonly", aof) => if aof.is_some() { "yes" } else { "no" }.to_string(),
+                        // the file settings are only known when it is enabled
+                        (_, None) => continue,
+                        ("appendfilename", Some(aof)) => aof.file_name.clone(),
+                        (_, Some(aof)) => aof.fsync.as_str().to_string(),
+                    };
+                    resp.push(Type::BulkString(name.into()));
+                    resp.push(Type::BulkString(value.into()));
                 }
-                "dbfilename" => {
-                    resp = vec![Type::BulkString("dbfilename".into()),
-                                Type::BulkString(dst.db().file_name().await.into())];
+                Type::Array(resp)
+            }
+            Subcommand::Set(pairs) => {
+                let mut resp = Type::SimpleString("OK".to_string());
+                for (name, value) in pairs.into_iter() {
+                    // only notify-keyspace-events gets past parsing
+                    if !dst.db().pubsub().set_keyspace_events(&value) {
+                        resp = Type::SimpleError(format!(
+                            "ERR CONFIG SET failed (possibly related to argument '{}') - Invalid event class character. Use 'Ag$lshzxeKEt'.",
+                            name,
+                        ));
+                        break;
+                    }
                 }
-                _ => {}
+                resp
             }
-        }
-        dst.write_all(Encoder::encode(&Type::Array(resp)).as_slice()).await?;
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
         dst.flush().await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Copy {
+    command_size: u64,
+    src: String,
+    dst: String,
+    replace: bool,
+}
+
+impl TryFrom<&mut Parse> for Copy {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let src = parse.next_string()?;
+        let dst = parse.next_string()?;
+        let mut replace = false;
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match option.as_str() {
+                "REPLACE" => replace = true,
+                _ => return Err("syntax error".into()),
+            }
+        }
+        Ok(Copy { command_size: parse.command_size(), src, dst, replace })
+    }
+}
+
+#[async_trait]
+impl Applicable for Copy {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().copy(self.src, self.dst, self.replace).await {
+            Ok(copied) => Type::Integer(copied as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
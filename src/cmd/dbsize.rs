@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct DBSize {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for DBSize {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(DBSize { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for DBSize {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let size = dst.db().dbsize().await;
+        dst.write_all(Encoder::encode(&Type::Integer(size as i64)).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -6,30 +6,35 @@ use crate::parser::Parse;
 use crate::resp::Type;
 
 #[derive(Debug, PartialEq)]
-pub struct DEL {
+pub struct Del {
     command_size: u64,
     keys: Vec<String>,
+    unlink: bool,
 }
 
-impl TryFrom<&mut Parse> for DEL {
-    type Error = crate::Error;
-    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+impl Del {
+    /// Parse DEL, or UNLINK with `unlink`.
+    pub(crate) fn parse_frames(parse: &mut Parse, unlink: bool) -> crate::Result<Self> {
         let mut keys = Vec::new();
         while let Ok(key) = parse.next_string() {
             keys.push(key);
         }
-        Ok(DEL { command_size: parse.command_size(), keys })
+        Ok(Del { command_size: parse.command_size(), keys, unlink })
     }
 }
 
 #[async_trait]
-impl Applicable for DEL {
+impl Applicable for Del {
     async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
         if dst.need_update_offset().await {
             dst.db().role().await.add_offset(self.command_size);
         }
-        let count = dst.db().del(self.keys).await;
-        dst.write_all(Encoder::encode(&Type::Integer(count)).as_slice()).await?;
+        let count = if self.unlink {
+            dst.db().unlink(self.keys).await
+        } else {
+            dst.db().del(self.keys).await
+        };
+        dst.write_all(Encoder::encode(&Type::Integer(count as i64)).as_slice()).await?;
         dst.flush().await?;
         Ok(())
     }
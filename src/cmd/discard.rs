@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Discard {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for Discard {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(Discard { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for Discard {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.take_transaction() {
+            Some(_) => {
+                dst.unwatch().await;
+                Type::SimpleString("OK".to_string())
+            }
+            None => Type::SimpleError("ERR DISCARD without MULTI".to_string()),
+        };
+        if !dst.is_master_link().await {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
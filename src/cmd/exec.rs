@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Exec {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for Exec {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(Exec { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for Exec {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let reply = !dst.is_master_link().await;
+        let transaction = match dst.take_transaction() {
+            Some(transaction) => transaction,
+            None => {
+                if reply {
+                    let resp = Type::SimpleError("ERR EXEC without MULTI".to_string());
+                    dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+                    dst.flush().await?;
+                }
+                return Ok(());
+            }
+        };
+        if transaction.aborted {
+            dst.unwatch().await;
+            let resp = Type::SimpleError("EXECABORT Transaction discarded because of previous errors.".to_string());
+            if reply {
+                dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+                dst.flush().await?;
+            }
+            return Ok(());
+        }
+        dst.db().begin_transaction().await;
+        let watched = dst.watched().to_vec();
+        let result = if dst.db().watched_changed(&watched).await {
+            if reply {
+                dst.write_all(Encoder::encode(&Type::Null).as_slice()).await?;
+                dst.flush().await?;
+            }
+            Ok(())
+        } else {
+            // every queued command writes its own reply as an element of the array
+            if reply {
+                dst.write_all(format!("*{}\r\n", transaction.commands.len()).as_bytes()).await?;
+            }
+            let mut result = Ok(());
+            for command in transaction.commands.into_iter() {
+                result = command.apply(dst).await;
+                if result.is_err() {
+                    break;
+                }
+            }
+            result
+        };
+        dst.db().end_transaction().await;
+        dst.unwatch().await;
+        if reply {
+            dst.flush().await?;
+        }
+        result
+    }
+}
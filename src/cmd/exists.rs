@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Exists {
+    command_size: u64,
+    keys: Vec<String>,
+}
+
+impl TryFrom<&mut Parse> for Exists {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let mut keys = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Exists { command_size: parse.command_size(), keys })
+    }
+}
+
+#[async_trait]
+impl Applicable for Exists {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let count = dst.db().exists(self.keys).await;
+        dst.write_all(Encoder::encode(&Type::Integer(count as i64)).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::ExpireOptions;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Expire {
+    command_size: u64,
+    key: String,
+    time: i64,
+    style: Style,
+    options: ExpireOptions,
+}
+
+/// The command flavour an EXPIRE-like request was issued with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Expire,
+    PExpire,
+    ExpireAt,
+    PExpireAt,
+}
+
+impl Style {
+    fn name(&self) -> &'static str {
+        match self {
+            Style::Expire => "expire",
+            Style::PExpire => "pexpire",
+            Style::ExpireAt => "expireat",
+            Style::PExpireAt => "pexpireat",
+        }
+    }
+}
+
+impl Expire {
+    pub(crate) fn parse_frames(parse: &mut Parse, style: Style) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let time = parse.next_signed_int()?;
+        let mut options = ExpireOptions::default();
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option,
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match option.to_uppercase().as_str() {
+                "NX" => options.nx = true,
+                "XX" => options.xx = true,
+                "GT" => options.gt = true,
+                "LT" => options.lt = true,
+                _ => return Err(format!("Unsupported option {}", option).into()),
+            }
+        }
+        if options.nx && (options.xx || options.gt || options.lt) {
+            return Err("NX and XX, GT or LT options at the same time are not compatible".into());
+        }
+        if options.gt && options.lt {
+            return Err("GT and LT options at the same time are not compatible".into());
+        }
+        Ok(Expire { command_size: parse.command_size(), key, time, style, options })
+    }
+
+    /// The absolute expiration time as milliseconds since the Unix epoch, `None` on overflow.
+    fn unix_millis(&self) -> Option<i64> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        match self.style {
+            Style::Expire => self.time.checked_mul(1000)?.checked_add(now),
+            Style::PExpire => self.time.checked_add(now),
+            Style::ExpireAt => self.time.checked_mul(1000),
+            Style::PExpireAt => Some(self.time),
+        }
+    }
+}
+
+#[async_trait]
+impl Applicable for Expire {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match self.unix_millis() {
+            Some(ms) => {
+                let when = SystemTime::UNIX_EPOCH + Duration::from_millis(ms.max(0) as u64);
+                let done = dst.db().expire(self.key, when, self.options).await;
+                Type::Integer(done as i64)
+            }
+            None => Type::SimpleError(format!("ERR invalid expire time in '{}' command", self.style.name())),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Flush {
+    command_size: u64,
+    all: bool,
+    lazy: bool,
+}
+
+impl Flush {
+    /// Parse FLUSHDB, or FLUSHALL with `all`.
+    pub(crate) fn parse_frames(parse: &mut Parse, all: bool) -> crate::Result<Self> {
+        let lazy = match parse.next_string() {
+            Ok(mode) => match mode.to_uppercase().as_str() {
+                "ASYNC" => true,
+                "SYNC" => false,
+                _ => return Err("syntax error".into()),
+            },
+            Err(parser::Error::EndOfStream) => false,
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Flush { command_size: parse.command_size(), all, lazy })
+    }
+}
+
+#[async_trait]
+impl Applicable for Flush {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        if self.all {
+            dst.db().flushall(self.lazy).await;
+        } else {
+            dst.db().flushdb(self.lazy).await;
+        }
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&Type::SimpleString("OK".to_string())).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct GetDel {
+    command_size: u64,
+    key: String,
+}
+
+impl TryFrom<&mut Parse> for GetDel {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        Ok(GetDel { command_size: parse.command_size(), key })
+    }
+}
+
+#[async_trait]
+impl Applicable for GetDel {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().getdel(self.key).await {
+            Ok(Some(value)) => Type::BulkString(value),
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
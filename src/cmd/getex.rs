@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::set::Expire;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct GetEx {
+    command_size: u64,
+    key: String,
+    expire: Option<Expire>,
+    persist: bool,
+}
+
+impl TryFrom<&mut Parse> for GetEx {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let (mut expire, mut persist) = (None, false);
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match option.as_str() {
+                "PERSIST" if expire.is_none() => persist = true,
+                "EX" | "PX" | "EXAT" | "PXAT" if expire.is_none() && !persist => {
+                    expire = Some(Expire::parse_frames(parse, &option, "getex")?);
+                }
+                _ => return Err("syntax error".into()),
+            }
+        }
+        Ok(GetEx { command_size: parse.command_size(), key, expire, persist })
+    }
+}
+
+#[async_trait]
+impl Applicable for GetEx {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match self.expire.map(Expire::resolve) {
+            Some(None) => Type::SimpleError("ERR invalid expire time in 'getex' command".to_string()),
+            expire => {
+                // `Some(None)` asks the DB to clear the expiration
+                let expire = expire.or(self.persist.then_some(None));
+                match dst.db().getex(self.key, expire).await {
+                    Ok(Some(value)) => Type::BulkString(value),
+                    Ok(None) => Type::Null,
+                    Err(e) => Type::SimpleError(e.to_string()),
+                }
+            }
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
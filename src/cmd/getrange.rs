@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct GetRange {
+    command_size: u64,
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+impl TryFrom<&mut Parse> for GetRange {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let start = parse.next_signed_int()?;
+        let end = parse.next_signed_int()?;
+        Ok(GetRange { command_size: parse.command_size(), key, start, end })
+    }
+}
+
+#[async_trait]
+impl Applicable for GetRange {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().getrange(self.key, self.start, self.end).await {
+            Ok(value) => Type::BulkString(value),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::string::SetOptions;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct GetSet {
+    command_size: u64,
+    key: String,
+    value: Bytes,
+}
+
+impl TryFrom<&mut Parse> for GetSet {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let value = parse.next_bytes()?;
+        Ok(GetSet { command_size: parse.command_size(), key, value })
+    }
+}
+
+#[async_trait]
+impl Applicable for GetSet {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let options = SetOptions { get: true, ..Default::default() };
+        let resp = match dst.db().set(self.key, self.value, None, options).await {
+            Ok((_, Some(old))) => Type::BulkString(old),
+            Ok((_, None)) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct HDel {
+    command_size: u64,
+    key: String,
+    fields: Vec<Bytes>,
+}
+
+impl TryFrom<&mut Parse> for HDel {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let mut fields = vec![parse.next_bytes()?];
+        loop {
+            match parse.next_bytes() {
+                Ok(field) => fields.push(field),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(HDel { command_size: parse.command_size(), key, fields })
+    }
+}
+
+#[async_trait]
+impl Applicable for HDel {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().hdel(self.key, self.fields).await {
+            Ok(removed) => Type::Integer(removed as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct HGet {
+    command_size: u64,
+    key: String,
+    field: Bytes,
+}
+
+impl TryFrom<&mut Parse> for HGet {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let field = parse.next_bytes()?;
+        Ok(HGet { command_size: parse.command_size(), key, field })
+    }
+}
+
+#[async_trait]
+impl Applicable for HGet {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().hget(self.key, self.field).await {
+            Ok(Some(value)) => Type::BulkString(value),
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct HGetAll {
+    command_size: u64,
+    key: String,
+}
+
+impl TryFrom<&mut Parse> for HGetAll {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        Ok(HGetAll { command_size: parse.command_size(), key })
+    }
+}
+
+#[async_trait]
+impl Applicable for HGetAll {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().hgetall(self.key).await {
+            Ok(pairs) => {
+                let mut arr = Vec::with_capacity(pairs.len() * 2);
+                for (field, value) in pairs.into_iter() {
+                    arr.push(Type::BulkString(field));
+                    arr.push(Type::BulkString(value));
+                }
+                Type::Array(arr)
+            }
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
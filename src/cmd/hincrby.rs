@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct HIncrBy {
+    command_size: u64,
+    key: String,
+    field: Bytes,
+    increment: i64,
+}
+
+impl TryFrom<&mut Parse> for HIncrBy {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let field = parse.next_bytes()?;
+        let increment = parse.next_signed_int()?;
+        Ok(HIncrBy { command_size: parse.command_size(), key, field, increment })
+    }
+}
+
+#[async_trait]
+impl Applicable for HIncrBy {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().hincrby(self.key, self.field, self.increment).await {
+            Ok(value) => Type::Integer(value),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct HSet {
+    command_size: u64,
+    key: String,
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+impl TryFrom<&mut Parse> for HSet {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let mut pairs = vec![(parse.next_bytes()?, parse.next_bytes()?)];
+        loop {
+            match parse.next_bytes() {
+                Ok(field) => {
+                    let value = parse.next_bytes()?;
+                    pairs.push((field, value));
+                }
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(HSet { command_size: parse.command_size(), key, pairs })
+    }
+}
+
+#[async_trait]
+impl Applicable for HSet {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().hset(self.key, self.pairs).await {
+            Ok(added) => Type::Integer(added as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+impl HSet {
+    pub fn new(command_size: u64, key: impl ToString, pairs: Vec<(Bytes, Bytes)>) -> HSet {
+        HSet {
+            command_size,
+            key: key.to_string(),
+            pairs,
+        }
+    }
+}
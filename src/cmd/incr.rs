@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+use crate::utils::strings;
+
+#[derive(Debug, PartialEq)]
+pub struct IncrBy {
+    command_size: u64,
+    key: String,
+    increment: i64,
+}
+
+/// The command flavour an INCR-like request was issued with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Incr,
+    Decr,
+    IncrBy,
+    DecrBy,
+}
+
+impl IncrBy {
+    pub(crate) fn parse_frames(parse: &mut Parse, style: Style) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let increment = match style {
+            Style::Incr => 1,
+            Style::Decr => -1,
+            Style::IncrBy => parse_increment(parse)?,
+            Style::DecrBy => parse_increment(parse)?
+                .checked_neg()
+                .ok_or("decrement would overflow")?,
+        };
+        Ok(IncrBy { command_size: parse.command_size(), key, increment })
+    }
+}
+
+fn parse_increment(parse: &mut Parse) -> crate::Result<i64> {
+    let increment = parse.next_string()?;
+    Ok(strings::parse_int(&increment).ok_or("value is not an integer or out of range")?)
+}
+
+#[async_trait]
+impl Applicable for IncrBy {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().incr_by(self.key, self.increment).await {
+            Ok(value) => Type::Integer(value),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+use crate::utils::strings;
+
+#[derive(Debug, PartialEq)]
+pub struct IncrByFloat {
+    command_size: u64,
+    key: String,
+    increment: f64,
+}
+
+impl TryFrom<&mut Parse> for IncrByFloat {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let increment = strings::parse_float(&parse.next_string()?).ok_or("value is not a valid float")?;
+        Ok(IncrByFloat { command_size: parse.command_size(), key, increment })
+    }
+}
+
+#[async_trait]
+impl Applicable for IncrByFloat {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().incr_by_float(self.key, self.increment).await {
+            Ok(value) => Type::BulkString(value),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -1,33 +1,23 @@
 use async_trait::async_trait;
-use regex::Regex;
+use bytes::Bytes;
 use tokio::io::AsyncWriteExt;
 use crate::connection::{Applicable, Connection};
 use crate::encoder::Encoder;
 use crate::parser::Parse;
 use crate::resp::Type;
+use crate::utils::glob;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Keys {
     command_size: u64,
-    regex: Regex,
+    pattern: Bytes,
 }
 
 impl TryFrom<&mut Parse> for Keys {
     type Error = crate::Error;
     fn try_from(parse: &mut Parse) -> crate::Result<Self> {
-        let ask = parse.next_string()?
-            .replace(".", "\\.")
-            .replace("*", ".*")
-            .replace("?", ".")
-            .replace("[", "\\[")
-            .replace("]", "\\]");
-        let regex = match Regex::new(&ask) {
-            Ok(r) => r,
-            Err(e) => {
-                return Err(e.into());
-            }
-        };
-        Ok(Keys { command_size: parse.command_size(), regex })
+        let pattern = parse.next_bytes()?;
+        Ok(Keys { command_size: parse.command_size(), pattern })
     }
 }
 
@@ -39,7 +29,7 @@ impl Applicable for Keys {
         }
         let mut resp = vec![];
         for key in dst.db().keys().await {
-            if self.regex.is_match(&key) {
+            if &self.pattern[..] == b"*" || glob::matches(&self.pattern, key.as_bytes()) {
                 resp.push(Type::BulkString(key.into()));
             }
         }
@@ -48,9 +38,3 @@ impl Applicable for Keys {
         Ok(())
     }
 }
-
-impl PartialEq for Keys {
-    fn eq(&self, other: &Self) -> bool {
-        self.regex.as_str() == other.regex.as_str()
-    }
-}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct LIndex {
+    command_size: u64,
+    key: String,
+    index: i64,
+}
+
+impl TryFrom<&mut Parse> for LIndex {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let index = parse.next_signed_int()?;
+        Ok(LIndex { command_size: parse.command_size(), key, index })
+    }
+}
+
+#[async_trait]
+impl Applicable for LIndex {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().lindex(self.key, self.index).await {
+            Ok(Some(value)) => Type::BulkString(value),
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::bpop::parse_timeout;
+use crate::cmd::lmpop::parse_direction;
+use crate::connection::{Applicable, Connection};
+use crate::db::{Blocking, Served};
+use crate::encoder::Encoder;
+use crate::engine::list::Direction;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct LMove {
+    command_size: u64,
+    source: String,
+    destination: String,
+    from: Direction,
+    to: Direction,
+    blocking: bool,
+    timeout: Option<Duration>,
+}
+
+impl LMove {
+    /// Parse LMOVE, or BLMOVE with `blocking`.
+    pub(crate) fn parse_frames(parse: &mut Parse, blocking: bool) -> crate::Result<Self> {
+        let source = parse.next_string()?;
+        let destination = parse.next_string()?;
+        let from = parse_direction(&parse.next_string()?)?;
+        let to = parse_direction(&parse.next_string()?)?;
+        let timeout = if blocking { parse_timeout(&parse.next_string()?)? } else { None };
+        Ok(LMove { command_size: parse.command_size(), source, destination, from, to, blocking, timeout })
+    }
+}
+
+#[async_trait]
+impl Applicable for LMove {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let request = Blocking::Move(self.from, self.destination, self.to);
+        let (db, closed) = dst.blocking_db();
+        let resp = match db.block(vec![self.source], request, self.blocking, self.timeout, closed).await {
+            Ok(Some(Served::Moved(value))) => Type::BulkString(value),
+            Ok(_) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::bpop::parse_timeout;
+use crate::connection::{Applicable, Connection};
+use crate::db::{Blocking, Served};
+use crate::encoder::Encoder;
+use crate::engine::list::Direction;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct LMPop {
+    command_size: u64,
+    keys: Vec<String>,
+    direction: Direction,
+    count: u64,
+    blocking: bool,
+    timeout: Option<Duration>,
+}
+
+impl LMPop {
+    /// Parse LMPOP, or BLMPOP with `blocking`.
+    pub(crate) fn parse_frames(parse: &mut Parse, blocking: bool) -> crate::Result<Self> {
+        let timeout = if blocking { parse_timeout(&parse.next_string()?)? } else { None };
+        let numkeys: i64 = parse.next_string()?.parse().map_err(|_| "numkeys should be greater than 0")?;
+        if numkeys <= 0 {
+            return Err("numkeys should be greater than 0".into());
+        }
+        let mut keys = Vec::with_capacity(numkeys as usize);
+        for _ in 0..numkeys {
+            keys.push(parse.next_string()?);
+        }
+        let direction = parse_direction(&parse.next_string()?)?;
+        let mut count = 1;
+        match parse.next_string() {
+            Ok(option) if option.eq_ignore_ascii_case("COUNT") => {
+                count = parse.next_signed_int()?;
+                if count <= 0 {
+                    return Err("count should be greater than 0".into());
+                }
+            }
+            Ok(_) => return Err("syntax error".into()),
+            Err(parser::Error::EndOfStream) => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(LMPop { command_size: parse.command_size(), keys, direction, count: count as u64, blocking, timeout })
+    }
+
+    pub fn new(command_size: u64, keys: Vec<String>, direction: Direction, count: u64, blocking: bool, timeout: Option<Duration>) -> LMPop {
+        LMPop { command_size, keys, direction, count, blocking, timeout }
+    }
+}
+
+/// Parse the LEFT or RIGHT side of a list.
+pub(crate) fn parse_direction(side: &str) -> crate::Result<Direction> {
+    match side.to_uppercase().as_str() {
+        "LEFT" => Ok(Direction::Left),
+        "RIGHT" => Ok(Direction::Right),
+        _ => Err("syntax error".into()),
+    }
+}
+
+#[async_trait]
+impl Applicable for LMPop {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let request = Blocking::Pop(self.direction, self.count);
+        let (db, closed) = dst.blocking_db();
+        let resp = match db.block(self.keys, request, self.blocking, self.timeout, closed).await {
+            Ok(Some(Served::Popped(key, values))) => Type::Array(vec![
+                Type::BulkString(key.into()),
+                Type::Array(values.into_iter().map(Type::BulkString).collect()),
+            ]),
+            Ok(_) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct LRange {
+    command_size: u64,
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl TryFrom<&mut Parse> for LRange {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let start = parse.next_signed_int()?;
+        let stop = parse.next_signed_int()?;
+        Ok(LRange { command_size: parse.command_size(), key, start, stop })
+    }
+}
+
+#[async_trait]
+impl Applicable for LRange {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().lrange(self.key, self.start, self.stop).await {
+            Ok(values) => Type::Array(values.into_iter().map(Type::BulkString).collect()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct LRem {
+    command_size: u64,
+    key: String,
+    count: i64,
+    value: Bytes,
+}
+
+impl TryFrom<&mut Parse> for LRem {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let count = parse.next_signed_int()?;
+        let value = parse.next_bytes()?;
+        Ok(LRem { command_size: parse.command_size(), key, count, value })
+    }
+}
+
+#[async_trait]
+impl Applicable for LRem {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().lrem(self.key, self.count, self.value).await {
+            Ok(removed) => Type::Integer(removed as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct LTrim {
+    command_size: u64,
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl TryFrom<&mut Parse> for LTrim {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let start = parse.next_signed_int()?;
+        let stop = parse.next_signed_int()?;
+        Ok(LTrim { command_size: parse.command_size(), key, start, stop })
+    }
+}
+
+#[async_trait]
+impl Applicable for LTrim {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().ltrim(self.key, self.start, self.stop).await {
+            Ok(()) => Type::SimpleString("OK".to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
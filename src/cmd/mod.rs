@@ -9,15 +9,100 @@ mod psync;
 mod wait;
 mod config;
 mod keys;
+mod scan;
+mod exists;
+mod rename;
+mod copy;
+mod randomkey;
+mod dbsize;
+mod select;
+mod r#move;
+mod swapdb;
+mod flush;
 mod types;
 mod xadd;
 mod xrange;
 mod xread;
+mod xgroup;
+mod xreadgroup;
+mod xack;
+mod xpending;
+mod xclaim;
+mod xautoclaim;
+mod xlen;
+mod xdel;
+mod xtrim;
+mod xsetid;
+mod xinfo;
+mod push;
+mod pop;
+mod bpop;
+mod lmpop;
+mod lmove;
+mod lrange;
+mod llen;
+mod lindex;
+mod lset;
+mod lrem;
+mod ltrim;
+mod hset;
+mod hget;
+mod hmget;
+mod hdel;
+mod hgetall;
+mod hkeys;
+mod hvals;
+mod hlen;
+mod hexists;
+mod hincrby;
+mod sadd;
+mod srem;
+mod smembers;
+mod sismember;
+mod scard;
+mod setop;
+mod setopstore;
+mod zadd;
+mod zincrby;
+mod zrem;
+mod zscore;
+mod zcard;
+mod zrange;
+mod zrank;
+mod expire;
+mod ttl;
+mod persist;
+mod incr;
+mod incrbyfloat;
+mod append;
+mod strlen;
+mod getrange;
+mod setrange;
+mod getdel;
+mod getex;
+mod getset;
+mod setnx;
+mod setex;
+mod mget;
+mod mset;
+mod multi;
+mod exec;
+mod discard;
+mod watch;
+mod unwatch;
+mod subscribe;
+mod publish;
+mod pubsub;
+mod bgrewriteaof;
+mod save;
+mod lastsave;
 
 use std::convert::TryFrom;
 use async_trait::async_trait;
 use crate::resp::Type;
 use crate::connection::Applicable;
+use crate::engine::list::Direction;
+use crate::engine::set::Algebra;
 use crate::parser::Parse;
 
 #[derive(Debug, PartialEq)]
@@ -26,7 +111,7 @@ pub enum Command {
     Echo(echo::Echo),
     Set(set::Set),
     Get(get::Get),
-    Del(del::DEL),
+    Del(del::Del),
     Info(info::Info),
     Type(types::Type),
     ReplConf(replconf::ReplConf),
@@ -34,9 +119,98 @@ pub enum Command {
     Wait(wait::Wait),
     Config(config::Config),
     Keys(keys::Keys),
+    Scan(scan::Scan),
+    Exists(exists::Exists),
+    Rename(rename::Rename),
+    Copy(copy::Copy),
+    RandomKey(randomkey::RandomKey),
+    DBSize(dbsize::DBSize),
+    Select(select::Select),
+    Move(r#move::Move),
+    SwapDB(swapdb::SwapDB),
+    Flush(flush::Flush),
     XAdd(xadd::XAdd),
     XRange(xrange::XRange),
     XRead(xread::XRead),
+    XGroup(xgroup::XGroup),
+    XReadGroup(xreadgroup::XReadGroup),
+    XAck(xack::XAck),
+    XPending(xpending::XPending),
+    XClaim(xclaim::XClaim),
+    XAutoClaim(xautoclaim::XAutoClaim),
+    XLen(xlen::XLen),
+    XDel(xdel::XDel),
+    XTrim(xtrim::XTrim),
+    XSetID(xsetid::XSetID),
+    XInfo(xinfo::XInfo),
+    LPush(push::Push),
+    RPush(push::Push),
+    LPop(pop::Pop),
+    RPop(pop::Pop),
+    BPop(bpop::BPop),
+    LMPop(lmpop::LMPop),
+    LMove(lmove::LMove),
+    LRange(lrange::LRange),
+    LLen(llen::LLen),
+    LIndex(lindex::LIndex),
+    LSet(lset::LSet),
+    LRem(lrem::LRem),
+    LTrim(ltrim::LTrim),
+    HSet(hset::HSet),
+    HGet(hget::HGet),
+    HMGet(hmget::HMGet),
+    HDel(hdel::HDel),
+    HGetAll(hgetall::HGetAll),
+    HKeys(hkeys::HKeys),
+    HVals(hvals::HVals),
+    HLen(hlen::HLen),
+    HExists(hexists::HExists),
+    HIncrBy(hincrby::HIncrBy),
+    SAdd(sadd::SAdd),
+    SRem(srem::SRem),
+    SMembers(smembers::SMembers),
+    SIsMember(sismember::SIsMember),
+    SCard(scard::SCard),
+    SInter(setop::SetOp),
+    SUnion(setop::SetOp),
+    SDiff(setop::SetOp),
+    SInterStore(setopstore::SetOpStore),
+    SUnionStore(setopstore::SetOpStore),
+    SDiffStore(setopstore::SetOpStore),
+    ZAdd(zadd::ZAdd),
+    ZIncrBy(zincrby::ZIncrBy),
+    ZRem(zrem::ZRem),
+    ZScore(zscore::ZScore),
+    ZCard(zcard::ZCard),
+    ZRange(zrange::ZRange),
+    ZRank(zrank::ZRank),
+    Expire(expire::Expire),
+    Ttl(ttl::Ttl),
+    Persist(persist::Persist),
+    IncrBy(incr::IncrBy),
+    IncrByFloat(incrbyfloat::IncrByFloat),
+    Append(append::Append),
+    StrLen(strlen::StrLen),
+    GetRange(getrange::GetRange),
+    SetRange(setrange::SetRange),
+    GetDel(getdel::GetDel),
+    GetEx(getex::GetEx),
+    GetSet(getset::GetSet),
+    SetNx(setnx::SetNx),
+    SetEx(setex::SetEx),
+    MGet(mget::MGet),
+    MSet(mset::MSet),
+    Multi(multi::Multi),
+    Exec(exec::Exec),
+    Discard(discard::Discard),
+    Watch(watch::Watch),
+    Unwatch(unwatch::Unwatch),
+    Subscribe(subscribe::Subscribe),
+    Publish(publish::Publish),
+    PubSub(pubsub::PubSub),
+    BgRewriteAof(bgrewriteaof::BgRewriteAof),
+    Save(save::Save),
+    LastSave(lastsave::LastSave),
 }
 
 
@@ -50,7 +224,21 @@ impl TryFrom<Type> for Command {
             "ECHO" => Command::Echo((&mut parse).try_into()?),
             "SET" => Command::Set((&mut parse).try_into()?),
             "GET" => Command::Get((&mut parse).try_into()?),
-            "DEL" => Command::Del((&mut parse).try_into()?),
+            "DEL" => Command::Del(del::Del::parse_frames(&mut parse, false)?),
+            "UNLINK" => Command::Del(del::Del::parse_frames(&mut parse, true)?),
+            "EXISTS" => Command::Exists((&mut parse).try_into()?),
+            // without access time tracking TOUCH only counts the existing keys
+            "TOUCH" => Command::Exists((&mut parse).try_into()?),
+            "RENAME" => Command::Rename(rename::Rename::parse_frames(&mut parse, false)?),
+            "RENAMENX" => Command::Rename(rename::Rename::parse_frames(&mut parse, true)?),
+            "COPY" => Command::Copy((&mut parse).try_into()?),
+            "RANDOMKEY" => Command::RandomKey((&mut parse).try_into()?),
+            "DBSIZE" => Command::DBSize((&mut parse).try_into()?),
+            "SELECT" => Command::Select((&mut parse).try_into()?),
+            "MOVE" => Command::Move((&mut parse).try_into()?),
+            "SWAPDB" => Command::SwapDB((&mut parse).try_into()?),
+            "FLUSHDB" => Command::Flush(flush::Flush::parse_frames(&mut parse, false)?),
+            "FLUSHALL" => Command::Flush(flush::Flush::parse_frames(&mut parse, true)?),
             "INFO" => Command::Info((&mut parse).try_into()?),
             "TYPE" => Command::Type((&mut parse).try_into()?),
             "REPLCONF" => Command::ReplConf((&mut parse).try_into()?),
@@ -58,9 +246,117 @@ impl TryFrom<Type> for Command {
             "WAIT" => Command::Wait((&mut parse).try_into()?),
             "CONFIG" => Command::Config((&mut parse).try_into()?),
             "KEYS" => Command::Keys((&mut parse).try_into()?),
+            "SCAN" => Command::Scan(scan::Scan::parse_frames(&mut parse, scan::Style::Keys)?),
+            "HSCAN" => Command::Scan(scan::Scan::parse_frames(&mut parse, scan::Style::Hash)?),
+            "SSCAN" => Command::Scan(scan::Scan::parse_frames(&mut parse, scan::Style::Set)?),
+            "ZSCAN" => Command::Scan(scan::Scan::parse_frames(&mut parse, scan::Style::SortedSet)?),
             "XADD" => Command::XAdd((&mut parse).try_into()?),
-            "XRANGE" => Command::XRange((&mut parse).try_into()?),
+            "XRANGE" => Command::XRange(xrange::XRange::parse_frames(&mut parse, false)?),
+            "XREVRANGE" => Command::XRange(xrange::XRange::parse_frames(&mut parse, true)?),
             "XREAD" => Command::XRead((&mut parse).try_into()?),
+            "XGROUP" => Command::XGroup((&mut parse).try_into()?),
+            "XREADGROUP" => Command::XReadGroup((&mut parse).try_into()?),
+            "XACK" => Command::XAck((&mut parse).try_into()?),
+            "XPENDING" => Command::XPending((&mut parse).try_into()?),
+            "XCLAIM" => Command::XClaim((&mut parse).try_into()?),
+            "XAUTOCLAIM" => Command::XAutoClaim((&mut parse).try_into()?),
+            "XLEN" => Command::XLen((&mut parse).try_into()?),
+            "XDEL" => Command::XDel((&mut parse).try_into()?),
+            "XTRIM" => Command::XTrim((&mut parse).try_into()?),
+            "XSETID" => Command::XSetID((&mut parse).try_into()?),
+            "XINFO" => Command::XInfo((&mut parse).try_into()?),
+            "LPUSH" => Command::LPush(push::Push::parse_frames(&mut parse, Direction::Left)?),
+            "RPUSH" => Command::RPush(push::Push::parse_frames(&mut parse, Direction::Right)?),
+            "LPOP" => Command::LPop(pop::Pop::parse_frames(&mut parse, Direction::Left)?),
+            "RPOP" => Command::RPop(pop::Pop::parse_frames(&mut parse, Direction::Right)?),
+            "BLPOP" => Command::BPop(bpop::BPop::parse_frames(&mut parse, Direction::Left)?),
+            "BRPOP" => Command::BPop(bpop::BPop::parse_frames(&mut parse, Direction::Right)?),
+            "LMPOP" => Command::LMPop(lmpop::LMPop::parse_frames(&mut parse, false)?),
+            "BLMPOP" => Command::LMPop(lmpop::LMPop::parse_frames(&mut parse, true)?),
+            "LMOVE" => Command::LMove(lmove::LMove::parse_frames(&mut parse, false)?),
+            "BLMOVE" => Command::LMove(lmove::LMove::parse_frames(&mut parse, true)?),
+            "LRANGE" => Command::LRange((&mut parse).try_into()?),
+            "LLEN" => Command::LLen((&mut parse).try_into()?),
+            "LINDEX" => Command::LIndex((&mut parse).try_into()?),
+            "LSET" => Command::LSet((&mut parse).try_into()?),
+            "LREM" => Command::LRem((&mut parse).try_into()?),
+            "LTRIM" => Command::LTrim((&mut parse).try_into()?),
+            "HSET" => Command::HSet((&mut parse).try_into()?),
+            "HGET" => Command::HGet((&mut parse).try_into()?),
+            "HMGET" => Command::HMGet((&mut parse).try_into()?),
+            "HDEL" => Command::HDel((&mut parse).try_into()?),
+            "HGETALL" => Command::HGetAll((&mut parse).try_into()?),
+            "HKEYS" => Command::HKeys((&mut parse).try_into()?),
+            "HVALS" => Command::HVals((&mut parse).try_into()?),
+            "HLEN" => Command::HLen((&mut parse).try_into()?),
+            "HEXISTS" => Command::HExists((&mut parse).try_into()?),
+            "HINCRBY" => Command::HIncrBy((&mut parse).try_into()?),
+            "SADD" => Command::SAdd((&mut parse).try_into()?),
+            "SREM" => Command::SRem((&mut parse).try_into()?),
+            "SMEMBERS" => Command::SMembers((&mut parse).try_into()?),
+            "SISMEMBER" => Command::SIsMember((&mut parse).try_into()?),
+            "SCARD" => Command::SCard((&mut parse).try_into()?),
+            "SINTER" => Command::SInter(setop::SetOp::parse_frames(&mut parse, Algebra::Inter)?),
+            "SUNION" => Command::SUnion(setop::SetOp::parse_frames(&mut parse, Algebra::Union)?),
+            "SDIFF" => Command::SDiff(setop::SetOp::parse_frames(&mut parse, Algebra::Diff)?),
+            "SINTERSTORE" => Command::SInterStore(setopstore::SetOpStore::parse_frames(&mut parse, Algebra::Inter)?),
+            "SUNIONSTORE" => Command::SUnionStore(setopstore::SetOpStore::parse_frames(&mut parse, Algebra::Union)?),
+            "SDIFFSTORE" => Command::SDiffStore(setopstore::SetOpStore::parse_frames(&mut parse, Algebra::Diff)?),
+            "ZADD" => Command::ZAdd((&mut parse).try_into()?),
+            "ZINCRBY" => Command::ZIncrBy((&mut parse).try_into()?),
+            "ZREM" => Command::ZRem((&mut parse).try_into()?),
+            "ZSCORE" => Command::ZScore((&mut parse).try_into()?),
+            "ZCARD" => Command::ZCard((&mut parse).try_into()?),
+            "ZRANGE" => Command::ZRange(zrange::ZRange::parse_frames(&mut parse, zrange::Style::Range)?),
+            "ZREVRANGE" => Command::ZRange(zrange::ZRange::parse_frames(&mut parse, zrange::Style::RevRange)?),
+            "ZRANGEBYSCORE" => Command::ZRange(zrange::ZRange::parse_frames(&mut parse, zrange::Style::RangeByScore)?),
+            "ZREVRANGEBYSCORE" => Command::ZRange(zrange::ZRange::parse_frames(&mut parse, zrange::Style::RevRangeByScore)?),
+            "ZRANGEBYLEX" => Command::ZRange(zrange::ZRange::parse_frames(&mut parse, zrange::Style::RangeByLex)?),
+            "ZREVRANGEBYLEX" => Command::ZRange(zrange::ZRange::parse_frames(&mut parse, zrange::Style::RevRangeByLex)?),
+            "ZRANK" => Command::ZRank(zrank::ZRank::parse_frames(&mut parse, false)?),
+            "ZREVRANK" => Command::ZRank(zrank::ZRank::parse_frames(&mut parse, true)?),
+            "EXPIRE" => Command::Expire(expire::Expire::parse_frames(&mut parse, expire::Style::Expire)?),
+            "PEXPIRE" => Command::Expire(expire::Expire::parse_frames(&mut parse, expire::Style::PExpire)?),
+            "EXPIREAT" => Command::Expire(expire::Expire::parse_frames(&mut parse, expire::Style::ExpireAt)?),
+            "PEXPIREAT" => Command::Expire(expire::Expire::parse_frames(&mut parse, expire::Style::PExpireAt)?),
+            "TTL" => Command::Ttl(ttl::Ttl::parse_frames(&mut parse, ttl::Style::Ttl)?),
+            "PTTL" => Command::Ttl(ttl::Ttl::parse_frames(&mut parse, ttl::Style::PTtl)?),
+            "EXPIRETIME" => Command::Ttl(ttl::Ttl::parse_frames(&mut parse, ttl::Style::ExpireTime)?),
+            "PEXPIRETIME" => Command::Ttl(ttl::Ttl::parse_frames(&mut parse, ttl::Style::PExpireTime)?),
+            "PERSIST" => Command::Persist((&mut parse).try_into()?),
+            "INCR" => Command::IncrBy(incr::IncrBy::parse_frames(&mut parse, incr::Style::Incr)?),
+            "DECR" => Command::IncrBy(incr::IncrBy::parse_frames(&mut parse, incr::Style::Decr)?),
+            "INCRBY" => Command::IncrBy(incr::IncrBy::parse_frames(&mut parse, incr::Style::IncrBy)?),
+            "DECRBY" => Command::IncrBy(incr::IncrBy::parse_frames(&mut parse, incr::Style::DecrBy)?),
+            "INCRBYFLOAT" => Command::IncrByFloat((&mut parse).try_into()?),
+            "APPEND" => Command::Append((&mut parse).try_into()?),
+            "STRLEN" => Command::StrLen((&mut parse).try_into()?),
+            "GETRANGE" | "SUBSTR" => Command::GetRange((&mut parse).try_into()?),
+            "SETRANGE" => Command::SetRange((&mut parse).try_into()?),
+            "GETDEL" => Command::GetDel((&mut parse).try_into()?),
+            "GETEX" => Command::GetEx((&mut parse).try_into()?),
+            "GETSET" => Command::GetSet((&mut parse).try_into()?),
+            "SETNX" => Command::SetNx((&mut parse).try_into()?),
+            "SETEX" => Command::SetEx(setex::SetEx::parse_frames(&mut parse, false)?),
+            "PSETEX" => Command::SetEx(setex::SetEx::parse_frames(&mut parse, true)?),
+            "MGET" => Command::MGet((&mut parse).try_into()?),
+            "MSET" => Command::MSet(mset::MSet::parse_frames(&mut parse, false)?),
+            "MSETNX" => Command::MSet(mset::MSet::parse_frames(&mut parse, true)?),
+            "MULTI" => Command::Multi((&mut parse).try_into()?),
+            "EXEC" => Command::Exec((&mut parse).try_into()?),
+            "DISCARD" => Command::Discard((&mut parse).try_into()?),
+            "WATCH" => Command::Watch((&mut parse).try_into()?),
+            "UNWATCH" => Command::Unwatch((&mut parse).try_into()?),
+            "SUBSCRIBE" => Command::Subscribe(subscribe::Subscribe::parse_frames(&mut parse, subscribe::Style::Subscribe)?),
+            "UNSUBSCRIBE" => Command::Subscribe(subscribe::Subscribe::parse_frames(&mut parse, subscribe::Style::Unsubscribe)?),
+            "PSUBSCRIBE" => Command::Subscribe(subscribe::Subscribe::parse_frames(&mut parse, subscribe::Style::PSubscribe)?),
+            "PUNSUBSCRIBE" => Command::Subscribe(subscribe::Subscribe::parse_frames(&mut parse, subscribe::Style::PUnsubscribe)?),
+            "PUBLISH" => Command::Publish((&mut parse).try_into()?),
+            "PUBSUB" => Command::PubSub((&mut parse).try_into()?),
+            "BGREWRITEAOF" => Command::BgRewriteAof((&mut parse).try_into()?),
+            "SAVE" => Command::Save(save::Save::parse_frames(&mut parse, false)?),
+            "BGSAVE" => Command::Save(save::Save::parse_frames(&mut parse, true)?),
+            "LASTSAVE" => Command::LastSave((&mut parse).try_into()?),
             _ => return Err(format!("Unsupported command: {}", command_name).into())
         };
         parse.finish()?;
@@ -68,6 +364,24 @@ impl TryFrom<Type> for Command {
     }
 }
 
+impl Command {
+    /// Commands that control a transaction run right away instead of being queued.
+    pub(crate) fn is_transaction_control(&self) -> bool {
+        matches!(self, Command::Multi(_) | Command::Exec(_) | Command::Discard(_) | Command::Watch(_) | Command::Unwatch(_))
+    }
+
+    /// Commands that wait on other connections can not run while a transaction holds the DB lock.
+    pub(crate) fn allowed_in_transaction(&self) -> bool {
+        // the snapshot of a rewrite would hold the writes of the transaction before they are logged
+        !matches!(self, Command::PSync(_) | Command::ReplConf(_) | Command::Wait(_) | Command::BgRewriteAof(_))
+    }
+
+    /// Commands accepted while the connection is subscribed to a channel or a pattern.
+    pub(crate) fn allowed_when_subscribed(&self) -> bool {
+        matches!(self, Command::Subscribe(_) | Command::Ping(_))
+    }
+}
+
 #[async_trait]
 impl Applicable for Command {
     async fn apply(self, dst: &mut crate::connection::Connection) -> crate::Result<()> {
@@ -84,9 +398,98 @@ impl Applicable for Command {
             Command::Wait(wait) => wait.apply(dst).await,
             Command::Config(config) => config.apply(dst).await,
             Command::Keys(keys) => keys.apply(dst).await,
+            Command::Scan(scan) => scan.apply(dst).await,
+            Command::Exists(exists) => exists.apply(dst).await,
+            Command::Rename(rename) => rename.apply(dst).await,
+            Command::Copy(copy) => copy.apply(dst).await,
+            Command::RandomKey(randomkey) => randomkey.apply(dst).await,
+            Command::DBSize(dbsize) => dbsize.apply(dst).await,
+            Command::Select(select) => select.apply(dst).await,
+            Command::Move(r#move) => r#move.apply(dst).await,
+            Command::SwapDB(swapdb) => swapdb.apply(dst).await,
+            Command::Flush(flush) => flush.apply(dst).await,
             Command::XAdd(xadd) => xadd.apply(dst).await,
             Command::XRange(xrange) => xrange.apply(dst).await,
             Command::XRead(xread) => xread.apply(dst).await,
+            Command::XGroup(xgroup) => xgroup.apply(dst).await,
+            Command::XReadGroup(xreadgroup) => xreadgroup.apply(dst).await,
+            Command::XAck(xack) => xack.apply(dst).await,
+            Command::XPending(xpending) => xpending.apply(dst).await,
+            Command::XClaim(xclaim) => xclaim.apply(dst).await,
+            Command::XAutoClaim(xautoclaim) => xautoclaim.apply(dst).await,
+            Command::XLen(xlen) => xlen.apply(dst).await,
+            Command::XDel(xdel) => xdel.apply(dst).await,
+            Command::XTrim(xtrim) => xtrim.apply(dst).await,
+            Command::XSetID(xsetid) => xsetid.apply(dst).await,
+            Command::XInfo(xinfo) => xinfo.apply(dst).await,
+            Command::LPush(push) => push.apply(dst).await,
+            Command::RPush(push) => push.apply(dst).await,
+            Command::LPop(pop) => pop.apply(dst).await,
+            Command::RPop(pop) => pop.apply(dst).await,
+            Command::BPop(bpop) => bpop.apply(dst).await,
+            Command::LMPop(lmpop) => lmpop.apply(dst).await,
+            Command::LMove(lmove) => lmove.apply(dst).await,
+            Command::LRange(lrange) => lrange.apply(dst).await,
+            Command::LLen(llen) => llen.apply(dst).await,
+            Command::LIndex(lindex) => lindex.apply(dst).await,
+            Command::LSet(lset) => lset.apply(dst).await,
+            Command::LRem(lrem) => lrem.apply(dst).await,
+            Command::LTrim(ltrim) => ltrim.apply(dst).await,
+            Command::HSet(hset) => hset.apply(dst).await,
+            Command::HGet(hget) => hget.apply(dst).await,
+            Command::HMGet(hmget) => hmget.apply(dst).await,
+            Command::HDel(hdel) => hdel.apply(dst).await,
+            Command::HGetAll(hgetall) => hgetall.apply(dst).await,
+            Command::HKeys(hkeys) => hkeys.apply(dst).await,
+            Command::HVals(hvals) => hvals.apply(dst).await,
+            Command::HLen(hlen) => hlen.apply(dst).await,
+            Command::HExists(hexists) => hexists.apply(dst).await,
+            Command::HIncrBy(hincrby) => hincrby.apply(dst).await,
+            Command::SAdd(sadd) => sadd.apply(dst).await,
+            Command::SRem(srem) => srem.apply(dst).await,
+            Command::SMembers(smembers) => smembers.apply(dst).await,
+            Command::SIsMember(sismember) => sismember.apply(dst).await,
+            Command::SCard(scard) => scard.apply(dst).await,
+            Command::SInter(setop) => setop.apply(dst).await,
+            Command::SUnion(setop) => setop.apply(dst).await,
+            Command::SDiff(setop) => setop.apply(dst).await,
+            Command::SInterStore(setopstore) => setopstore.apply(dst).await,
+            Command::SUnionStore(setopstore) => setopstore.apply(dst).await,
+            Command::SDiffStore(setopstore) => setopstore.apply(dst).await,
+            Command::ZAdd(zadd) => zadd.apply(dst).await,
+            Command::ZIncrBy(zincrby) => zincrby.apply(dst).await,
+            Command::ZRem(zrem) => zrem.apply(dst).await,
+            Command::ZScore(zscore) => zscore.apply(dst).await,
+            Command::ZCard(zcard) => zcard.apply(dst).await,
+            Command::ZRange(zrange) => zrange.apply(dst).await,
+            Command::ZRank(zrank) => zrank.apply(dst).await,
+            Command::Expire(expire) => expire.apply(dst).await,
+            Command::Ttl(ttl) => ttl.apply(dst).await,
+            Command::Persist(persist) => persist.apply(dst).await,
+            Command::IncrBy(incr) => incr.apply(dst).await,
+            Command::IncrByFloat(incrbyfloat) => incrbyfloat.apply(dst).await,
+            Command::Append(append) => append.apply(dst).await,
+            Command::StrLen(strlen) => strlen.apply(dst).await,
+            Command::GetRange(getrange) => getrange.apply(dst).await,
+            Command::SetRange(setrange) => setrange.apply(dst).await,
+            Command::GetDel(getdel) => getdel.apply(dst).await,
+            Command::GetEx(getex) => getex.apply(dst).await,
+            Command::GetSet(getset) => getset.apply(dst).await,
+            Command::SetNx(setnx) => setnx.apply(dst).await,
+            Command::SetEx(setex) => setex.apply(dst).await,
+            Command::MGet(mget) => mget.apply(dst).await,
+            Command::MSet(mset) => mset.apply(dst).await,
+            Command::Multi(multi) => multi.apply(dst).await,
+            Command::Exec(exec) => exec.apply(dst).await,
+            Command::Discard(discard) => discard.apply(dst).await,
+            Command::Watch(watch) => watch.apply(dst).await,
+            Command::Unwatch(unwatch) => unwatch.apply(dst).await,
+            Command::Subscribe(subscribe) => subscribe.apply(dst).await,
+            Command::Publish(publish) => publish.apply(dst).await,
+            Command::PubSub(pubsub) => pubsub.apply(dst).await,
+            Command::BgRewriteAof(bgrewriteaof) => bgrewriteaof.apply(dst).await,
+            Command::Save(save) => save.apply(dst).await,
+            Command::LastSave(lastsave) => lastsave.apply(dst).await,
         }
     }
 }
@@ -113,9 +516,214 @@ mod tests {
         assert_eq!(Command::try_from(input).unwrap(), expected);
     }
 
+    #[test]
+    fn parse_lpush() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("LPUSH")),
+            Type::BulkString(Bytes::from("list")),
+            Type::BulkString(Bytes::from("a")),
+            Type::BulkString(Bytes::from("b")),
+        ]);
+        let expected = Command::LPush(push::Push::new(39, "list", vec![Bytes::from("a"), Bytes::from("b")], Direction::Left));
+        assert_eq!(Command::try_from(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_hset() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("HSET")),
+            Type::BulkString(Bytes::from("user")),
+            Type::BulkString(Bytes::from("name")),
+            Type::BulkString(Bytes::from("bob")),
+        ]);
+        let expected = Command::HSet(hset::HSet::new(43, "user", vec![(Bytes::from("name"), Bytes::from("bob"))]));
+        assert_eq!(Command::try_from(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_set_incompatible_options() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("SET")),
+            Type::BulkString(Bytes::from("key")),
+            Type::BulkString(Bytes::from("value")),
+            Type::BulkString(Bytes::from("NX")),
+            Type::BulkString(Bytes::from("XX")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_watch_without_keys() {
+        let input = Type::Array(vec![Type::BulkString(Bytes::from("WATCH"))]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_mset_missing_value() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("MSET")),
+            Type::BulkString(Bytes::from("a")),
+            Type::BulkString(Bytes::from("1")),
+            Type::BulkString(Bytes::from("b")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_getex_incompatible_options() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("GETEX")),
+            Type::BulkString(Bytes::from("key")),
+            Type::BulkString(Bytes::from("EX")),
+            Type::BulkString(Bytes::from("10")),
+            Type::BulkString(Bytes::from("PERSIST")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_decrby_overflow() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("DECRBY")),
+            Type::BulkString(Bytes::from("counter")),
+            Type::BulkString(Bytes::from("-9223372036854775808")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_expire_incompatible_options() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("EXPIRE")),
+            Type::BulkString(Bytes::from("key")),
+            Type::BulkString(Bytes::from("10")),
+            Type::BulkString(Bytes::from("NX")),
+            Type::BulkString(Bytes::from("GT")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_scan_type_only_for_keyspace() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("HSCAN")),
+            Type::BulkString(Bytes::from("key")),
+            Type::BulkString(Bytes::from("0")),
+            Type::BulkString(Bytes::from("TYPE")),
+            Type::BulkString(Bytes::from("string")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_copy_unknown_option() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("COPY")),
+            Type::BulkString(Bytes::from("src")),
+            Type::BulkString(Bytes::from("dst")),
+            Type::BulkString(Bytes::from("KEEPTTL")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_select_negative_index() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("SELECT")),
+            Type::BulkString(Bytes::from("-1")),
+        ]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_unsubscribe_without_channels() {
+        let input = Type::Array(vec![Type::BulkString(Bytes::from("UNSUBSCRIBE"))]);
+        let expected = Command::Subscribe(subscribe::Subscribe::new(22, subscribe::Style::Unsubscribe, vec![]));
+        assert_eq!(Command::try_from(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_blmpop_count() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("BLMPOP")),
+            Type::BulkString(Bytes::from("0.5")),
+            Type::BulkString(Bytes::from("2")),
+            Type::BulkString(Bytes::from("a")),
+            Type::BulkString(Bytes::from("b")),
+            Type::BulkString(Bytes::from("RIGHT")),
+            Type::BulkString(Bytes::from("COUNT")),
+            Type::BulkString(Bytes::from("3")),
+        ]);
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let timeout = Some(std::time::Duration::from_millis(500));
+        let expected = Command::LMPop(lmpop::LMPop::new(75, keys, Direction::Right, 3, true, timeout));
+        assert_eq!(Command::try_from(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_xreadgroup() {
+        let input = Type::Array(vec![
+            Type::BulkString(Bytes::from("XREADGROUP")),
+            Type::BulkString(Bytes::from("GROUP")),
+            Type::BulkString(Bytes::from("workers")),
+            Type::BulkString(Bytes::from("alice")),
+            Type::BulkString(Bytes::from("COUNT")),
+            Type::BulkString(Bytes::from("10")),
+            Type::BulkString(Bytes::from("NOACK")),
+            Type::BulkString(Bytes::from("STREAMS")),
+            Type::BulkString(Bytes::from("a")),
+            Type::BulkString(Bytes::from("b")),
+            Type::BulkString(Bytes::from(">")),
+            Type::BulkString(Bytes::from("5")),
+        ]);
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let expected = Command::XReadGroup(xreadgroup::XReadGroup::new(128, "workers", "alice", keys, vec![None, Some((5, 0))], Some(10), None, true));
+        assert_eq!(Command::try_from(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_exclusive_bounds() {
+        assert_eq!(xrange::parse_bound("(5", true).unwrap(), Some((5, Some(1))));
+        assert_eq!(xrange::parse_bound("(5", false).unwrap(), Some((5, Some(u64::MAX - 1))));
+        assert_eq!(xrange::parse_bound("(5-0", false).unwrap(), Some((4, Some(u64::MAX))));
+        assert_eq!(xrange::parse_bound("+", false).unwrap(), None);
+        assert!(xrange::parse_bound("(-", false).is_err());
+        let args = ["XREVRANGE", "s", "+", "(0-0"];
+        let input = Type::Array(args.iter().map(|arg| Type::BulkString(Bytes::from(*arg))).collect());
+        assert!(Command::try_from(input).is_ok());
+        let args = ["XRANGE", "s", "(+", "+"];
+        let input = Type::Array(args.iter().map(|arg| Type::BulkString(Bytes::from(*arg))).collect());
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn bgrewriteaof_not_allowed_in_transaction() {
+        let input = Type::Array(vec![Type::BulkString(Bytes::from("BGREWRITEAOF"))]);
+        let command = Command::try_from(input).unwrap();
+        assert!(!command.allowed_in_transaction());
+    }
+
+    #[test]
+    fn parse_save() {
+        let input = Type::Array(vec![Type::BulkString(Bytes::from("BGSAVE"))]);
+        assert!(matches!(Command::try_from(input).unwrap(), Command::Save(_)));
+        let input = Type::Array(vec![Type::BulkString(Bytes::from("SAVE")), Type::BulkString(Bytes::from("now"))]);
+        assert!(Command::try_from(input).is_err());
+    }
+
+    #[test]
+    fn parse_xadd_limit_requires_approximate() {
+        let args = ["XADD", "s", "MAXLEN", "10", "LIMIT", "5", "*", "f", "v"];
+        let input = Type::Array(args.iter().map(|arg| Type::BulkString(Bytes::from(*arg))).collect());
+        assert!(Command::try_from(input).is_err());
+        let args = ["XADD", "s", "MAXLEN", "~", "10", "LIMIT", "5", "*", "f", "v"];
+        let input = Type::Array(args.iter().map(|arg| Type::BulkString(Bytes::from(*arg))).collect());
+        assert!(Command::try_from(input).is_ok());
+    }
+
     #[test]
     fn parse_invalid_command() {
         let input = Type::Array(vec![]);
-        assert_eq!(Command::try_from(input).is_err(), true);
+        assert!(Command::try_from(input).is_err());
     }
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::select;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Move {
+    command_size: u64,
+    key: String,
+    index: usize,
+}
+
+impl TryFrom<&mut Parse> for Move {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let index = select::parse_index(parse)?;
+        Ok(Move { command_size: parse.command_size(), key, index })
+    }
+}
+
+#[async_trait]
+impl Applicable for Move {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().move_key(self.key, self.index).await {
+            Ok(moved) => Type::Integer(moved as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct MSet {
+    command_size: u64,
+    pairs: Vec<(String, Bytes)>,
+    nx: bool,
+}
+
+impl MSet {
+    /// Parse MSET, or MSETNX with `nx`.
+    pub(crate) fn parse_frames(parse: &mut Parse, nx: bool) -> crate::Result<Self> {
+        let mut pairs = vec![(parse.next_string()?, parse.next_bytes()?)];
+        loop {
+            let key = match parse.next_string() {
+                Ok(key) => key,
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            pairs.push((key, parse.next_bytes()?));
+        }
+        Ok(MSet { command_size: parse.command_size(), pairs, nx })
+    }
+}
+
+#[async_trait]
+impl Applicable for MSet {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let written = dst.db().mset(self.pairs, self.nx).await;
+        let resp = if self.nx {
+            Type::Integer(written as i64)
+        } else {
+            Type::SimpleString("OK".to_string())
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Multi {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for Multi {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(Multi { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for Multi {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = if dst.begin_transaction() {
+            Type::SimpleString("OK".to_string())
+        } else {
+            Type::SimpleError("ERR MULTI calls can not be nested".to_string())
+        };
+        if !dst.is_master_link().await {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Persist {
+    command_size: u64,
+    key: String,
+}
+
+impl TryFrom<&mut Parse> for Persist {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        Ok(Persist { command_size: parse.command_size(), key })
+    }
+}
+
+#[async_trait]
+impl Applicable for Persist {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let done = dst.db().persist(self.key).await;
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&Type::Integer(done as i64)).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -33,9 +33,17 @@ impl Applicable for Ping {
         if !dst.db().role().await.is_master() && dst.writeable() {
             return Ok(());
         }
-        let resp = match self.msg {
-            None => Type::SimpleString("PONG".to_string()),
-            Some(msg) => Type::BulkString(msg),
+        // a subscribed connection gets the reply in the shape of a pushed message
+        let resp = if dst.is_subscribed() {
+            Type::Array(vec![
+                Type::BulkString("pong".into()),
+                Type::BulkString(self.msg.unwrap_or_default()),
+            ])
+        } else {
+            match self.msg {
+                None => Type::SimpleString("PONG".to_string()),
+                Some(msg) => Type::BulkString(msg),
+            }
         };
         dst.write_all(Encoder::encode(&resp).as_slice()).await?;
         dst.flush().await?;
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::list::Direction;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Pop {
+    command_size: u64,
+    key: String,
+    count: Option<u64>,
+    direction: Direction,
+}
+
+impl Pop {
+    pub(crate) fn parse_frames(parse: &mut Parse, direction: Direction) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let count = match parse.next_int() {
+            Ok(count) => Some(count),
+            Err(parser::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Pop { command_size: parse.command_size(), key, count, direction })
+    }
+}
+
+#[async_trait]
+impl Applicable for Pop {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().pop(self.key, self.count, self.direction).await {
+            Ok(Some(values)) => {
+                match self.count {
+                    Some(_) => Type::Array(values.into_iter().map(Type::BulkString).collect()),
+                    None => values.into_iter().next().map(Type::BulkString).unwrap_or(Type::Null),
+                }
+            }
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
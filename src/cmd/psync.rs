@@ -25,9 +25,8 @@ impl TryFrom<&mut Parse> for PSync {
         if master_id.to_uppercase() != "?" {
             id = Some(master_id);
         }
-        match parse.next_int() {
-            Ok(o) => { offset = Some(o); }
-            _ => {}
+        if let Ok(o) = parse.next_int() {
+            offset = Some(o);
         }
         Ok(PSync { id, offset })
     }
@@ -42,7 +41,7 @@ impl Applicable for PSync {
             let (mut now_offset, mut need_offset) = (0u64, 0u64);
             let resp = Type::SimpleString(format!("FULLRESYNC {} {}", role.id(), now_offset));
             dst.write_all(Encoder::encode(&resp).as_slice()).await?;
-            let db = dst.db().clone();
+            let mut db = dst.db().clone();
             let mut rx = db.add_slave(key.clone(), dst).await?;
             let sender = async {
                 loop {
@@ -102,10 +101,8 @@ pub async fn update_offset(dst: &mut Connection, timeout: Option<Duration>) -> c
     tokio::select! {
         cmd = dst.read_frame() => {
             if let Ok(Some(cmd)) = cmd {
-                if let Ok(cmd) = cmd::Command::try_from(cmd) {
-                    if let cmd::Command::ReplConf(replconf) = cmd {
-                        offset = replconf.offset();
-                    }
+                if let Ok(cmd::Command::ReplConf(replconf)) = cmd::Command::try_from(cmd) {
+                    offset = replconf.offset();
                 }
             }
         }
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Publish {
+    command_size: u64,
+    channel: Bytes,
+    message: Bytes,
+}
+
+impl TryFrom<&mut Parse> for Publish {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let channel = parse.next_bytes()?;
+        let message = parse.next_bytes()?;
+        Ok(Publish { command_size: parse.command_size(), channel, message })
+    }
+}
+
+#[async_trait]
+impl Applicable for Publish {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        // replicas deliver the messages published on their master to their own subscribers
+        let receivers = dst.db().publish(self.channel, self.message).await;
+        if !dst.is_master_link().await {
+            dst.write_all(Encoder::encode(&Type::Integer(receivers as i64)).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct PubSub {
+    command_size: u64,
+    subcommand: Subcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum Subcommand {
+    Channels(Option<Bytes>),
+    NumSub(Vec<Bytes>),
+    NumPat,
+}
+
+impl TryFrom<&mut Parse> for PubSub {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let name = parse.next_string()?.to_uppercase();
+        let subcommand = match name.as_str() {
+            "CHANNELS" => match parse.next_bytes() {
+                Ok(pattern) => Subcommand::Channels(Some(pattern)),
+                Err(parser::Error::EndOfStream) => Subcommand::Channels(None),
+                Err(err) => return Err(err.into()),
+            },
+            "NUMSUB" => {
+                let mut channels = Vec::new();
+                loop {
+                    match parse.next_bytes() {
+                        Ok(channel) => channels.push(channel),
+                        Err(parser::Error::EndOfStream) => break,
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Subcommand::NumSub(channels)
+            }
+            "NUMPAT" => Subcommand::NumPat,
+            _ => return Err(format!("unknown subcommand '{}'. Try PUBSUB HELP.", name.to_lowercase()).into()),
+        };
+        Ok(PubSub { command_size: parse.command_size(), subcommand })
+    }
+}
+
+#[async_trait]
+impl Applicable for PubSub {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let pubsub = dst.db().pubsub();
+        let resp = match self.subcommand {
+            Subcommand::Channels(pattern) => {
+                let channels = pubsub.channels(pattern.as_deref());
+                Type::Array(channels.into_iter().map(Type::BulkString).collect())
+            }
+            Subcommand::NumSub(channels) => {
+                let mut arr = Vec::new();
+                for channel in channels.into_iter() {
+                    let count = pubsub.numsub(&channel);
+                    arr.push(Type::BulkString(channel));
+                    arr.push(Type::Integer(count as i64));
+                }
+                Type::Array(arr)
+            }
+            Subcommand::NumPat => Type::Integer(pubsub.numpat() as i64),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
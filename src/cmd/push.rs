@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::list::Direction;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Push {
+    command_size: u64,
+    key: String,
+    values: Vec<Bytes>,
+    direction: Direction,
+}
+
+impl Push {
+    pub(crate) fn parse_frames(parse: &mut Parse, direction: Direction) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let mut values = vec![parse.next_bytes()?];
+        loop {
+            match parse.next_bytes() {
+                Ok(value) => values.push(value),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Push { command_size: parse.command_size(), key, values, direction })
+    }
+
+    pub fn new(command_size: u64, key: impl ToString, values: Vec<Bytes>, direction: Direction) -> Push {
+        Push {
+            command_size,
+            key: key.to_string(),
+            values,
+            direction,
+        }
+    }
+}
+
+#[async_trait]
+impl Applicable for Push {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().push(self.key, self.values, self.direction).await {
+            Ok(len) => Type::Integer(len as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
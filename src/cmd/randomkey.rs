@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct RandomKey {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for RandomKey {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(RandomKey { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for RandomKey {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().random_key().await {
+            Some(key) => Type::BulkString(key.into()),
+            None => Type::Null,
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
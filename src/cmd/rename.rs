@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Rename {
+    command_size: u64,
+    src: String,
+    dst: String,
+    nx: bool,
+}
+
+impl Rename {
+    /// Parse RENAME, or RENAMENX with `nx`.
+    pub(crate) fn parse_frames(parse: &mut Parse, nx: bool) -> crate::Result<Self> {
+        let src = parse.next_string()?;
+        let dst = parse.next_string()?;
+        Ok(Rename { command_size: parse.command_size(), src, dst, nx })
+    }
+}
+
+#[async_trait]
+impl Applicable for Rename {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().rename(self.src, self.dst, self.nx).await {
+            Ok(renamed) if self.nx => Type::Integer(renamed as i64),
+            Ok(_) => Type::SimpleString("OK".to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
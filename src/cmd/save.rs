@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Save {
+    command_size: u64,
+    background: bool,
+}
+
+impl Save {
+    /// Parse SAVE, or BGSAVE with `background`.
+    pub(crate) fn parse_frames(parse: &mut Parse, background: bool) -> crate::Result<Self> {
+        Ok(Save { command_size: parse.command_size(), background })
+    }
+}
+
+#[async_trait]
+impl Applicable for Save {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = if self.background {
+            dst.db().bgsave().await.map(|()| "Background saving started")
+        } else {
+            dst.db().save().await.map(|()| "OK")
+        };
+        let resp = match resp {
+            Ok(status) => Type::SimpleString(status.to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+use crate::utils::{glob, strings};
+
+#[derive(Debug, PartialEq)]
+pub struct Scan {
+    command_size: u64,
+    style: Style,
+    key: Option<String>,
+    cursor: u64,
+    pattern: Option<Bytes>,
+    count: u64,
+    type_name: Option<String>,
+}
+
+/// What a SCAN-like request iterates over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Keys,
+    Hash,
+    Set,
+    SortedSet,
+}
+
+impl Scan {
+    pub(crate) fn parse_frames(parse: &mut Parse, style: Style) -> crate::Result<Self> {
+        let key = match style {
+            Style::Keys => None,
+            _ => Some(parse.next_string()?),
+        };
+        let cursor = parse.next_string()?.parse().map_err(|_| "invalid cursor")?;
+        let (mut pattern, mut count, mut type_name) = (None, 10, None);
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match option.as_str() {
+                "MATCH" => pattern = Some(parse.next_bytes()?),
+                "COUNT" => {
+                    count = parse.next_signed_int()?;
+                    if count < 1 {
+                        return Err("syntax error".into());
+                    }
+                }
+                "TYPE" if style == Style::Keys => type_name = Some(parse.next_string()?),
+                _ => return Err("syntax error".into()),
+            }
+        }
+        Ok(Scan { command_size: parse.command_size(), style, key, cursor, pattern, count: count as u64, type_name })
+    }
+
+    fn matches(&self, element: &[u8]) -> bool {
+        match &self.pattern {
+            Some(pattern) => &pattern[..] == b"*" || glob::matches(pattern, element),
+            None => true,
+        }
+    }
+}
+
+#[async_trait]
+impl Applicable for Scan {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let key = self.key.clone().unwrap_or_default();
+        let page = match self.style {
+            Style::Keys => {
+                let (next, keys) = dst.db().scan(self.cursor, self.count, self.type_name.clone()).await;
+                let keys = keys.into_iter()
+                    .filter(|key| self.matches(key.as_bytes()))
+                    .map(|key| Type::BulkString(key.into()))
+                    .collect();
+                Ok((next, keys))
+            }
+            Style::Hash => dst.db().hscan(key, self.cursor, self.count).await.map(|(next, pairs)| {
+                let mut arr = Vec::new();
+                for (field, value) in pairs.into_iter().filter(|(field, _)| self.matches(field)) {
+                    arr.push(Type::BulkString(field));
+                    arr.push(Type::BulkString(value));
+                }
+                (next, arr)
+            }),
+            Style::Set => dst.db().sscan(key, self.cursor, self.count).await.map(|(next, members)| {
+                let members = members.into_iter()
+                    .filter(|member| self.matches(member))
+                    .map(Type::BulkString)
+                    .collect();
+                (next, members)
+            }),
+            Style::SortedSet => dst.db().zscan(key, self.cursor, self.count).await.map(|(next, pairs)| {
+                let mut arr = Vec::new();
+                for (member, score) in pairs.into_iter().filter(|(member, _)| self.matches(member)) {
+                    arr.push(Type::BulkString(member));
+                    arr.push(Type::BulkString(strings::format_float(score).into()));
+                }
+                (next, arr)
+            }),
+        };
+        let resp = match page {
+            Ok((next, arr)) => Type::Array(vec![Type::BulkString(Bytes::from(next.to_string())), Type::Array(arr)]),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Select {
+    command_size: u64,
+    index: usize,
+}
+
+impl TryFrom<&mut Parse> for Select {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let index = parse_index(parse)?;
+        Ok(Select { command_size: parse.command_size(), index })
+    }
+}
+
+/// Parse a database index, whether it exists is checked against the configured number of databases.
+pub(crate) fn parse_index(parse: &mut Parse) -> crate::Result<usize> {
+    let index: i64 = parse.next_string()?.parse().map_err(|_| "value is not an integer or out of range")?;
+    if index < 0 {
+        return Err("DB index is out of range".into());
+    }
+    Ok(index as usize)
+}
+
+#[async_trait]
+impl Applicable for Select {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().select(self.index) {
+            Ok(()) => Type::SimpleString("OK".to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if !dst.is_master_link().await {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
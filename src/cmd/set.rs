@@ -1,9 +1,10 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use async_trait::async_trait;
 use bytes::Bytes;
 use tokio::io::AsyncWriteExt;
 use crate::connection::{Applicable, Connection};
 use crate::encoder::Encoder;
+use crate::engine::string::SetOptions;
 use crate::parser::{self, Parse};
 use crate::resp::Type;
 
@@ -12,7 +13,15 @@ pub struct Set {
     command_size: u64,
     key: String,
     value: Bytes,
-    expire: Option<Duration>,
+    expire: Option<Expire>,
+    options: SetOptions,
+}
+
+/// An expiration given relative to now (EX/PX) or as an absolute Unix time (EXAT/PXAT), in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Expire {
+    After(i64),
+    At(i64),
 }
 
 impl TryFrom<&mut Parse> for Set {
@@ -21,20 +30,59 @@ impl TryFrom<&mut Parse> for Set {
         let key = parse.next_string()?;
         let value = parse.next_bytes()?;
         let mut expire = None;
-        match parse.next_string() {
-            Ok(s) if s.to_uppercase() == "EX" => {
-                let secs = parse.next_int()?;
-                expire = Some(Duration::from_secs(secs));
-            }
-            Ok(s) if s.to_uppercase() == "PX" => {
-                let ms = parse.next_int()?;
-                expire = Some(Duration::from_millis(ms));
+        let mut options = SetOptions::default();
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match option.as_str() {
+                "NX" if !options.xx => options.nx = true,
+                "XX" if !options.nx => options.xx = true,
+                "GET" => options.get = true,
+                "KEEPTTL" if expire.is_none() => options.keep_ttl = true,
+                "EX" | "PX" | "EXAT" | "PXAT" if expire.is_none() && !options.keep_ttl => {
+                    expire = Some(Expire::parse_frames(parse, &option, "set")?);
+                }
+                _ => return Err("syntax error".into()),
             }
-            Ok(_) => return Err("currently `SET` only supports the expiration option".into()),
-            Err(parser::Error::EndOfStream) => {}
-            Err(err) => return Err(err.into()),
         }
-        Ok(Set { command_size: parse.command_size(), key, value, expire })
+        Ok(Set { command_size: parse.command_size(), key, value, expire, options })
+    }
+}
+
+impl Expire {
+    /// Parse the time following an EX/PX/EXAT/PXAT `option` of `command`.
+    pub(crate) fn parse_frames(parse: &mut Parse, option: &str, command: &str) -> crate::Result<Self> {
+        let invalid = || format!("invalid expire time in '{}' command", command);
+        let time = parse.next_signed_int()?;
+        if time <= 0 {
+            return Err(invalid().into());
+        }
+        let ms = match option {
+            "EX" | "EXAT" => time.checked_mul(1000).ok_or_else(invalid)?,
+            _ => time,
+        };
+        Ok(match option {
+            "EX" | "PX" => Expire::After(ms),
+            _ => Expire::At(ms),
+        })
+    }
+
+    /// The absolute expiration time, `None` when it does not fit in milliseconds since the Unix epoch.
+    pub(crate) fn resolve(self) -> Option<SystemTime> {
+        let ms = match self {
+            Expire::After(ms) => {
+                let now = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as i64;
+                ms.checked_add(now)?
+            }
+            Expire::At(ms) => ms,
+        };
+        Some(SystemTime::UNIX_EPOCH + Duration::from_millis(ms as u64))
     }
 }
 
@@ -45,9 +93,20 @@ impl Applicable for Set {
             return Ok(());
         }
         dst.db().role().await.add_offset(self.command_size);
-        dst.db().set(self.key, self.value, self.expire).await;
+        let expire = self.expire.map(Expire::resolve);
+        let resp = match expire {
+            Some(None) => Type::SimpleError("ERR invalid expire time in 'set' command".to_string()),
+            _ => match dst.db().set(self.key, self.value, expire.flatten(), self.options).await {
+                Ok((_, old)) if self.options.get => match old {
+                    Some(old) => Type::BulkString(old),
+                    None => Type::Null,
+                },
+                Ok((true, _)) => Type::SimpleString("OK".to_string()),
+                Ok((false, _)) => Type::Null,
+                Err(e) => Type::SimpleError(e.to_string()),
+            },
+        };
         if dst.db().role().await.is_master() {
-            let resp = Type::SimpleString("OK".to_string());
             dst.write_all(Encoder::encode(&resp).as_slice()).await?;
             dst.flush().await?;
         }
@@ -61,7 +120,8 @@ impl Set {
             command_size,
             key: key.to_string(),
             value,
-            expire,
+            expire: expire.map(|duration| Expire::After(duration.as_millis() as i64)),
+            options: SetOptions::default(),
         }
     }
 }
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::set::Expire;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::string::SetOptions;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct SetEx {
+    command_size: u64,
+    key: String,
+    expire: Expire,
+    value: Bytes,
+    millis: bool,
+}
+
+impl SetEx {
+    /// Parse SETEX, or PSETEX when the time is given in `millis`.
+    pub(crate) fn parse_frames(parse: &mut Parse, millis: bool) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let expire = if millis {
+            Expire::parse_frames(parse, "PX", "psetex")?
+        } else {
+            Expire::parse_frames(parse, "EX", "setex")?
+        };
+        let value = parse.next_bytes()?;
+        Ok(SetEx { command_size: parse.command_size(), key, expire, value, millis })
+    }
+}
+
+#[async_trait]
+impl Applicable for SetEx {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match self.expire.resolve() {
+            Some(when) => match dst.db().set(self.key, self.value, Some(when), SetOptions::default()).await {
+                Ok(_) => Type::SimpleString("OK".to_string()),
+                Err(e) => Type::SimpleError(e.to_string()),
+            },
+            None => {
+                let name = if self.millis { "psetex" } else { "setex" };
+                Type::SimpleError(format!("ERR invalid expire time in '{}' command", name))
+            }
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
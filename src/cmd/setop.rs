@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::set::Algebra;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct SetOp {
+    command_size: u64,
+    keys: Vec<String>,
+    algebra: Algebra,
+}
+
+impl SetOp {
+    pub(crate) fn parse_frames(parse: &mut Parse, algebra: Algebra) -> crate::Result<Self> {
+        let mut keys = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(SetOp { command_size: parse.command_size(), keys, algebra })
+    }
+}
+
+#[async_trait]
+impl Applicable for SetOp {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().set_combine(self.keys, self.algebra).await {
+            Ok(members) => Type::Array(members.into_iter().map(Type::BulkString).collect()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::set::Algebra;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct SetOpStore {
+    command_size: u64,
+    destination: String,
+    keys: Vec<String>,
+    algebra: Algebra,
+}
+
+impl SetOpStore {
+    pub(crate) fn parse_frames(parse: &mut Parse, algebra: Algebra) -> crate::Result<Self> {
+        let destination = parse.next_string()?;
+        let mut keys = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(SetOpStore { command_size: parse.command_size(), destination, keys, algebra })
+    }
+}
+
+#[async_trait]
+impl Applicable for SetOpStore {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().set_combine_store(self.destination, self.keys, self.algebra).await {
+            Ok(len) => Type::Integer(len as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct SetRange {
+    command_size: u64,
+    key: String,
+    offset: u64,
+    value: Bytes,
+}
+
+impl TryFrom<&mut Parse> for SetRange {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let offset = u64::try_from(parse.next_signed_int()?).map_err(|_| "offset is out of range")?;
+        let value = parse.next_bytes()?;
+        Ok(SetRange { command_size: parse.command_size(), key, offset, value })
+    }
+}
+
+#[async_trait]
+impl Applicable for SetRange {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().setrange(self.key, self.offset, self.value).await {
+            Ok(len) => Type::Integer(len as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct SIsMember {
+    command_size: u64,
+    key: String,
+    member: Bytes,
+}
+
+impl TryFrom<&mut Parse> for SIsMember {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let member = parse.next_bytes()?;
+        Ok(SIsMember { command_size: parse.command_size(), key, member })
+    }
+}
+
+#[async_trait]
+impl Applicable for SIsMember {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().sismember(self.key, self.member).await {
+            Ok(exists) => Type::Integer(exists as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
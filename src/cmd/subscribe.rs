@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Subscribe {
+    command_size: u64,
+    style: Style,
+    targets: Vec<Bytes>,
+}
+
+/// Which subscriptions a request changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Subscribe,
+    Unsubscribe,
+    PSubscribe,
+    PUnsubscribe,
+}
+
+impl Subscribe {
+    pub(crate) fn parse_frames(parse: &mut Parse, style: Style) -> crate::Result<Self> {
+        let mut targets = Vec::new();
+        // subscribing needs at least one target, unsubscribing without any drops them all
+        if matches!(style, Style::Subscribe | Style::PSubscribe) {
+            targets.push(parse.next_bytes()?);
+        }
+        loop {
+            match parse.next_bytes() {
+                Ok(target) => targets.push(target),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Subscribe { command_size: parse.command_size(), style, targets })
+    }
+
+    pub fn new(command_size: u64, style: Style, targets: Vec<Bytes>) -> Subscribe {
+        Subscribe { command_size, style, targets }
+    }
+}
+
+#[async_trait]
+impl Applicable for Subscribe {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let subscriber = dst.subscriber();
+        let kind = match self.style {
+            Style::Subscribe => "subscribe",
+            Style::Unsubscribe => "unsubscribe",
+            Style::PSubscribe => "psubscribe",
+            Style::PUnsubscribe => "punsubscribe",
+        };
+        let targets = match self.style {
+            Style::Unsubscribe if self.targets.is_empty() => subscriber.channels(),
+            Style::PUnsubscribe if self.targets.is_empty() => subscriber.patterns(),
+            _ => self.targets,
+        };
+        let mut replies = Vec::new();
+        for target in targets.iter() {
+            match self.style {
+                Style::Subscribe => subscriber.subscribe(target.clone()),
+                Style::Unsubscribe => subscriber.unsubscribe(target),
+                Style::PSubscribe => subscriber.psubscribe(target.clone()),
+                Style::PUnsubscribe => subscriber.punsubscribe(target),
+            };
+            replies.push(Type::Array(vec![
+                Type::BulkString(kind.into()),
+                Type::BulkString(target.clone()),
+                Type::Integer(subscriber.count() as i64),
+            ]));
+        }
+        // unsubscribing from nothing still confirms it
+        if replies.is_empty() {
+            replies.push(Type::Array(vec![
+                Type::BulkString(kind.into()),
+                Type::Null,
+                Type::Integer(subscriber.count() as i64),
+            ]));
+        }
+        for reply in replies.iter() {
+            dst.write_all(Encoder::encode(reply).as_slice()).await?;
+        }
+        dst.flush().await?;
+        Ok(())
+    }
+}
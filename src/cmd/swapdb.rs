@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::select;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct SwapDB {
+    command_size: u64,
+    a: usize,
+    b: usize,
+}
+
+impl TryFrom<&mut Parse> for SwapDB {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let a = select::parse_index(parse)?;
+        let b = select::parse_index(parse)?;
+        Ok(SwapDB { command_size: parse.command_size(), a, b })
+    }
+}
+
+#[async_trait]
+impl Applicable for SwapDB {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().swapdb(self.a, self.b).await {
+            Ok(()) => Type::SimpleString("OK".to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::SystemTime;
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Ttl {
+    command_size: u64,
+    key: String,
+    style: Style,
+}
+
+/// Whether the expiration is reported as a remaining TTL or as an absolute Unix time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Ttl,
+    PTtl,
+    ExpireTime,
+    PExpireTime,
+}
+
+impl Ttl {
+    pub(crate) fn parse_frames(parse: &mut Parse, style: Style) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        Ok(Ttl { command_size: parse.command_size(), key, style })
+    }
+}
+
+#[async_trait]
+impl Applicable for Ttl {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().expiration(self.key).await {
+            None => Type::Integer(-2),
+            Some(None) => Type::Integer(-1),
+            Some(Some(when)) => {
+                // expirations are kept as `Instant`s, round away the drift of converting them back
+                let unix_millis = (when.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_micros() as i64 + 500) / 1000;
+                let value = match self.style {
+                    Style::Ttl => {
+                        let ttl = when.duration_since(SystemTime::now()).unwrap_or_default();
+                        (ttl.as_millis() as i64 + 500) / 1000
+                    }
+                    Style::PTtl => when.duration_since(SystemTime::now()).unwrap_or_default().as_millis() as i64,
+                    Style::ExpireTime => unix_millis / 1000,
+                    Style::PExpireTime => unix_millis,
+                };
+                Type::Integer(value)
+            }
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
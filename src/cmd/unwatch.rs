@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct Unwatch {
+    command_size: u64,
+}
+
+impl TryFrom<&mut Parse> for Unwatch {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        Ok(Unwatch { command_size: parse.command_size() })
+    }
+}
+
+#[async_trait]
+impl Applicable for Unwatch {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        dst.unwatch().await;
+        if !dst.is_master_link().await {
+            dst.write_all(Encoder::encode(&Type::SimpleString("OK".to_string())).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
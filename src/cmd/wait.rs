@@ -31,7 +31,7 @@ impl Applicable for Wait {
         let mut sync = Synchronization::new(self.timeout, min(dst.db().slave_count().await, self.num_replicas));
         dst.db().sync_replication(sync.clone()).await;
         sync.wait().await;
-        let resp = Type::Integer(sync.have_finish());
+        let resp = Type::Integer(sync.have_finish() as i64);
         dst.write_all(Encoder::encode(&resp).as_slice()).await?;
         dst.flush().await?;
         Ok(())
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XAck {
+    command_size: u64,
+    key: String,
+    group: String,
+    ids: Vec<(u64, u64)>,
+}
+
+impl TryFrom<&mut Parse> for XAck {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let (key, group) = (parse.next_string()?, parse.next_string()?);
+        let mut ids = vec![parse_exact_id(&parse.next_string()?)?];
+        loop {
+            match parse.next_string() {
+                Ok(id) => ids.push(parse_exact_id(&id)?),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(XAck { command_size: parse.command_size(), key, group, ids })
+    }
+}
+
+#[async_trait]
+impl Applicable for XAck {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().xack(self.key, self.group, self.ids).await {
+            Ok(acked) => Type::Integer(acked as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
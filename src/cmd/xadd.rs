@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use bytes::Bytes;
 use tokio::io::AsyncWriteExt;
+use crate::cmd::xtrim::{parse_limit, parse_trim};
 use crate::connection::{Applicable, Connection};
 use crate::encoder::Encoder;
+use crate::engine::stream::Trim;
 use crate::parser;
 use crate::parser::Parse;
 use crate::resp::Type;
@@ -13,16 +15,30 @@ pub struct XAdd {
     key: String,
     id: Option<(u64, Option<u64>)>,
     field: Vec<(Bytes, Bytes)>,
+    nomkstream: bool,
+    trim: Option<Trim>,
 }
 
 impl TryFrom<&mut Parse> for XAdd {
     type Error = crate::Error;
     fn try_from(parse: &mut Parse) -> crate::Result<Self> {
         let key = parse.next_string()?;
-        let id_pattern = parse.next_string()?;
-        let id;
-        if id_pattern == "*" {
-            id = None;
+        let (mut nomkstream, mut trim) = (false, None);
+        // the options come before the ID
+        let id_pattern = loop {
+            let arg = parse.next_string()?;
+            match arg.to_uppercase().as_str() {
+                "NOMKSTREAM" => nomkstream = true,
+                "MAXLEN" | "MINID" => trim = Some(parse_trim(parse, &arg)?),
+                "LIMIT" => match trim.as_mut() {
+                    Some(trim) => parse_limit(parse, trim)?,
+                    None => return Err("syntax error".into()),
+                },
+                _ => break arg,
+            }
+        };
+        let id = if id_pattern == "*" {
+            None
         } else {
             let id_parts: Vec<&str> = id_pattern.split('-').collect();
             if id_parts.len() != 2 {
@@ -33,8 +49,8 @@ impl TryFrom<&mut Parse> for XAdd {
             } else {
                 Some(id_parts[1].parse().map_err(|_| "Invalid seq format")?)
             };
-            id = Some((id_parts[0].parse().map_err(|_| "Invalid time format")?, seq));
-        }
+            Some((id_parts[0].parse().map_err(|_| "Invalid time format")?, seq))
+        };
         let mut field = Vec::new();
         loop {
             match parse.next_bytes() {
@@ -46,7 +62,7 @@ impl TryFrom<&mut Parse> for XAdd {
                 Err(err) => return Err(err.into()),
             }
         }
-        Ok(XAdd { command_size: parse.command_size(), key, id, field })
+        Ok(XAdd { command_size: parse.command_size(), key, id, field, nomkstream, trim })
     }
 }
 
@@ -57,8 +73,9 @@ impl Applicable for XAdd {
             return Ok(());
         }
         dst.db().role().await.add_offset(self.command_size);
-        let resp = match dst.db().xadd(self.key, self.id, self.field).await {
-            Ok((time, seq)) => Type::SimpleString(format!("{}-{}", time, seq)),
+        let resp = match dst.db().xadd(self.key, self.id, self.field, self.nomkstream, self.trim).await {
+            Ok(Some((time, seq))) => Type::SimpleString(format!("{}-{}", time, seq)),
+            Ok(None) => Type::Null,
             Err(e) => Type::SimpleError(e.to_string()),
         };
         dst.write_all(Encoder::encode(&resp).as_slice()).await?;
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XAutoClaim {
+    command_size: u64,
+    key: String,
+    group: String,
+    consumer: String,
+    min_idle: u64,
+    start: (u64, u64),
+    count: u64,
+    justid: bool,
+}
+
+impl TryFrom<&mut Parse> for XAutoClaim {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let (key, group, consumer) = (parse.next_string()?, parse.next_string()?, parse.next_string()?);
+        let min_idle = parse.next_int()?;
+        let start = parse.next_string()?;
+        let start = if start == "-" { (0, 0) } else { parse_exact_id(&start)? };
+        let (mut count, mut justid) = (100, false);
+        loop {
+            match parse.next_string() {
+                Ok(arg) => match arg.to_uppercase().as_str() {
+                    "COUNT" => count = parse.next_int()?,
+                    "JUSTID" => justid = true,
+                    _ => return Err("syntax error".into()),
+                },
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        if count == 0 {
+            return Err("COUNT must be > 0".into());
+        }
+        Ok(XAutoClaim { command_size: parse.command_size(), key, group, consumer, min_idle, start, count, justid })
+    }
+}
+
+#[async_trait]
+impl Applicable for XAutoClaim {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().xautoclaim(self.key, self.group, self.consumer, self.min_idle, self.start, self.count, self.justid).await {
+            Ok((next, entries, deleted)) => {
+                let id = |(time, seq): (u64, u64)| Type::BulkString(format!("{}-{}", time, seq).into());
+                let entries = entries.into_iter()
+                    .map(|entry| if self.justid { id(entry.id()) } else { entry.encode() })
+                    .collect();
+                Type::Array(vec![id(next), Type::Array(entries), Type::Array(deleted.into_iter().map(id).collect())])
+            }
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
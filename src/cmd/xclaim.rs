@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::stream::ClaimOptions;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XClaim {
+    command_size: u64,
+    key: String,
+    group: String,
+    consumer: String,
+    min_idle: u64,
+    ids: Vec<(u64, u64)>,
+    options: ClaimOptions,
+}
+
+impl TryFrom<&mut Parse> for XClaim {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let (key, group, consumer) = (parse.next_string()?, parse.next_string()?, parse.next_string()?);
+        let min_idle = parse.next_int()?;
+        let mut ids = vec![parse_exact_id(&parse.next_string()?)?];
+        let mut options = ClaimOptions::default();
+        loop {
+            let arg = match parse.next_string() {
+                Ok(arg) => arg,
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match arg.to_uppercase().as_str() {
+                "IDLE" => options.idle = Some(parse.next_int()?),
+                "TIME" => options.time = Some(parse.next_int()?),
+                "RETRYCOUNT" => options.retry_count = Some(parse.next_int()?),
+                "FORCE" => options.force = true,
+                "JUSTID" => options.justid = true,
+                "LASTID" => options.last_id = Some(parse_exact_id(&parse.next_string()?)?),
+                // the IDs come before the options
+                _ if options == ClaimOptions::default() => ids.push(parse_exact_id(&arg)?),
+                _ => return Err(format!("Unrecognized XCLAIM option '{}'", arg).into()),
+            }
+        }
+        Ok(XClaim { command_size: parse.command_size(), key, group, consumer, min_idle, ids, options })
+    }
+}
+
+#[async_trait]
+impl Applicable for XClaim {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let justid = self.options.justid;
+        let resp = match dst.db().xclaim(self.key, self.group, self.consumer, self.min_idle, self.ids, self.options).await {
+            Ok(entries) => Type::Array(entries.into_iter().map(|entry| {
+                if justid {
+                    let (time, seq) = entry.id();
+                    Type::BulkString(format!("{}-{}", time, seq).into())
+                } else {
+                    entry.encode()
+                }
+            }).collect()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
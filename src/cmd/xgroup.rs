@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XGroup {
+    command_size: u64,
+    subcommand: Subcommand,
+}
+
+/// The XGROUP subcommands, on a key and a group. A `None` ID stands for `$`, the last entry of the stream,
+/// and the optional count is the number of entries the group read.
+#[derive(Debug, PartialEq)]
+enum Subcommand {
+    Create(String, String, Option<(u64, u64)>, bool, Option<u64>),
+    SetID(String, String, Option<(u64, u64)>, Option<u64>),
+    Destroy(String, String),
+    CreateConsumer(String, String, String),
+    DelConsumer(String, String, String),
+}
+
+impl TryFrom<&mut Parse> for XGroup {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let name = parse.next_string()?;
+        let subcommand = match name.to_uppercase().as_str() {
+            "CREATE" => {
+                let (key, group) = (parse.next_string()?, parse.next_string()?);
+                let id = parse_group_id(&parse.next_string()?)?;
+                let (mut mkstream, mut entries_read) = (false, None);
+                loop {
+                    match parse.next_string() {
+                        Ok(option) => match option.to_uppercase().as_str() {
+                            "MKSTREAM" => mkstream = true,
+                            "ENTRIESREAD" => entries_read = Some(parse.next_int()?),
+                            _ => return Err("syntax error".into()),
+                        },
+                        Err(parser::Error::EndOfStream) => break,
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Subcommand::Create(key, group, id, mkstream, entries_read)
+            }
+            "SETID" => {
+                let (key, group) = (parse.next_string()?, parse.next_string()?);
+                let id = parse_group_id(&parse.next_string()?)?;
+                let entries_read = match parse.next_string() {
+                    Ok(option) if option.to_uppercase() == "ENTRIESREAD" => Some(parse.next_int()?),
+                    Ok(_) => return Err("syntax error".into()),
+                    Err(parser::Error::EndOfStream) => None,
+                    Err(err) => return Err(err.into()),
+                };
+                Subcommand::SetID(key, group, id, entries_read)
+            }
+            "DESTROY" => Subcommand::Destroy(parse.next_string()?, parse.next_string()?),
+            "CREATECONSUMER" => Subcommand::CreateConsumer(parse.next_string()?, parse.next_string()?, parse.next_string()?),
+            "DELCONSUMER" => Subcommand::DelConsumer(parse.next_string()?, parse.next_string()?, parse.next_string()?),
+            _ => return Err(format!("unknown subcommand '{}'. Try XGROUP HELP.", name).into()),
+        };
+        parse.finish()?;
+        Ok(XGroup { command_size: parse.command_size(), subcommand })
+    }
+}
+
+fn parse_group_id(id: &str) -> crate::Result<Option<(u64, u64)>> {
+    if id == "$" {
+        return Ok(None);
+    }
+    Ok(Some(parse_exact_id(id)?))
+}
+
+#[async_trait]
+impl Applicable for XGroup {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let ok = |result: Result<(), _>| result.map(|_| Type::SimpleString("OK".to_string()));
+        let result = match self.subcommand {
+            Subcommand::Create(key, group, id, mkstream, entries_read) => {
+                ok(dst.db().xgroup_create(key, group, id, mkstream, entries_read).await)
+            }
+            Subcommand::SetID(key, group, id, entries_read) => ok(dst.db().xgroup_setid(key, group, id, entries_read).await),
+            Subcommand::Destroy(key, group) => dst.db().xgroup_destroy(key, group).await.map(|destroyed| Type::Integer(destroyed as i64)),
+            Subcommand::CreateConsumer(key, group, consumer) => {
+                dst.db().xgroup_create_consumer(key, group, consumer).await.map(|created| Type::Integer(created as i64))
+            }
+            Subcommand::DelConsumer(key, group, consumer) => {
+                dst.db().xgroup_del_consumer(key, group, consumer).await.map(|pending| Type::Integer(pending as i64))
+            }
+        };
+        let resp = result.unwrap_or_else(|e| Type::SimpleError(e.to_string()));
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XInfo {
+    command_size: u64,
+    subcommand: Subcommand,
+}
+
+/// The XINFO subcommands, on a key and for CONSUMERS a group.
+#[derive(Debug, PartialEq)]
+enum Subcommand {
+    Stream(String),
+    Groups(String),
+    Consumers(String, String),
+}
+
+impl TryFrom<&mut Parse> for XInfo {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let name = parse.next_string()?;
+        let subcommand = match name.to_uppercase().as_str() {
+            "STREAM" => Subcommand::Stream(parse.next_string()?),
+            "GROUPS" => Subcommand::Groups(parse.next_string()?),
+            "CONSUMERS" => Subcommand::Consumers(parse.next_string()?, parse.next_string()?),
+            _ => return Err(format!("unknown subcommand '{}'. Try XINFO HELP.", name).into()),
+        };
+        parse.finish()?;
+        Ok(XInfo { command_size: parse.command_size(), subcommand })
+    }
+}
+
+fn id(id: (u64, u64)) -> Type {
+    Type::BulkString(format!("{}-{}", id.0, id.1).into())
+}
+
+fn optional(value: Option<u64>) -> Type {
+    match value {
+        Some(value) => Type::Integer(value as i64),
+        None => Type::Null,
+    }
+}
+
+/// Reply the fields of a report as a flat array of names and values.
+fn fields(fields: Vec<(&str, Type)>) -> Type {
+    let mut arr = Vec::with_capacity(fields.len() * 2);
+    for (name, value) in fields.into_iter() {
+        arr.push(Type::BulkString(name.to_string().into()));
+        arr.push(value);
+    }
+    Type::Array(arr)
+}
+
+#[async_trait]
+impl Applicable for XInfo {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let result = match self.subcommand {
+            Subcommand::Stream(key) => dst.db().xinfo_stream(key).await.map(|info| fields(vec![
+                ("length", Type::Integer(info.length as i64)),
+                ("last-generated-id", id(info.last_id)),
+                ("max-deleted-entry-id", id(info.max_deleted_id)),
+                ("entries-added", Type::Integer(info.added as i64)),
+                ("recorded-first-entry-id", id(info.first_id)),
+                ("groups", Type::Integer(info.groups as i64)),
+                ("first-entry", info.first_entry.map(|entry| entry.encode()).unwrap_or(Type::Null)),
+                ("last-entry", info.last_entry.map(|entry| entry.encode()).unwrap_or(Type::Null)),
+            ])),
+            Subcommand::Groups(key) => dst.db().xinfo_groups(key).await.map(|groups| {
+                Type::Array(groups.into_iter().map(|group| fields(vec![
+                    ("name", Type::BulkString(group.name.into())),
+                    ("consumers", Type::Integer(group.consumers as i64)),
+                    ("pending", Type::Integer(group.pending as i64)),
+                    ("last-delivered-id", id(group.last_id)),
+                    ("entries-read", optional(group.entries_read)),
+                    ("lag", optional(group.lag)),
+                ])).collect())
+            }),
+            Subcommand::Consumers(key, group) => dst.db().xinfo_consumers(key, group).await.map(|consumers| {
+                Type::Array(consumers.into_iter().map(|consumer| fields(vec![
+                    ("name", Type::BulkString(consumer.name.into())),
+                    ("pending", Type::Integer(consumer.pending as i64)),
+                    ("idle", Type::Integer(consumer.idle as i64)),
+                    ("inactive", Type::Integer(consumer.inactive.map(|inactive| inactive as i64).unwrap_or(-1))),
+                ])).collect())
+            }),
+        };
+        let resp = result.unwrap_or_else(|e| Type::SimpleError(e.to_string()));
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
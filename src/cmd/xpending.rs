@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_bound;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::stream;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XPending {
+    command_size: u64,
+    key: String,
+    group: String,
+    // without a range only the summary of the pending entries is replied
+    range: Option<Range>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Range {
+    min_idle: Option<u64>,
+    start: Option<stream::ID>,
+    end: Option<stream::ID>,
+    count: u64,
+    consumer: Option<String>,
+}
+
+impl TryFrom<&mut Parse> for XPending {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let (key, group) = (parse.next_string()?, parse.next_string()?);
+        let mut start = match parse.next_string() {
+            Ok(start) => start,
+            Err(parser::Error::EndOfStream) => return Ok(XPending { command_size: parse.command_size(), key, group, range: None }),
+            Err(err) => return Err(err.into()),
+        };
+        let mut min_idle = None;
+        if start.to_uppercase() == "IDLE" {
+            min_idle = Some(parse.next_int()?);
+            start = parse.next_string()?;
+        }
+        let start = parse_bound(&start, true)?;
+        let end = parse_bound(&parse.next_string()?, false)?;
+        let count = parse.next_int()?;
+        let consumer = match parse.next_string() {
+            Ok(consumer) => Some(consumer),
+            Err(parser::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+        parse.finish()?;
+        let range = Some(Range { min_idle, start, end, count, consumer });
+        Ok(XPending { command_size: parse.command_size(), key, group, range })
+    }
+}
+
+#[async_trait]
+impl Applicable for XPending {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match self.range {
+            None => match dst.db().xpending_summary(self.key, self.group).await {
+                Ok(summary) => {
+                    let (min, max) = match summary.bounds {
+                        Some((min, max)) => (encode_id(min), encode_id(max)),
+                        None => (Type::Null, Type::Null),
+                    };
+                    let consumers = if summary.consumers.is_empty() {
+                        Type::Null
+                    } else {
+                        Type::Array(summary.consumers.into_iter().map(|(consumer, count)| Type::Array(vec![
+                            Type::BulkString(consumer.into()),
+                            Type::BulkString(count.to_string().into()),
+                        ])).collect())
+                    };
+                    Type::Array(vec![Type::Integer(summary.count as i64), min, max, consumers])
+                }
+                Err(e) => Type::SimpleError(e.to_string()),
+            },
+            Some(range) => match dst.db().xpending(self.key, self.group, range.min_idle, range.start, range.end, range.count, range.consumer).await {
+                Ok(pending) => Type::Array(pending.into_iter().map(|pending| Type::Array(vec![
+                    encode_id(pending.id),
+                    Type::BulkString(pending.consumer.into()),
+                    Type::Integer(pending.idle as i64),
+                    Type::Integer(pending.count as i64),
+                ])).collect()),
+                Err(e) => Type::SimpleError(e.to_string()),
+            },
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
+
+fn encode_id(id: (u64, u64)) -> Type {
+    Type::BulkString(format!("{}-{}", id.0, id.1).into())
+}
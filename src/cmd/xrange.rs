@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use tokio::io::AsyncWriteExt;
 use crate::connection::{Applicable, Connection};
 use crate::encoder::Encoder;
+use crate::engine::stream;
 use crate::parser;
 use crate::parser::Parse;
 use crate::resp::Type;
@@ -10,67 +11,80 @@ use crate::resp::Type;
 pub struct XRange {
     command_size: u64,
     key: String,
-    start: Option<(u64, Option<u64>)>,
-    end: Option<(u64, Option<u64>)>,
+    start: Option<stream::ID>,
+    end: Option<stream::ID>,
     count: Option<u64>,
+    rev: bool,
 }
 
-impl TryFrom<&mut Parse> for XRange {
-    type Error = crate::Error;
-    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+impl XRange {
+    /// Parse XRANGE, or XREVRANGE with `rev`, which takes the end of the range first.
+    pub(crate) fn parse_frames(parse: &mut Parse, rev: bool) -> crate::Result<Self> {
         let key = parse.next_string()?;
-        let start_pattern = parse.next_string()?;
-        let start;
-        if start_pattern == "-" {
-            start = None;
-        } else {
-            let start_parts: Vec<&str> = start_pattern.split('-').collect();
-            if start_parts.len() == 1 {
-                start = Some((start_parts[0].parse().map_err(|_| "Invalid time format")?, None));
-            } else if start_parts.len() != 2 {
-                return Err("Invalid start format".into());
-            } else {
-                start = Some((start_parts[0].parse().map_err(|_| "Invalid time format")?,
-                              Some(start_parts[1].parse().map_err(|_| "Invalid time format")?)));
-            }
-        }
-        let end_pattern = parse.next_string()?;
-        let end;
-        if end_pattern == "+" {
-            end = None;
-        } else {
-            let end_parts: Vec<&str> = end_pattern.split('-').collect();
-            if end_parts.len() == 1 {
-                end = Some((end_parts[0].parse().map_err(|_| "Invalid time format")?, None));
-            } else if end_parts.len() != 2 {
-                return Err("Invalid end format".into());
-            } else {
-                end = Some((end_parts[0].parse().map_err(|_| "Invalid time format")?,
-                            Some(end_parts[1].parse().map_err(|_| "Invalid time format")?)));
-            }
-        }
+        let (first, second) = (parse.next_string()?, parse.next_string()?);
+        let (start, end) = if rev { (second, first) } else { (first, second) };
+        let (start, end) = (parse_bound(&start, true)?, parse_bound(&end, false)?);
         let count = match parse.next_string() {
-            Ok(c) => {
-                if c.to_uppercase() == "COUNT" {
-                    Some(parse.next_int()?)
-                } else {
-                    return Err("Invalid count format".into());
-                }
-            }
+            Ok(option) if option.to_uppercase() == "COUNT" => Some(parse.next_int()?),
+            Ok(_) => return Err("syntax error".into()),
             Err(parser::Error::EndOfStream) => None,
             Err(err) => return Err(err.into()),
         };
-        Ok(XRange { command_size: parse.command_size(), key, start, end, count })
+        parse.finish()?;
+        Ok(XRange { command_size: parse.command_size(), key, start, end, count, rev })
+    }
+}
+
+/// Parse the start or end of a range: `-` and `+` for the first and last IDs, or an ID, excluded when
+/// prefixed by `(`. A missing sequence number is 0 at the start and the greatest at the end.
+pub(crate) fn parse_bound(bound: &str, start: bool) -> crate::Result<Option<stream::ID>> {
+    let (exclusive, id) = match bound.strip_prefix('(') {
+        Some(id) if !id.is_empty() => (true, id),
+        _ => (false, bound),
+    };
+    let id = match id {
+        "-" if start && !exclusive => return Ok(None),
+        "+" if !start && !exclusive => return Ok(None),
+        "-" => (0, 0),
+        "+" => (u64::MAX, u64::MAX),
+        id => {
+            let (time, seq) = parse_id(id)?;
+            (time, seq.unwrap_or(if start { 0 } else { u64::MAX }))
+        }
+    };
+    if !exclusive {
+        return Ok(Some((id.0, Some(id.1))));
+    }
+    let id = if start { stream::next_id(id) } else { stream::prev_id(id) };
+    match id {
+        Some((time, seq)) => Ok(Some((time, Some(seq)))),
+        None if start => Err("invalid start ID for the interval".into()),
+        None => Err("invalid end ID for the interval".into()),
+    }
+}
+
+/// Parse a stream ID, `<ms>-<seq>` or `<ms>` alone leaving the sequence open.
+pub(crate) fn parse_id(id: &str) -> crate::Result<stream::ID> {
+    const MSG: &str = "Invalid stream ID specified as stream command argument";
+    match id.split_once('-') {
+        Some((time, seq)) => Ok((time.parse().map_err(|_| MSG)?, Some(seq.parse().map_err(|_| MSG)?))),
+        None => Ok((id.parse().map_err(|_| MSG)?, None)),
     }
 }
 
+/// Parse a complete stream ID, `<ms>` alone standing for `<ms>-0`.
+pub(crate) fn parse_exact_id(id: &str) -> crate::Result<(u64, u64)> {
+    let (time, seq) = parse_id(id)?;
+    Ok((time, seq.unwrap_or(0)))
+}
+
 #[async_trait]
 impl Applicable for XRange {
     async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
         if dst.need_update_offset().await {
             dst.db().role().await.add_offset(self.command_size);
         }
-        let resp = match dst.db().xrange(self.key, self.start, self.end, self.count).await {
+        let resp = match dst.db().xrange(self.key, self.start, self.end, self.count, self.rev).await {
             Ok(entries) => {
                 let mut arr = Vec::new();
                 for entry in entries {
@@ -43,18 +43,17 @@ impl TryFrom<&mut Parse> for XRead {
         if querys.len() % 2 != 0 {
             return Err("Invalid XREAD format".into());
         }
-        for i in 0..querys.len() / 2 {
-            keys.push(querys[i].clone());
-        }
-        for i in querys.len() / 2..querys.len() {
-            if querys[i] == "$" {
+        let (names, queries) = querys.split_at(querys.len() / 2);
+        keys.extend_from_slice(names);
+        for query in queries {
+            if query == "$" {
                 if block.is_none() {
                     return Err("Invalid XREAD format".into());
                 }
                 ids.push(None);
                 continue;
             }
-            let id_parts: Vec<&str> = querys[i].split('-').collect();
+            let id_parts: Vec<&str> = query.split('-').collect();
             if id_parts.len() == 1 {
                 ids.push(Some((id_parts[0].parse().map_err(|_| "Invalid time format")?, None)));
             } else if id_parts.len() != 2 {
@@ -74,7 +73,8 @@ impl Applicable for XRead {
         if dst.need_update_offset().await {
             dst.db().role().await.add_offset(self.command_size);
         }
-        let resp = match dst.db().xread(self.keys.clone().into_iter().zip(self.ids).collect(), self.count, self.block).await {
+        let (db, closed) = dst.blocking_db();
+        let resp = match db.xread(self.keys.clone().into_iter().zip(self.ids).collect(), self.count, self.block, closed).await {
             Ok(streams) => {
                 let mut arr = Vec::new();
                 for (stream, key) in streams.into_iter().zip(self.keys) {
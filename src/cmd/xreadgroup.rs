@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XReadGroup {
+    command_size: u64,
+    group: String,
+    consumer: String,
+    keys: Vec<String>,
+    // `None` for `>`, the entries never delivered to the group
+    ids: Vec<Option<(u64, u64)>>,
+    count: Option<u64>,
+    block: Option<u64>,
+    noack: bool,
+}
+
+impl TryFrom<&mut Parse> for XReadGroup {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        if parse.next_string()?.to_uppercase() != "GROUP" {
+            return Err("syntax error".into());
+        }
+        let (group, consumer) = (parse.next_string()?, parse.next_string()?);
+        let (mut count, mut block, mut noack) = (None, None, false);
+        loop {
+            match parse.next_string()?.to_uppercase().as_str() {
+                "COUNT" => count = Some(parse.next_int()?),
+                "BLOCK" => block = Some(parse.next_int()?),
+                "NOACK" => noack = true,
+                "STREAMS" => break,
+                _ => return Err("syntax error".into()),
+            }
+        }
+        let mut args = Vec::new();
+        loop {
+            match parse.next_string() {
+                Ok(arg) => args.push(arg),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err("Unbalanced 'xreadgroup' list of streams: for each stream key an ID or '>' must be specified.".into());
+        }
+        let ids = args.split_off(args.len() / 2);
+        let ids = ids.iter()
+            .map(|id| if id == ">" { Ok(None) } else { parse_exact_id(id).map(Some) })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(XReadGroup { command_size: parse.command_size(), group, consumer, keys: args, ids, count, block, noack })
+    }
+}
+
+impl XReadGroup {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(command_size: u64, group: &str, consumer: &str, keys: Vec<String>, ids: Vec<Option<(u64, u64)>>, count: Option<u64>, block: Option<u64>, noack: bool) -> XReadGroup {
+        XReadGroup { command_size, group: group.to_string(), consumer: consumer.to_string(), keys, ids, count, block, noack }
+    }
+}
+
+#[async_trait]
+impl Applicable for XReadGroup {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let query = self.keys.clone().into_iter().zip(self.ids.iter().copied()).collect();
+        let (db, closed) = dst.blocking_db();
+        let resp = match db.xreadgroup(self.group, self.consumer, query, self.count, self.block, self.noack, closed).await {
+            Ok(streams) => {
+                let mut arr = Vec::new();
+                for ((entries, key), id) in streams.into_iter().zip(self.keys).zip(self.ids) {
+                    // the pending entries of a consumer are replied even when there are none
+                    if entries.is_empty() && id.is_none() {
+                        continue;
+                    }
+                    let entries = entries.into_iter().map(|entry| entry.encode()).collect();
+                    arr.push(Type::Array(vec![Type::BulkString(key.into()), Type::Array(entries)]));
+                }
+                if arr.is_empty() {
+                    Type::Null
+                } else {
+                    Type::Array(arr)
+                }
+            }
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
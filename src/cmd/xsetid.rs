@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XSetID {
+    command_size: u64,
+    key: String,
+    id: (u64, u64),
+    added: Option<u64>,
+    max_deleted_id: Option<(u64, u64)>,
+}
+
+impl TryFrom<&mut Parse> for XSetID {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let id = parse_exact_id(&parse.next_string()?)?;
+        let (mut added, mut max_deleted_id) = (None, None);
+        loop {
+            match parse.next_string() {
+                Ok(option) => match option.to_uppercase().as_str() {
+                    "ENTRIESADDED" => added = Some(parse.next_int()?),
+                    "MAXDELETEDID" => max_deleted_id = Some(parse_exact_id(&parse.next_string()?)?),
+                    _ => return Err("syntax error".into()),
+                },
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(XSetID { command_size: parse.command_size(), key, id, added, max_deleted_id })
+    }
+}
+
+#[async_trait]
+impl Applicable for XSetID {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().xsetid(self.key, self.id, self.added, self.max_deleted_id).await {
+            Ok(()) => Type::SimpleString("OK".to_string()),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
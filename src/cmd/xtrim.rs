@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use crate::cmd::xrange::parse_exact_id;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::stream::{Threshold, Trim};
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct XTrim {
+    command_size: u64,
+    key: String,
+    trim: Trim,
+}
+
+impl TryFrom<&mut Parse> for XTrim {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let strategy = parse.next_string()?;
+        let mut trim = parse_trim(parse, &strategy)?;
+        match parse.next_string() {
+            Ok(option) if option.to_uppercase() == "LIMIT" => parse_limit(parse, &mut trim)?,
+            Ok(_) => return Err("syntax error".into()),
+            Err(parser::Error::EndOfStream) => {}
+            Err(err) => return Err(err.into()),
+        }
+        parse.finish()?;
+        Ok(XTrim { command_size: parse.command_size(), key, trim })
+    }
+}
+
+/// Parse the threshold following MAXLEN or MINID, with its optional `=` or `~` modifier.
+pub(crate) fn parse_trim(parse: &mut Parse, strategy: &str) -> crate::Result<Trim> {
+    let mut threshold = parse.next_string()?;
+    let approximate = threshold == "~";
+    if threshold == "~" || threshold == "=" {
+        threshold = parse.next_string()?;
+    }
+    let threshold = match strategy.to_uppercase().as_str() {
+        "MAXLEN" => {
+            let max_len: i64 = threshold.parse().map_err(|_| "value is not an integer or out of range")?;
+            if max_len < 0 {
+                return Err("The MAXLEN argument must be >= 0.".into());
+            }
+            Threshold::MaxLen(max_len as u64)
+        }
+        "MINID" => Threshold::MinID(parse_exact_id(&threshold)?),
+        _ => return Err("syntax error".into()),
+    };
+    Ok(Trim { threshold, approximate, limit: None })
+}
+
+/// Parse the LIMIT of a trim, which only bounds approximate trims.
+pub(crate) fn parse_limit(parse: &mut Parse, trim: &mut Trim) -> crate::Result<()> {
+    if !trim.approximate {
+        return Err("syntax error, LIMIT cannot be used without the special ~ option".into());
+    }
+    trim.limit = Some(parse.next_int()?);
+    Ok(())
+}
+
+#[async_trait]
+impl Applicable for XTrim {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().xtrim(self.key, self.trim).await {
+            Ok(removed) => Type::Integer(removed as i64),
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::sorted_set::{AddOptions, Outcome};
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+use crate::utils::strings;
+
+#[derive(Debug, PartialEq)]
+pub struct ZAdd {
+    command_size: u64,
+    key: String,
+    options: AddOptions,
+    ch: bool,
+    pairs: Vec<(f64, Bytes)>,
+}
+
+impl TryFrom<&mut Parse> for ZAdd {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let mut options = AddOptions::default();
+        let mut ch = false;
+        let mut arg = parse.next_string()?;
+        loop {
+            match arg.to_uppercase().as_str() {
+                "NX" => options.nx = true,
+                "XX" => options.xx = true,
+                "GT" => options.gt = true,
+                "LT" => options.lt = true,
+                "CH" => ch = true,
+                "INCR" => options.incr = true,
+                _ => break,
+            }
+            arg = parse.next_string()?;
+        }
+        let mut pairs = Vec::new();
+        loop {
+            let score = strings::parse_float(&arg).ok_or("value is not a valid float")?;
+            let member = parse.next_bytes()?;
+            pairs.push((score, member));
+            match parse.next_string() {
+                Ok(next) => arg = next,
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        if options.nx && options.xx {
+            return Err("XX and NX options at the same time are not compatible".into());
+        }
+        if [options.gt, options.lt, options.nx].iter().filter(|flag| **flag).count() > 1 {
+            return Err("GT, LT, and/or NX options at the same time are not compatible".into());
+        }
+        if options.incr && pairs.len() > 1 {
+            return Err("INCR option supports a single increment-element pair".into());
+        }
+        Ok(ZAdd { command_size: parse.command_size(), key, options, ch, pairs })
+    }
+}
+
+#[async_trait]
+impl Applicable for ZAdd {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().zadd(self.key, self.pairs, self.options).await {
+            Ok(outcomes) if self.options.incr => {
+                match outcomes.first().and_then(Outcome::score) {
+                    Some(score) => Type::BulkString(strings::format_float(score).into()),
+                    None => Type::Null,
+                }
+            }
+            Ok(outcomes) => {
+                let count = outcomes.iter()
+                    .filter(|outcome| matches!(outcome, Outcome::Added(_)) || (self.ch && matches!(outcome, Outcome::Updated(_))))
+                    .count();
+                Type::Integer(count as i64)
+            }
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
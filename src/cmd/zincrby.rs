@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+use crate::utils::strings;
+
+#[derive(Debug, PartialEq)]
+pub struct ZIncrBy {
+    command_size: u64,
+    key: String,
+    increment: f64,
+    member: Bytes,
+}
+
+impl TryFrom<&mut Parse> for ZIncrBy {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let increment = strings::parse_float(&parse.next_string()?).ok_or("value is not a valid float")?;
+        let member = parse.next_bytes()?;
+        Ok(ZIncrBy { command_size: parse.command_size(), key, increment, member })
+    }
+}
+
+#[async_trait]
+impl Applicable for ZIncrBy {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if !dst.writeable() {
+            return Ok(());
+        }
+        dst.db().role().await.add_offset(self.command_size);
+        let resp = match dst.db().zincrby(self.key, self.increment, self.member).await {
+            Ok(Some(score)) => Type::BulkString(strings::format_float(score).into()),
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        if dst.db().role().await.is_master() {
+            dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+            dst.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::ops::Bound;
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::engine::sorted_set::Range;
+use crate::parser::{self, Parse};
+use crate::resp::Type;
+use crate::utils::strings;
+
+#[derive(Debug, PartialEq)]
+pub struct ZRange {
+    command_size: u64,
+    key: String,
+    range: Range,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+/// The command flavour a ZRANGE-like request was issued with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Range,
+    RevRange,
+    RangeByScore,
+    RevRangeByScore,
+    RangeByLex,
+    RevRangeByLex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum By {
+    Rank,
+    Score,
+    Lex,
+}
+
+impl ZRange {
+    pub(crate) fn parse_frames(parse: &mut Parse, style: Style) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let start = parse.next_string()?;
+        let stop = parse.next_string()?;
+        let (mut by, mut rev) = match style {
+            Style::Range => (By::Rank, false),
+            Style::RevRange => (By::Rank, true),
+            Style::RangeByScore => (By::Score, false),
+            Style::RevRangeByScore => (By::Score, true),
+            Style::RangeByLex => (By::Lex, false),
+            Style::RevRangeByLex => (By::Lex, true),
+        };
+        let (mut limit, mut with_scores) = (None, false);
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parser::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match option.as_str() {
+                "BYSCORE" if style == Style::Range => by = By::Score,
+                "BYLEX" if style == Style::Range => by = By::Lex,
+                "REV" if style == Style::Range => rev = true,
+                "WITHSCORES" if style != Style::RangeByLex && style != Style::RevRangeByLex => with_scores = true,
+                "LIMIT" if style != Style::RevRange => {
+                    limit = Some((parse.next_signed_int()?, parse.next_signed_int()?));
+                }
+                _ => return Err("syntax error".into()),
+            }
+        }
+        if limit.is_some() && by == By::Rank {
+            return Err("syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX".into());
+        }
+        if with_scores && by == By::Lex {
+            return Err("syntax error, WITHSCORES not supported in combination with BYLEX".into());
+        }
+        // score and lex ranges are given as `max min` when reversed
+        let (min, max) = if rev && by != By::Rank { (stop, start) } else { (start, stop) };
+        let range = match by {
+            By::Rank => Range::Rank(
+                min.parse().map_err(|_| "value is not an integer or out of range")?,
+                max.parse().map_err(|_| "value is not an integer or out of range")?,
+            ),
+            By::Score => Range::Score(parse_score_bound(&min)?, parse_score_bound(&max)?),
+            By::Lex => Range::Lex(parse_lex_bound(&min)?, parse_lex_bound(&max)?),
+        };
+        Ok(ZRange { command_size: parse.command_size(), key, range, rev, limit, with_scores })
+    }
+}
+
+fn parse_score_bound(s: &str) -> crate::Result<Bound<f64>> {
+    let (exclusive, s) = match s.strip_prefix('(') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let score = strings::parse_float(s).ok_or("min or max is not a float")?;
+    Ok(if exclusive { Bound::Excluded(score) } else { Bound::Included(score) })
+}
+
+fn parse_lex_bound(s: &str) -> crate::Result<Bound<Bytes>> {
+    match s.as_bytes().first() {
+        Some(b'-') | Some(b'+') if s.len() == 1 => Ok(Bound::Unbounded),
+        Some(b'[') => Ok(Bound::Included(Bytes::copy_from_slice(&s.as_bytes()[1..]))),
+        Some(b'(') => Ok(Bound::Excluded(Bytes::copy_from_slice(&s.as_bytes()[1..]))),
+        _ => Err("min or max not valid string range item".into()),
+    }
+}
+
+#[async_trait]
+impl Applicable for ZRange {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().zrange(self.key, self.range, self.rev, self.limit).await {
+            Ok(pairs) => {
+                let mut arr = Vec::new();
+                for (member, score) in pairs.into_iter() {
+                    arr.push(Type::BulkString(member));
+                    if self.with_scores {
+                        arr.push(Type::BulkString(strings::format_float(score).into()));
+                    }
+                }
+                Type::Array(arr)
+            }
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
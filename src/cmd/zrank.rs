@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+
+#[derive(Debug, PartialEq)]
+pub struct ZRank {
+    command_size: u64,
+    key: String,
+    member: Bytes,
+    rev: bool,
+}
+
+impl ZRank {
+    pub(crate) fn parse_frames(parse: &mut Parse, rev: bool) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let member = parse.next_bytes()?;
+        Ok(ZRank { command_size: parse.command_size(), key, member, rev })
+    }
+}
+
+#[async_trait]
+impl Applicable for ZRank {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().zrank(self.key, self.member, self.rev).await {
+            Ok(Some(rank)) => Type::Integer(rank as i64),
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::AsyncWriteExt;
+use crate::connection::{Applicable, Connection};
+use crate::encoder::Encoder;
+use crate::parser::Parse;
+use crate::resp::Type;
+use crate::utils::strings;
+
+#[derive(Debug, PartialEq)]
+pub struct ZScore {
+    command_size: u64,
+    key: String,
+    member: Bytes,
+}
+
+impl TryFrom<&mut Parse> for ZScore {
+    type Error = crate::Error;
+    fn try_from(parse: &mut Parse) -> crate::Result<Self> {
+        let key = parse.next_string()?;
+        let member = parse.next_bytes()?;
+        Ok(ZScore { command_size: parse.command_size(), key, member })
+    }
+}
+
+#[async_trait]
+impl Applicable for ZScore {
+    async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        if dst.need_update_offset().await {
+            dst.db().role().await.add_offset(self.command_size);
+        }
+        let resp = match dst.db().zscore(self.key, self.member).await {
+            Ok(Some(score)) => Type::BulkString(strings::format_float(score).into()),
+            Ok(None) => Type::Null,
+            Err(e) => Type::SimpleError(e.to_string()),
+        };
+        dst.write_all(Encoder::encode(&resp).as_slice()).await?;
+        dst.flush().await?;
+        Ok(())
+    }
+}
@@ -1,24 +1,48 @@
+use std::future::Future;
 use std::io::Cursor;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use bytes::{Buf, BytesMut};
-use tokio::io::{AsyncReadExt, AsyncWrite, BufWriter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter, ReadBuf};
 use tokio::net::TcpStream;
 use async_trait::async_trait;
 use tokio::io;
 use crate::resp::{self, Type};
 use crate::cmd::Command;
 use crate::db::DB;
+use crate::encoder::Encoder;
+use crate::pubsub::Subscriber;
 use crate::utils;
 
 #[derive(Debug)]
 pub struct Connection {
-    stream: BufWriter<TcpStream>,
+    stream: BufWriter<Socket>,
     buffer: BytesMut,
     db: DB,
     writeable: bool,
     port: Option<usize>,
     id: Option<String>,
+    transaction: Option<Transaction>,
+    // the watched keys with the index of their database and their version when watched
+    watched: Vec<(usize, String, u64)>,
+    // the channel and pattern subscriptions, created by the first SUBSCRIBE or PSUBSCRIBE
+    subscriber: Option<Subscriber>,
+}
+
+/// Where the commands of a connection come from.
+#[derive(Debug)]
+enum Socket {
+    Tcp(TcpStream),
+    // the append only file replayed at startup, its commands are all buffered beforehand and the replies dropped
+    Replay,
+}
+
+/// The commands queued between MULTI and EXEC.
+#[derive(Debug, Default)]
+pub(crate) struct Transaction {
+    pub(crate) commands: Vec<Command>,
+    // set when a command could not be queued, EXEC then discards the transaction
+    pub(crate) aborted: bool,
 }
 
 #[async_trait]
@@ -29,28 +53,160 @@ pub(crate) trait Applicable {
 impl Connection {
     /// Create a new `Connection` instance.
     pub fn new(stream: TcpStream, db: DB, writeable: bool) -> Connection {
+        Connection::with_socket(Socket::Tcp(stream), BytesMut::with_capacity(4 * 1024), db, writeable)
+    }
+
+    fn with_socket(socket: Socket, buffer: BytesMut, db: DB, writeable: bool) -> Connection {
         Connection {
-            stream: BufWriter::new(stream),
-            buffer: BytesMut::with_capacity(4 * 1024),
+            stream: BufWriter::new(socket),
+            buffer,
             db,
             writeable,
             port: None,
             id: None,
+            transaction: None,
+            watched: Vec::new(),
+            subscriber: None,
+        }
+    }
+
+    /// Apply the commands logged in the append only file, returns the length of the data holding them,
+    /// less than all of it when a crash cut the last command or transaction short.
+    pub(crate) async fn replay(data: &[u8], db: DB) -> crate::Result<usize> {
+        let len = Self::whole_commands(data)?;
+        let mut con = Connection::with_socket(Socket::Replay, BytesMut::from(&data[..len]), db, true);
+        con.run().await?;
+        Ok(len)
+    }
+
+    /// The length of the commands at the start of `data`, without an incomplete command or a transaction
+    /// missing its EXEC.
+    fn whole_commands(data: &[u8]) -> crate::Result<usize> {
+        let mut cur = Cursor::new(data);
+        let mut len = 0;
+        let mut in_transaction = false;
+        loop {
+            let start = cur.position();
+            match Type::check(&mut cur) {
+                Ok(_) => {}
+                Err(resp::Error::Incomplete) => return Ok(len),
+                Err(e) => return Err(e.into()),
+            }
+            let end = cur.position();
+            cur.set_position(start);
+            let frame = Type::parse(&mut cur)?;
+            cur.set_position(end);
+            match Self::command_name(&frame).as_str() {
+                "multi" => in_transaction = true,
+                "exec" => in_transaction = false,
+                _ => {}
+            }
+            if !in_transaction {
+                len = end as usize;
+            }
         }
     }
 
     pub async fn run(&mut self) -> crate::Result<()> {
+        let result = self.serve().await;
+        self.unwatch().await;
+        self.subscriber = None;
+        result
+    }
+
+    async fn serve(&mut self) -> crate::Result<()> {
         loop {
-            let maybe_frame = self.read_frame().await?;
+            let maybe_frame = self.next_frame().await?;
             let frame = match maybe_frame {
                 Some(frame) => frame,
                 None => return Ok(()),
             };
-            let command: Command = frame.try_into()?;
+            let command_size = frame.len();
+            let command_name = Self::command_name(&frame);
+            let command: Command = match frame.try_into() {
+                Ok(command) => command,
+                Err(e) => {
+                    if let Some(transaction) = self.transaction.as_mut() {
+                        transaction.aborted = true;
+                    }
+                    if self.is_master_link().await {
+                        // the master link never expects replies, only keep the offset in sync
+                        self.db.role().await.add_offset(command_size);
+                    } else {
+                        let resp = Type::SimpleError(format!("ERR {}", e));
+                        self.write_all(Encoder::encode(&resp).as_slice()).await?;
+                        self.flush().await?;
+                    }
+                    continue;
+                }
+            };
+            if self.is_subscribed() && !command.allowed_when_subscribed() {
+                let resp = Type::SimpleError(format!(
+                    "ERR Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+                    command_name,
+                ));
+                self.write_all(Encoder::encode(&resp).as_slice()).await?;
+                self.flush().await?;
+                continue;
+            }
+            if let Some(transaction) = self.transaction.as_mut() {
+                if !command.is_transaction_control() {
+                    let resp = if command.allowed_in_transaction() {
+                        transaction.commands.push(command);
+                        Type::SimpleString("QUEUED".to_string())
+                    } else {
+                        transaction.aborted = true;
+                        Type::SimpleError("ERR Command not allowed inside a transaction".to_string())
+                    };
+                    if !self.is_master_link().await {
+                        self.write_all(Encoder::encode(&resp).as_slice()).await?;
+                        self.flush().await?;
+                    }
+                    continue;
+                }
+            }
             command.apply(self).await?;
         }
     }
 
+    /// Read the next frame, writing out the published messages while waiting when subscribed.
+    async fn next_frame(&mut self) -> crate::Result<Option<Type>> {
+        loop {
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+            let subscriber = match self.subscriber.as_mut() {
+                Some(subscriber) if subscriber.count() > 0 => subscriber,
+                _ => return self.read_frame().await,
+            };
+            tokio::select! {
+                read = self.stream.read_buf(&mut self.buffer) => {
+                    if 0 == read? {
+                        if self.buffer.is_empty() {
+                            return Ok(None);
+                        } else {
+                            return Err("connection reset by peer".into());
+                        }
+                    }
+                }
+                Some(message) = subscriber.recv() => {
+                    self.stream.write_all(Encoder::encode(&message.encode()).as_slice()).await?;
+                    self.stream.flush().await?;
+                }
+            }
+        }
+    }
+
+    fn command_name(frame: &Type) -> String {
+        match frame {
+            Type::Array(items) => match items.first() {
+                Some(Type::BulkString(name)) => String::from_utf8_lossy(name).to_lowercase(),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        }
+    }
+
     pub async fn read_frame(&mut self) -> crate::Result<Option<Type>> {
         loop {
             if let Some(frame) = self.parse_frame()? {
@@ -85,6 +241,25 @@ impl Connection {
         &mut self.db
     }
 
+    /// The database with a future completing once the client closes the connection, for the commands
+    /// that block. The commands sent while blocked are buffered to be served afterwards.
+    pub(crate) fn blocking_db(&mut self) -> (&mut DB, impl Future<Output = ()> + Send + '_) {
+        let Connection { stream, buffer, db, .. } = self;
+        let closed = async move {
+            // the append only file never closes, its commands are all buffered already
+            if let Socket::Replay = stream.get_ref() {
+                return std::future::pending().await;
+            }
+            loop {
+                match stream.read_buf(buffer).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) => {}
+                }
+            }
+        };
+        (db, closed)
+    }
+
     pub(crate) fn writeable(&self) -> bool {
         self.writeable
     }
@@ -98,22 +273,66 @@ impl Connection {
         self.id = Some(id);
     }
 
+    /// Whether this is a replica's connection to its master, which never expects replies.
+    pub(crate) async fn is_master_link(&self) -> bool {
+        self.writeable && !self.db.role().await.is_master()
+    }
+
+    /// Start queueing commands, returns false if a transaction is already open.
+    pub(crate) fn begin_transaction(&mut self) -> bool {
+        if self.transaction.is_some() {
+            return false;
+        }
+        self.transaction = Some(Transaction::default());
+        true
+    }
+
+    pub(crate) fn take_transaction(&mut self) -> Option<Transaction> {
+        self.transaction.take()
+    }
+
+    pub(crate) fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    pub(crate) async fn watch(&mut self, keys: Vec<String>) {
+        let versions = self.db.watch(&keys).await;
+        let index = self.db.index();
+        self.watched.extend(keys.into_iter().zip(versions).map(|(key, version)| (index, key, version)));
+    }
+
+    pub(crate) async fn unwatch(&mut self) {
+        if self.watched.is_empty() {
+            return;
+        }
+        let keys: Vec<(usize, String)> = self.watched.drain(..).map(|(index, key, _)| (index, key)).collect();
+        self.db.unwatch(&keys).await;
+    }
+
+    pub(crate) fn watched(&self) -> &[(usize, String, u64)] {
+        &self.watched
+    }
+
+    pub(crate) fn subscriber(&mut self) -> &mut Subscriber {
+        let pubsub = self.db.pubsub();
+        self.subscriber.get_or_insert_with(|| pubsub.subscriber())
+    }
+
+    /// Whether the connection is subscribed to a channel or a pattern, which limits the accepted commands.
+    pub(crate) fn is_subscribed(&self) -> bool {
+        self.subscriber.as_ref().map(|subscriber| subscriber.count() > 0).unwrap_or(false)
+    }
+
     pub(crate) async fn need_update_offset(&self) -> bool {
         self.writeable || self.db.role().await.is_master()
     }
 
     pub(crate) fn socket_addr(&self) -> Option<String> {
-        match self.stream.get_ref().peer_addr() {
-            Ok(addr) => {
-                match self.port {
-                    Some(port) => {
-                        Some(format!("{}:{}", addr.ip(), port))
-                    }
-                    None => None
-                }
-            }
-            Err(_) => None,
-        }
+        let addr = match self.stream.get_ref() {
+            Socket::Tcp(stream) => stream.peer_addr().ok()?,
+            Socket::Replay => return None,
+        };
+        self.port.map(|port| format!("{}:{}", addr.ip(), port))
     }
 
     pub(crate) fn id(&mut self) -> String {
@@ -153,4 +372,49 @@ impl AsyncWrite for Connection {
         let this = self.get_mut();
         Pin::new(&mut this.stream).poll_shutdown(cx)
     }
-}
\ No newline at end of file
+}
+impl AsyncRead for Socket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Socket::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Socket::Replay => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+impl AsyncWrite for Socket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Socket::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Socket::Replay => Poll::Ready(Ok(buf.len())),
+        }
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Socket::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Socket::Replay => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Socket::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            Socket::Replay => Poll::Ready(Ok(())),
+        }
+    }
+}
@@ -1,46 +1,308 @@
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use bytes::Bytes;
 use tokio::io::AsyncWriteExt;
-use tokio::select;
 use tokio::sync::mpsc::{channel, Receiver};
-use tokio::sync::RwLock;
+use tokio::sync::oneshot;
+use tokio::time;
+use tokio::sync::{OwnedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::aof::{self, Aof};
 use crate::connection;
 use crate::encoder::Encoder;
-use crate::engine::{DataType, Engine, stream, string};
+use crate::pubsub::{Class, PubSub};
+use crate::engine::{DataType, Engine, ExpireOptions, SavePoints, hash, list, set, sorted_set, stream, string};
+use crate::engine::blocking::Blocked;
 use crate::engine::stream::Entry;
 use crate::replication::command::Command;
 use crate::replication::role::Role;
 use crate::replication::simple::Simple;
 use crate::replication::synchronization::Synchronization;
 use crate::resp::Type;
-use crate::utils::sync::Notifier;
+use crate::utils::strings;
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct DB {
     shard: Arc<RwLock<Shard>>,
+    // the database selected by this handle
+    engine: Engine,
+    // the write lock held by this handle while a transaction executes
+    exclusive: Option<OwnedRwLockWriteGuard<Shard>>,
 }
 
 #[derive(Debug)]
 struct Shard {
-    engine: Engine,
     role: Role,
+    // operations buffered while a transaction executes, propagated as one MULTI/EXEC block
+    transaction: Option<Vec<Propagated>>,
+    // the database the replication stream last selected, `None` forces the next write to select it
+    replicated_db: Option<usize>,
+    aof: Option<Aof>,
+    // the database the append only file last selected, like `replicated_db`
+    aof_db: Option<usize>,
+    blocked: Blocked<Pending>,
+}
+
+/// A write on its way to the replicas and the append only file, with the database it applies to.
+#[derive(Debug)]
+struct Propagated {
+    index: usize,
+    operation: Type,
+    replicated: bool,
+    logged: bool,
+}
+
+/// What a blocked command does once one of its keys is ready.
+#[derive(Debug, Clone)]
+pub enum Blocking {
+    /// Pop up to `count` elements of a list.
+    Pop(list::Direction, u64),
+    /// Pop an element of a list and push it to the destination list.
+    Move(list::Direction, String, list::Direction),
+    /// Read the entries of a stream from the start ID given for its key.
+    Read(Vec<(String, stream::ID)>, Option<u64>),
+    /// Read up to `count` new entries of a stream for a consumer of a group, `noack` leaving them unacknowledged.
+    ReadGroup(String, String, Option<u64>, bool),
+}
+
+/// The result of a blocking command, with the key that served it.
+#[derive(Debug)]
+pub enum Served {
+    Popped(String, Vec<Bytes>),
+    Moved(Bytes),
+    Read(String, Vec<Entry>),
+}
+
+#[derive(Debug)]
+struct Pending {
+    request: Blocking,
+    reply: oneshot::Sender<Result<Served, Error>>,
+}
+
+/// The locked shard, with the database selected by the handle that took the lock.
+struct Guard<L> {
+    lock: L,
+    engine: Engine,
+}
+
+enum ReadLock<'a> {
+    Locked(RwLockReadGuard<'a, Shard>),
+    Held(&'a Shard),
+}
+
+enum WriteLock<'a> {
+    Locked(RwLockWriteGuard<'a, Shard>),
+    Held(&'a mut Shard),
+}
+
+type ReadGuard<'a> = Guard<ReadLock<'a>>;
+
+type WriteGuard<'a> = Guard<WriteLock<'a>>;
+
+impl Deref for ReadLock<'_> {
+    type Target = Shard;
+
+    fn deref(&self) -> &Shard {
+        match self {
+            ReadLock::Locked(guard) => guard,
+            ReadLock::Held(shard) => shard,
+        }
+    }
+}
+
+impl Deref for WriteLock<'_> {
+    type Target = Shard;
+
+    fn deref(&self) -> &Shard {
+        match self {
+            WriteLock::Locked(guard) => guard,
+            WriteLock::Held(shard) => shard,
+        }
+    }
+}
+
+impl DerefMut for WriteLock<'_> {
+    fn deref_mut(&mut self) -> &mut Shard {
+        match self {
+            WriteLock::Locked(guard) => guard,
+            WriteLock::Held(shard) => shard,
+        }
+    }
+}
+
+impl<L: Deref<Target = Shard>> Deref for Guard<L> {
+    type Target = Shard;
+
+    fn deref(&self) -> &Shard {
+        &self.lock
+    }
+}
+
+impl<L: DerefMut<Target = Shard>> DerefMut for Guard<L> {
+    fn deref_mut(&mut self) -> &mut Shard {
+        &mut self.lock
+    }
+}
+
+impl Clone for DB {
+    fn clone(&self) -> Self {
+        DB {
+            shard: self.shard.clone(),
+            engine: self.engine.clone(),
+            exclusive: None,
+        }
+    }
 }
 
 impl DB {
-    pub async fn new(dir: String, file_name: String, role: Option<Role>) -> DB {
-        let engine = Engine::new(dir, file_name).await;
+    pub async fn new(dir: String, file_name: String, databases: usize, role: Option<Role>, aof: Option<aof::Config>, save_points: SavePoints) -> crate::Result<DB> {
+        let aof_file_name = aof.as_ref().map(|aof| aof.file_name.clone());
+        let engine = Engine::new(dir, file_name, databases, PubSub::new(), aof_file_name, save_points).await?;
         let role = role.unwrap_or_default();
-        DB {
+        let mut db = DB {
             shard: Arc::new(RwLock::new(Shard {
-                engine,
                 role,
-            }))
+                transaction: None,
+                replicated_db: None,
+                aof: None,
+                aof_db: None,
+                blocked: Blocked::default(),
+            })),
+            engine,
+            exclusive: None,
+        };
+        if let Some(aof) = aof {
+            db.open_aof(aof.fsync).await?;
+        }
+        if !db.engine.save_points().0.is_empty() {
+            tokio::spawn(save_points_task(db.clone()));
+        }
+        Ok(db)
+    }
+
+    /// Replay the append only file, or create it from the data loaded from the RDB file, then log
+    /// the writes to it. The replay runs the logged commands, which is why it lives here and not with
+    /// the RDB loading in the engine.
+    async fn open_aof(&mut self, fsync: aof::Fsync) -> crate::Result<()> {
+        let path = match self.engine.aof_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let existed = self.engine.has_aof();
+        if existed {
+            let (commands, offset) = self.engine.load_aof().await
+                .map_err(|e| format!("failed to load the append only file {}: {}", path.display(), e))?;
+            let replayed = connection::Connection::replay(&commands, self.clone()).await
+                .map_err(|e| format!("failed to replay the append only file {}: {}", path.display(), e))?;
+            if replayed < commands.len() {
+                // a crash cut the last command short, drop it so that the next writes follow a whole command
+                aof::truncate(&path, offset + replayed as u64).await?;
+            }
+            // the replayed commands are not part of the replication stream, nor writes to save
+            self.shard.write().await.role.set_offset(0);
+            self.engine.reset_dirty();
+        }
+        let aof = Aof::open(path, fsync).await?;
+        let mut shard = self.write().await;
+        shard.aof = Some(aof.clone());
+        if !existed {
+            let generation = aof.begin_rewrite(false).await.unwrap_or_default();
+            let mut base = Vec::new();
+            shard.engine.snapshot().await.write(&mut base).await?;
+            aof.finish_rewrite(generation, base).await?;
+        }
+        Ok(())
+    }
+
+    async fn read(&self) -> ReadGuard<'_> {
+        let lock = match &self.exclusive {
+            Some(shard) => ReadLock::Held(shard),
+            None => ReadLock::Locked(self.shard.read().await),
+        };
+        Guard { lock, engine: self.engine.clone() }
+    }
+
+    async fn write(&mut self) -> WriteGuard<'_> {
+        let engine = self.engine.clone();
+        let lock = if self.exclusive.is_some() {
+            WriteLock::Held(self.exclusive.as_deref_mut().unwrap())
+        } else {
+            WriteLock::Locked(self.shard.write().await)
+        };
+        Guard { lock, engine }
+    }
+
+    /// Switch this handle to the database `index`.
+    pub fn select(&mut self, index: usize) -> Result<(), Error> {
+        if index >= self.engine.databases() {
+            return Err(Error::OutOfRange);
+        }
+        self.engine = self.engine.select(index);
+        Ok(())
+    }
+
+    /// The index of the selected database.
+    pub fn index(&self) -> usize {
+        self.engine.index()
+    }
+
+    pub fn databases(&self) -> usize {
+        self.engine.databases()
+    }
+
+    /// Take the write lock for the commands of a transaction, until `end_transaction`.
+    pub async fn begin_transaction(&mut self) {
+        let mut shard = self.shard.clone().write_owned().await;
+        shard.transaction = Some(Vec::new());
+        self.exclusive = Some(shard);
+    }
+
+    /// Propagate the operations of the transaction as one MULTI/EXEC block and release the write lock.
+    pub async fn end_transaction(&mut self) {
+        let mut shard = match self.exclusive.take() {
+            Some(shard) => shard,
+            None => return,
+        };
+        let operations = shard.transaction.take().unwrap_or_default();
+        if !operations.is_empty() {
+            let mut shard = Guard { lock: WriteLock::Held(&mut shard), engine: self.engine.clone() };
+            shard.flush(operations, true).await;
         }
     }
 
-    pub async fn get(&self, key: String) -> Result<Option<string::String>, Error> {
-        let shard = self.shard.read().await;
+    /// Start tracking `keys` for a transaction, returns their current versions.
+    pub async fn watch(&mut self, keys: &[String]) -> Vec<u64> {
+        let mut shard = self.write().await;
+        let mut versions = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            versions.push(shard.engine.watch(key.clone()).await);
+        }
+        versions
+    }
+
+    /// Stop tracking keys, each given with the index of its database.
+    pub async fn unwatch(&mut self, keys: &[(usize, String)]) {
+        let shard = self.write().await;
+        for (index, key) in keys.iter() {
+            shard.engine.select(*index).unwatch(key).await;
+        }
+    }
+
+    /// Whether any of the watched keys was modified since its version was taken.
+    pub async fn watched_changed(&self, watched: &[(usize, String, u64)]) -> bool {
+        let shard = self.read().await;
+        for (index, key, version) in watched.iter() {
+            if shard.engine.select(*index).version(key).await != *version {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub async fn get(&mut self, key: String) -> Result<Option<string::String>, Error> {
+        let shard = self.read().await;
         let val = shard.engine.get(key).await;
         match val {
             Some(DataType::String(string)) => Ok(Some(string)),
@@ -49,38 +311,385 @@ impl DB {
         }
     }
 
-    pub async fn set(&mut self, key: String, value: Bytes, expire: Option<Duration>) {
-        let mut shard = self.shard.write().await;
+    /// Set `key` following the SET modifiers, returns whether the value was written and,
+    /// with `get`, the previous value.
+    pub async fn set(&mut self, key: String, value: Bytes, expire: Option<SystemTime>, options: string::SetOptions) -> Result<(bool, Option<Bytes>), Error> {
+        let mut shard = self.write().await;
+        let prev = shard.engine.get(key.clone()).await;
+        let old = match (&prev, options.get) {
+            (Some(DataType::String(string)), true) => Some(string.clone().into()),
+            (Some(_), true) => return Err(Error::InvalidType),
+            _ => None,
+        };
+        if (options.nx && prev.is_some()) || (options.xx && prev.is_none()) {
+            return Ok((false, old));
+        }
         let val = string::String::new(value);
-        shard.engine.set(key.clone(), DataType::String(val.clone()), expire).await;
-        if shard.role.is_master() {
-            let data = Encoder::encode(&Operation::Set(key, val).encode());
-            shard.role.replicate_data(Command::Simple(Simple::new(data.into()))).await;
+        let expire = if options.keep_ttl {
+            shard.engine.replace(key.clone(), DataType::String(val.clone())).await;
+            shard.engine.expiration(&key).await.flatten()
+        } else {
+            shard.engine.set(key.clone(), DataType::String(val.clone()), None).await;
+            if expire.is_some() {
+                shard.engine.expire(key.clone(), expire).await;
+            }
+            expire
+        };
+        shard.engine.notify(Class::String, "set", &key);
+        if expire.is_some() && !options.keep_ttl {
+            shard.engine.notify(Class::Generic, "expire", &key);
+        }
+        shard.replicate(Operation::Set(key, val, expire)).await;
+        Ok((true, old))
+    }
+
+    /// Values of several keys read under one lock, keys that are missing or not strings are `None`.
+    pub async fn mget(&self, keys: Vec<String>) -> Vec<Option<Bytes>> {
+        let shard = self.read().await;
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys.into_iter() {
+            values.push(match shard.engine.get(key).await {
+                Some(DataType::String(string)) => Some(string.into()),
+                _ => None,
+            });
+        }
+        values
+    }
+
+    /// Set several keys at once, with `nx` nothing is written if any of the keys exists.
+    pub async fn mset(&mut self, pairs: Vec<(String, Bytes)>, nx: bool) -> bool {
+        let mut shard = self.write().await;
+        if nx {
+            for (key, _) in pairs.iter() {
+                if shard.engine.get(key.clone()).await.is_some() {
+                    return false;
+                }
+            }
+        }
+        for (key, value) in pairs.iter() {
+            shard.engine.set(key.clone(), DataType::String(string::String::new(value.clone())), None).await;
+            shard.engine.notify(Class::String, "set", key);
+        }
+        shard.replicate(Operation::MSet(pairs)).await;
+        true
+    }
+
+    /// Append `value` to the string at `key`, creating it when missing, returns the new length.
+    pub async fn append(&mut self, key: String, value: Bytes) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let val = match shard.get_string(key.clone()).await? {
+            Some(string) => string.append(&value).map_err(Error::StringError)?,
+            None => string::String::new(value.clone()),
+        };
+        let len = val.len();
+        shard.engine.replace(key.clone(), DataType::String(val)).await;
+        shard.engine.notify(Class::String, "append", &key);
+        shard.replicate(Operation::Append(key, value)).await;
+        Ok(len)
+    }
+
+    pub async fn strlen(&self, key: String) -> Result<u64, Error> {
+        let shard = self.read().await;
+        match shard.get_string(key).await? {
+            Some(string) => Ok(string.len()),
+            None => Ok(0),
+        }
+    }
+
+    pub async fn getrange(&self, key: String, start: i64, end: i64) -> Result<Bytes, Error> {
+        let shard = self.read().await;
+        match shard.get_string(key).await? {
+            Some(string) => Ok(string.range(start, end)),
+            None => Ok(Bytes::new()),
+        }
+    }
+
+    /// Overwrite the string at `key` from `offset`, returns the new length.
+    pub async fn setrange(&mut self, key: String, offset: u64, value: Bytes) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let current = match shard.get_string(key.clone()).await? {
+            Some(string) => string,
+            // an empty value never creates the key
+            None if value.is_empty() => return Ok(0),
+            None => string::String::new(Bytes::new()),
+        };
+        if value.is_empty() {
+            return Ok(current.len());
+        }
+        let val = current.set_range(offset, &value).map_err(Error::StringError)?;
+        let len = val.len();
+        shard.engine.replace(key.clone(), DataType::String(val)).await;
+        shard.engine.notify(Class::String, "setrange", &key);
+        shard.replicate(Operation::SetRange(key, offset, value)).await;
+        Ok(len)
+    }
+
+    pub async fn getdel(&mut self, key: String) -> Result<Option<Bytes>, Error> {
+        let mut shard = self.write().await;
+        let string = match shard.get_string(key.clone()).await? {
+            Some(string) => string,
+            None => return Ok(None),
+        };
+        shard.engine.del(key.clone()).await;
+        shard.engine.notify(Class::Generic, "del", &key);
+        shard.replicate(Operation::Del(vec![key])).await;
+        Ok(Some(string.into()))
+    }
+
+    /// Get the string at `key` and optionally change its expiration, `Some(None)` removes it.
+    pub async fn getex(&mut self, key: String, expire: Option<Option<SystemTime>>) -> Result<Option<Bytes>, Error> {
+        let mut shard = self.write().await;
+        let string = match shard.get_string(key.clone()).await? {
+            Some(string) => string,
+            None => return Ok(None),
+        };
+        match expire {
+            Some(Some(when)) if when <= SystemTime::now() => {
+                shard.engine.del(key.clone()).await;
+                shard.engine.notify(Class::Generic, "del", &key);
+                shard.replicate(Operation::Del(vec![key])).await;
+            }
+            Some(Some(when)) => {
+                shard.engine.expire(key.clone(), Some(when)).await;
+                shard.engine.notify(Class::Generic, "expire", &key);
+                shard.replicate(Operation::PExpireAt(key, when)).await;
+            }
+            Some(None) => {
+                if let Some(Some(_)) = shard.engine.expiration(&key).await {
+                    shard.engine.notify(Class::Generic, "persist", &key);
+                }
+                shard.engine.expire(key.clone(), None).await;
+                shard.replicate(Operation::Persist(key)).await;
+            }
+            None => {}
         }
+        Ok(Some(string.into()))
+    }
+
+    /// Add `increment` to the integer stored at `key`, a missing key counts as 0 and the TTL is kept.
+    pub async fn incr_by(&mut self, key: String, increment: i64) -> Result<i64, Error> {
+        let mut shard = self.write().await;
+        let value = match shard.get_string(key.clone()).await? {
+            Some(string) => string.incr_by(increment).map_err(Error::StringError)?,
+            None => increment,
+        };
+        let val = string::String::new(value.to_string().into());
+        shard.engine.replace(key.clone(), DataType::String(val)).await;
+        shard.engine.notify(Class::String, "incrby", &key);
+        shard.replicate(Operation::IncrBy(key, increment)).await;
+        Ok(value)
+    }
+
+    /// Add `increment` to the float stored at `key`, replicated as a SET of the result.
+    pub async fn incr_by_float(&mut self, key: String, increment: f64) -> Result<Bytes, Error> {
+        let mut shard = self.write().await;
+        let value = match shard.get_string(key.clone()).await? {
+            Some(string) => string.incr_by_float(increment).map_err(Error::StringError)?,
+            None if increment.is_finite() => increment,
+            None => return Err(Error::StringError(string::Error::NaNOrInfinity)),
+        };
+        let formatted: Bytes = strings::format_float(value).into();
+        let val = string::String::new(formatted.clone());
+        shard.engine.replace(key.clone(), DataType::String(val.clone())).await;
+        let expire = shard.engine.expiration(&key).await.flatten();
+        shard.engine.notify(Class::String, "incrbyfloat", &key);
+        shard.replicate(Operation::Set(key, val, expire)).await;
+        Ok(formatted)
     }
 
     pub async fn del(&mut self, keys: Vec<String>) -> u64 {
-        let mut shard = self.shard.write().await;
+        let mut shard = self.write().await;
         let mut count = 0u64;
         for key in keys.iter() {
             if shard.engine.del(key.clone()).await {
+                shard.engine.notify(Class::Generic, "del", key);
                 count += 1;
             }
         }
-        if shard.role.is_master() {
-            let data = Encoder::encode(&Operation::Del(keys).encode());
-            shard.role.replicate_data(Command::Simple(Simple::new(data.into()))).await;
+        shard.replicate(Operation::Del(keys)).await;
+        count
+    }
+
+    /// Remove `keys` like `del`, the values are freed in the background.
+    pub async fn unlink(&mut self, keys: Vec<String>) -> u64 {
+        let mut shard = self.write().await;
+        let removed = shard.engine.unlink(&keys).await;
+        for key in removed.iter() {
+            shard.engine.notify(Class::Generic, "del", key);
+        }
+        shard.replicate(Operation::Unlink(keys)).await;
+        removed.len() as u64
+    }
+
+    /// The number of `keys` that exist, a key given several times is counted as many times.
+    pub async fn exists(&self, keys: Vec<String>) -> u64 {
+        let shard = self.read().await;
+        let mut count = 0;
+        for key in keys.iter() {
+            if shard.engine.exists(key).await {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Rename `src` to `dst` with its expiration, with `nx` only if `dst` does not exist yet.
+    /// Returns whether the key was renamed.
+    pub async fn rename(&mut self, src: String, dst: String, nx: bool) -> Result<bool, Error> {
+        let mut shard = self.write().await;
+        if !shard.engine.exists(&src).await {
+            return Err(Error::NoSuchKey);
+        }
+        if src == dst {
+            return Ok(!nx);
+        }
+        if nx && shard.engine.exists(&dst).await {
+            return Ok(false);
+        }
+        shard.engine.rename(&src, dst.clone()).await;
+        shard.engine.notify(Class::Generic, "rename_from", &src);
+        shard.engine.notify(Class::Generic, "rename_to", &dst);
+        shard.replicate(Operation::Rename(src, dst)).await;
+        Ok(true)
+    }
+
+    /// Copy `src` to `dst` with its expiration, overwriting `dst` only with `replace`.
+    /// Returns whether the key was copied.
+    pub async fn copy(&mut self, src: String, dst: String, replace: bool) -> Result<bool, Error> {
+        if src == dst {
+            return Err(Error::SameObject);
+        }
+        let mut shard = self.write().await;
+        let copied = shard.engine.copy(&src, dst.clone(), replace).await;
+        if copied {
+            shard.engine.notify(Class::Generic, "copy_to", &dst);
+            shard.replicate(Operation::Copy(src, dst)).await;
+        }
+        Ok(copied)
+    }
+
+    /// Move `key` to the database `index`, returns false if it does not exist or already exists there.
+    pub async fn move_key(&mut self, key: String, index: usize) -> Result<bool, Error> {
+        if index >= self.engine.databases() {
+            return Err(Error::OutOfRange);
+        }
+        if index == self.engine.index() {
+            return Err(Error::SameObject);
+        }
+        let mut shard = self.write().await;
+        let moved = shard.engine.move_key(&key, index).await;
+        if moved {
+            shard.engine.notify(Class::Generic, "move_from", &key);
+            shard.engine.select(index).notify(Class::Generic, "move_to", &key);
+            shard.replicate(Operation::Move(key, index)).await;
+        }
+        Ok(moved)
+    }
+
+    pub async fn swapdb(&mut self, a: usize, b: usize) -> Result<(), Error> {
+        if a >= self.engine.databases() || b >= self.engine.databases() {
+            return Err(Error::OutOfRange);
+        }
+        let mut shard = self.write().await;
+        shard.engine.swap_databases(a, b).await;
+        shard.replicate(Operation::SwapDB(a, b)).await;
+        Ok(())
+    }
+
+    /// Remove every key of the selected database, with `lazy` the values are freed in the background.
+    pub async fn flushdb(&mut self, lazy: bool) {
+        let mut shard = self.write().await;
+        shard.engine.flush(lazy).await;
+        shard.replicate(Operation::FlushDB(lazy)).await;
+    }
+
+    /// Remove every key of every database, with `lazy` the values are freed in the background.
+    pub async fn flushall(&mut self, lazy: bool) {
+        let mut shard = self.write().await;
+        shard.engine.flush_all(lazy).await;
+        shard.replicate(Operation::FlushAll(lazy)).await;
+    }
+
+    /// Deliver `message` to the subscribers of `channel`, returns the number of receivers.
+    pub async fn publish(&mut self, channel: Bytes, message: Bytes) -> u64 {
+        let mut shard = self.write().await;
+        let receivers = shard.engine.pubsub().publish(channel.clone(), message.clone());
+        shard.replicate(Operation::Publish(channel, message)).await;
+        receivers
+    }
+
+    pub fn pubsub(&self) -> PubSub {
+        self.engine.pubsub()
+    }
+
+    pub async fn random_key(&self) -> Option<String> {
+        let shard = self.read().await;
+        shard.engine.random_key().await
+    }
+
+    pub async fn dbsize(&self) -> u64 {
+        let shard = self.read().await;
+        shard.engine.size().await
+    }
+
+    /// Expire `key` at the absolute time `when`, a time in the past deletes the key right away.
+    pub async fn expire(&mut self, key: String, when: SystemTime, options: ExpireOptions) -> bool {
+        let mut shard = self.write().await;
+        let current = match shard.engine.expiration(&key).await {
+            Some(current) => current,
+            None => return false,
+        };
+        if !options.allows(current, when) {
+            return false;
+        }
+        if when <= SystemTime::now() {
+            shard.engine.del(key.clone()).await;
+            shard.engine.notify(Class::Generic, "del", &key);
+            shard.replicate(Operation::Del(vec![key])).await;
+        } else {
+            shard.engine.expire(key.clone(), Some(when)).await;
+            shard.engine.notify(Class::Generic, "expire", &key);
+            shard.replicate(Operation::PExpireAt(key, when)).await;
+        }
+        true
+    }
+
+    /// The absolute expiration time of `key`, `None` if the key does not exist.
+    pub async fn expiration(&self, key: String) -> Option<Option<SystemTime>> {
+        let shard = self.read().await;
+        shard.engine.expiration(&key).await
+    }
+
+    pub async fn persist(&mut self, key: String) -> bool {
+        let mut shard = self.write().await;
+        match shard.engine.expiration(&key).await {
+            Some(Some(_)) => {}
+            _ => return false,
         }
-        return count;
+        shard.engine.expire(key.clone(), None).await;
+        shard.engine.notify(Class::Generic, "persist", &key);
+        shard.replicate(Operation::Persist(key)).await;
+        true
     }
 
     pub async fn keys(&self) -> Vec<String> {
-        let shard = self.shard.read().await;
+        let shard = self.read().await;
         shard.engine.keys().await
     }
 
+    /// A page of keys from `cursor`, optionally restricted to the values of one type.
+    pub async fn scan(&self, cursor: u64, count: u64, type_name: Option<String>) -> (u64, Vec<String>) {
+        let shard = self.read().await;
+        let (next, page) = shard.engine.scan(cursor, count).await;
+        let keys = page.into_iter()
+            .filter(|(_, data)| type_name.as_ref().map(|name| data.type_name().eq_ignore_ascii_case(name)).unwrap_or(true))
+            .map(|(key, _)| key)
+            .collect();
+        (next, keys)
+    }
+
     pub async fn get_type(&self, key: String) -> &'static str {
-        let shard = self.shard.read().await;
+        let shard = self.read().await;
         let val = shard.engine.get(key).await;
         match val {
             Some(val) => val.type_name(),
@@ -88,232 +697,1272 @@ impl DB {
         }
     }
 
-    pub async fn xadd(&self, key: String, id: Option<(u64, Option<u64>)>, fields: Vec<(Bytes, Bytes)>) -> Result<(u64, u64), Error> {
-        let mut shard = self.shard.write().await;
-        match shard.engine.get(key.clone()).await {
-            Some(DataType::Stream(stream)) => {
-                match stream.add_entry(id, fields.clone()).await {
-                    Ok(id) => {
-                        if shard.role.is_master() {
-                            let data = Encoder::encode(&Operation::XAdd(key, Entry::new(id.0, id.1, fields)).encode());
-                            shard.role.replicate_data(Command::Simple(Simple::new(data.into()))).await;
-                        }
-                        Ok(id)
-                    }
-                    Err(e) => Err(Error::StreamError(e)),
-                }
-            }
-            None => {
-                let stream = stream::Stream::new();
-                let id = match stream.add_entry(id, fields.clone()).await {
-                    Ok(id) => id,
-                    Err(e) => return Err(Error::StreamError(e)),
-                };
-                shard.engine.set(key.clone(), DataType::Stream(stream), None).await;
-                if shard.role.is_master() {
-                    let data = Encoder::encode(&Operation::XAdd(key, Entry::new(id.0, id.1, fields)).encode());
-                    shard.role.replicate_data(Command::Simple(Simple::new(data.into()))).await;
-                }
-                Ok(id)
-            }
-            _ => Err(Error::InvalidType),
+    pub async fn push(&mut self, key: String, values: Vec<Bytes>, direction: list::Direction) -> Result<u64, Error> {
+        self.write().await.push(key, values, direction).await
+    }
+
+    pub async fn pop(&mut self, key: String, count: Option<u64>, direction: list::Direction) -> Result<Option<Vec<Bytes>>, Error> {
+        let mut shard = self.write().await;
+        let list = match shard.get_list(key.clone()).await? {
+            Some(list) => list,
+            None => return Ok(None),
+        };
+        let values = list.pop(count.unwrap_or(1), direction).await;
+        if !values.is_empty() {
+            shard.engine.notify(Class::List, pop_event(direction), &key);
+        }
+        shard.remove_if_empty(&key, list.is_empty().await).await;
+        if !values.is_empty() {
+            shard.replicate(Operation::Pop(key, values.len() as u64, direction)).await;
         }
+        Ok(Some(values))
     }
 
-    pub async fn xrange(&self, key: String, start: Option<(u64, Option<u64>)>, end: Option<(u64, Option<u64>)>, count: Option<u64>) -> Result<Vec<Entry>, Error> {
-        let shard = self.shard.read().await;
-        match shard.engine.get(key).await {
-            Some(DataType::Stream(stream)) => {
-                Ok(stream.range(start, end, count).await)
-            }
-            _ => Err(Error::InvalidType),
+    pub async fn lrange(&self, key: String, start: i64, stop: i64) -> Result<Vec<Bytes>, Error> {
+        let shard = self.read().await;
+        match shard.get_list(key).await? {
+            Some(list) => Ok(list.range(start, stop).await),
+            None => Ok(Vec::new()),
         }
     }
 
-    pub async fn xread(&self, query: Vec<(String, Option<(u64, Option<u64>)>)>, count: Option<u64>, block: Option<u64>) -> Result<Vec<Vec<Entry>>, Error> {
-        let shard = self.shard.read().await;
-        let (keys, ids): (Vec<String>, Vec<Option<(u64, Option<u64>)>>) = query.into_iter().unzip();
-        let mut streams = Vec::new();
-        for key in keys.into_iter() {
-            match shard.engine.get(key).await {
-                Some(DataType::Stream(stream)) => {
-                    streams.push(stream);
-                }
-                _ => return Err(Error::InvalidType),
-            }
-        }
-        let mut entries = Vec::new();
-        for (stream, id) in streams.iter().zip(ids.into_iter()) {
-            if let Some(id) = id {
-                let id = match id.1 {
-                    Some(seq) => {
-                        if seq == u64::MAX {
-                            (id.0 + 1, Some(0u64))
-                        } else {
-                            (id.0, Some(seq + 1))
-                        }
-                    }
-                    None => (id.0 + 1, None),
-                };
-                entries.push(stream.range(Some(id), None, count).await);
-            }else {
-                entries.push(Vec::new());
-            }
-        }
-        if let Some(block) = block {
-            for entry in entries.iter() {
-                if !entry.is_empty() {
-                    return Ok(entries);
-                }
-            }
-            let mut rx = Vec::new();
-            for stream in streams.iter() {
-                rx.push(stream.listen().await);
-            }
-            drop(shard); // release lock
-            let mut block_entries = Vec::new();
-            for entry in entries.into_iter() {
-                block_entries.push(Arc::new(Mutex::new(entry)));
-            }
-            let mut notifier = Notifier::new();
-            for (((key, rx), stream), entries) in rx.into_iter().zip(streams.into_iter()).zip(block_entries.iter()) {
-                let mut rx = rx;
-                let mut notifier = notifier.clone();
-                let entries = entries.clone();
-                tokio::spawn(async move {
-                    loop {
-                        select! {
-                            _ = notifier.wait() => {
-                                break;
-                            }
-                            entry = rx.recv() => {
-                                match entry {
-                                    Some(entry) => {
-                                        entries.lock().unwrap().push(entry);
-                                        notifier.notify_all();
-                                    }
-                                    None => {
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    stream.unlisten(key).await;
-                });
-            }
-            match block {
-                0 => { notifier.wait().await; },
-                block => {
-                    select! {
-                        _ = notifier.wait() => {}
-                        _ = tokio::time::sleep(Duration::from_millis(block)) => {}
-                    }
-                },
-            }
-            let mut entries = Vec::new();
-            for stream in block_entries.into_iter() {
-                if let Some(count) = count {
-                    let mut stream = stream.lock().unwrap();
-                    let len = stream.len();
-                    if len > count as usize {
-                        entries.push(stream.drain(len - count as usize..).collect());
-                    } else {
-                        entries.push(stream.drain(..).collect());
-                    }
-                } else {
-                    entries.push(stream.lock().unwrap().drain(..).collect());
-                }
-            }
-            Ok(entries)
-        } else {
-            Ok(entries)
+    pub async fn llen(&self, key: String) -> Result<u64, Error> {
+        let shard = self.read().await;
+        match shard.get_list(key).await? {
+            Some(list) => Ok(list.len().await),
+            None => Ok(0),
         }
     }
 
-    pub async fn rdb_sync(&self) -> crate::Result<()> {
-        let mut shard = self.shard.write().await;
-        shard.engine.write_rdb().await?;
-        shard.role.set_offset(0);
+    pub async fn lindex(&self, key: String, index: i64) -> Result<Option<Bytes>, Error> {
+        let shard = self.read().await;
+        match shard.get_list(key).await? {
+            Some(list) => Ok(list.index(index).await),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn lset(&mut self, key: String, index: i64, value: Bytes) -> Result<(), Error> {
+        let mut shard = self.write().await;
+        let list = shard.get_list(key.clone()).await?.ok_or(Error::NoSuchKey)?;
+        list.set(index, value.clone()).await.map_err(Error::ListError)?;
+        shard.engine.notify(Class::List, "lset", &key);
+        shard.replicate(Operation::LSet(key, index, value)).await;
         Ok(())
     }
 
-    pub async fn write_rdb_data(&self, data: &[u8]) -> crate::Result<()> {
-        let mut shard = self.shard.write().await;
-        shard.engine.write_rdb_data(data).await?;
-        shard.engine.load_rdb().await?;
-        shard.role.set_offset(0);
+    pub async fn lrem(&mut self, key: String, count: i64, value: Bytes) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let list = match shard.get_list(key.clone()).await? {
+            Some(list) => list,
+            None => return Ok(0),
+        };
+        let removed = list.remove(count, &value).await;
+        if removed > 0 {
+            shard.engine.notify(Class::List, "lrem", &key);
+        }
+        shard.remove_if_empty(&key, list.is_empty().await).await;
+        if removed > 0 {
+            shard.replicate(Operation::LRem(key, count, value)).await;
+        }
+        Ok(removed)
+    }
+
+    pub async fn ltrim(&mut self, key: String, start: i64, stop: i64) -> Result<(), Error> {
+        let mut shard = self.write().await;
+        let list = match shard.get_list(key.clone()).await? {
+            Some(list) => list,
+            None => return Ok(()),
+        };
+        list.trim(start, stop).await;
+        shard.engine.notify(Class::List, "ltrim", &key);
+        shard.remove_if_empty(&key, list.is_empty().await).await;
+        shard.replicate(Operation::LTrim(key, start, stop)).await;
         Ok(())
     }
 
-    pub async fn read_rdb(&self) -> crate::Result<Vec<u8>> {
-        let mut shard = self.shard.write().await;
-        shard.engine.write_rdb().await?;
-        shard.role.set_offset(0);
-        shard.engine.get_rdb().await
+    pub async fn hset(&mut self, key: String, pairs: Vec<(Bytes, Bytes)>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let hash = match shard.get_hash(key.clone()).await? {
+            Some(hash) => hash,
+            None => {
+                let hash = hash::Hash::new();
+                shard.engine.set(key.clone(), DataType::Hash(hash.clone()), None).await;
+                hash
+            }
+        };
+        let added = hash.set(pairs.clone()).await;
+        shard.engine.notify(Class::Hash, "hset", &key);
+        shard.replicate(Operation::HSet(key, pairs)).await;
+        Ok(added)
     }
 
-    pub async fn role(&self) -> Role {
-        let shard = self.shard.read().await;
-        shard.role.clone()
+    pub async fn hget(&self, key: String, field: Bytes) -> Result<Option<Bytes>, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.get(&field).await),
+            None => Ok(None),
+        }
     }
 
-    pub async fn add_slave(&self, key: String, con: &mut connection::Connection) -> crate::Result<Receiver<Command>> {
-        let mut shard = self.shard.write().await;
-        // send RDB file to slave
-        shard.engine.write_rdb().await?;
-        shard.role.set_offset(0);
-        let data = shard.engine.get_rdb().await?;
-        let resp = Type::RDBFile(data.into());
-        con.write_all(Encoder::encode(&resp).as_slice()).await?;
-        con.flush().await?;
-        // add slave to master
-        let (tx, rx) = channel::<Command>(32);
-        shard.role.add_slave(key, tx).await;
-        Ok(rx)
+    pub async fn hmget(&self, key: String, fields: Vec<Bytes>) -> Result<Vec<Option<Bytes>>, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.get_multiple(&fields).await),
+            None => Ok(vec![None; fields.len()]),
+        }
     }
 
-    pub async fn delete_slave(&self, key: &String) {
-        let mut shard = self.shard.write().await;
-        shard.role.delete_slave(key).await;
+    pub async fn hdel(&mut self, key: String, fields: Vec<Bytes>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let hash = match shard.get_hash(key.clone()).await? {
+            Some(hash) => hash,
+            None => return Ok(0),
+        };
+        let removed = hash.remove(&fields).await;
+        if removed > 0 {
+            shard.engine.notify(Class::Hash, "hdel", &key);
+        }
+        shard.remove_if_empty(&key, hash.is_empty().await).await;
+        if removed > 0 {
+            shard.replicate(Operation::HDel(key, fields)).await;
+        }
+        Ok(removed)
     }
 
-    pub async fn slave_count(&self) -> u64 {
-        let shard = self.shard.read().await;
-        shard.role.slave_count().await
+    pub async fn hgetall(&self, key: String) -> Result<Vec<(Bytes, Bytes)>, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.pairs().await),
+            None => Ok(Vec::new()),
+        }
     }
 
-    pub async fn sync_replication(&self, sync: Synchronization) {
-        let mut shard = self.shard.write().await;
-        shard.role.replicate_data(Command::Synchronization(sync)).await;
+    pub async fn hkeys(&self, key: String) -> Result<Vec<Bytes>, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.fields().await),
+            None => Ok(Vec::new()),
+        }
     }
 
-    pub async fn dir(&self) -> String {
-        let shard = self.shard.read().await;
-        shard.engine.dir()
+    pub async fn hvals(&self, key: String) -> Result<Vec<Bytes>, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.values().await),
+            None => Ok(Vec::new()),
+        }
     }
 
-    pub async fn file_name(&self) -> String {
-        let shard = self.shard.read().await;
-        shard.engine.file_name()
+    pub async fn hlen(&self, key: String) -> Result<u64, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.len().await),
+            None => Ok(0),
+        }
     }
-}
 
-enum Operation {
-    Set(String, string::String),
-    Del(Vec<String>),
-    XAdd(String, Entry),
-}
+    pub async fn hscan(&self, key: String, cursor: u64, count: u64) -> Result<(u64, Vec<(Bytes, Bytes)>), Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.scan(cursor, count).await),
+            None => Ok((0, Vec::new())),
+        }
+    }
+
+    pub async fn hexists(&self, key: String, field: Bytes) -> Result<bool, Error> {
+        let shard = self.read().await;
+        match shard.get_hash(key).await? {
+            Some(hash) => Ok(hash.contains(&field).await),
+            None => Ok(false),
+        }
+    }
+
+    pub async fn hincrby(&mut self, key: String, field: Bytes, increment: i64) -> Result<i64, Error> {
+        let mut shard = self.write().await;
+        let (hash, created) = match shard.get_hash(key.clone()).await? {
+            Some(hash) => (hash, false),
+            None => (hash::Hash::new(), true),
+        };
+        let value = hash.incr_by(field.clone(), increment).await.map_err(Error::HashError)?;
+        if created {
+            shard.engine.set(key.clone(), DataType::Hash(hash), None).await;
+        }
+        shard.engine.notify(Class::Hash, "hincrby", &key);
+        shard.replicate(Operation::HIncrBy(key, field, increment)).await;
+        Ok(value)
+    }
+
+    pub async fn sadd(&mut self, key: String, members: Vec<Bytes>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let set = match shard.get_set(key.clone()).await? {
+            Some(set) => set,
+            None => {
+                let set = set::Set::new();
+                shard.engine.set(key.clone(), DataType::Set(set.clone()), None).await;
+                set
+            }
+        };
+        let added = set.add(members).await;
+        let count = added.len() as u64;
+        if count > 0 {
+            shard.engine.notify(Class::Set, "sadd", &key);
+            shard.replicate(Operation::SAdd(key, added)).await;
+        }
+        Ok(count)
+    }
+
+    pub async fn srem(&mut self, key: String, members: Vec<Bytes>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let set = match shard.get_set(key.clone()).await? {
+            Some(set) => set,
+            None => return Ok(0),
+        };
+        let removed = set.remove(&members).await;
+        if removed > 0 {
+            shard.engine.notify(Class::Set, "srem", &key);
+        }
+        shard.remove_if_empty(&key, set.is_empty().await).await;
+        if removed > 0 {
+            shard.replicate(Operation::SRem(key, members)).await;
+        }
+        Ok(removed)
+    }
+
+    pub async fn smembers(&self, key: String) -> Result<Vec<Bytes>, Error> {
+        let shard = self.read().await;
+        match shard.get_set(key).await? {
+            Some(set) => Ok(set.members().await),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub async fn sismember(&self, key: String, member: Bytes) -> Result<bool, Error> {
+        let shard = self.read().await;
+        match shard.get_set(key).await? {
+            Some(set) => Ok(set.contains(&member).await),
+            None => Ok(false),
+        }
+    }
+
+    pub async fn sscan(&self, key: String, cursor: u64, count: u64) -> Result<(u64, Vec<Bytes>), Error> {
+        let shard = self.read().await;
+        match shard.get_set(key).await? {
+            Some(set) => Ok(set.scan(cursor, count).await),
+            None => Ok((0, Vec::new())),
+        }
+    }
+
+    pub async fn scard(&self, key: String) -> Result<u64, Error> {
+        let shard = self.read().await;
+        match shard.get_set(key).await? {
+            Some(set) => Ok(set.len().await),
+            None => Ok(0),
+        }
+    }
+
+    pub async fn set_combine(&self, keys: Vec<String>, algebra: set::Algebra) -> Result<Vec<Bytes>, Error> {
+        let shard = self.read().await;
+        let mut sets = Vec::with_capacity(keys.len());
+        for key in keys.into_iter() {
+            sets.push(shard.get_set(key).await?);
+        }
+        Ok(set::combine(&sets, algebra).await)
+    }
+
+    pub async fn set_combine_store(&mut self, destination: String, keys: Vec<String>, algebra: set::Algebra) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let mut sets = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            sets.push(shard.get_set(key.clone()).await?);
+        }
+        let members = set::combine(&sets, algebra).await;
+        let len = members.len() as u64;
+        if members.is_empty() {
+            if shard.engine.del(destination.clone()).await {
+                shard.engine.notify(Class::Generic, "del", &destination);
+            }
+        } else {
+            shard.engine.set(destination.clone(), DataType::Set(set::Set::from(members)), None).await;
+            let event = match algebra {
+                set::Algebra::Inter => "sinterstore",
+                set::Algebra::Union => "sunionstore",
+                set::Algebra::Diff => "sdiffstore",
+            };
+            shard.engine.notify(Class::Set, event, &destination);
+        }
+        shard.replicate(Operation::SetCombineStore(destination, keys, algebra)).await;
+        Ok(len)
+    }
+
+    pub async fn zadd(&mut self, key: String, pairs: Vec<(f64, Bytes)>, options: sorted_set::AddOptions) -> Result<Vec<sorted_set::Outcome>, Error> {
+        let mut shard = self.write().await;
+        let (zset, created) = match shard.get_sorted_set(key.clone()).await? {
+            Some(zset) => (zset, false),
+            None => (sorted_set::SortedSet::new(), true),
+        };
+        let mut outcomes = Vec::with_capacity(pairs.len());
+        let mut changes = Vec::new();
+        for (score, member) in pairs.into_iter() {
+            let outcome = zset.add(member.clone(), score, options).await.map_err(Error::SortedSetError)?;
+            if let sorted_set::Outcome::Added(score) | sorted_set::Outcome::Updated(score) = outcome {
+                changes.push((score, member));
+            }
+            outcomes.push(outcome);
+        }
+        if created && !zset.is_empty().await {
+            shard.engine.set(key.clone(), DataType::SortedSet(zset), None).await;
+        }
+        if !changes.is_empty() {
+            shard.engine.notify(Class::SortedSet, if options.incr { "zincr" } else { "zadd" }, &key);
+            shard.replicate(Operation::ZAdd(key, changes)).await;
+        }
+        Ok(outcomes)
+    }
+
+    /// Add `increment` to the score of `member` as ZADD INCR does, returns the new score.
+    pub async fn zincrby(&mut self, key: String, increment: f64, member: Bytes) -> Result<Option<f64>, Error> {
+        let options = sorted_set::AddOptions { incr: true, ..Default::default() };
+        let outcomes = self.zadd(key, vec![(increment, member)], options).await?;
+        Ok(outcomes.first().and_then(sorted_set::Outcome::score))
+    }
+
+    pub async fn zrem(&mut self, key: String, members: Vec<Bytes>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let zset = match shard.get_sorted_set(key.clone()).await? {
+            Some(zset) => zset,
+            None => return Ok(0),
+        };
+        let removed = zset.remove(&members).await;
+        if !removed.is_empty() {
+            shard.engine.notify(Class::SortedSet, "zrem", &key);
+        }
+        shard.remove_if_empty(&key, zset.is_empty().await).await;
+        let count = removed.len() as u64;
+        if !removed.is_empty() {
+            shard.replicate(Operation::ZRem(key, removed)).await;
+        }
+        Ok(count)
+    }
+
+    pub async fn zscore(&self, key: String, member: Bytes) -> Result<Option<f64>, Error> {
+        let shard = self.read().await;
+        match shard.get_sorted_set(key).await? {
+            Some(zset) => Ok(zset.score(&member).await),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn zrank(&self, key: String, member: Bytes, rev: bool) -> Result<Option<u64>, Error> {
+        let shard = self.read().await;
+        match shard.get_sorted_set(key).await? {
+            Some(zset) => Ok(zset.rank(&member, rev).await),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn zscan(&self, key: String, cursor: u64, count: u64) -> Result<(u64, Vec<(Bytes, f64)>), Error> {
+        let shard = self.read().await;
+        match shard.get_sorted_set(key).await? {
+            Some(zset) => Ok(zset.scan(cursor, count).await),
+            None => Ok((0, Vec::new())),
+        }
+    }
+
+    pub async fn zcard(&self, key: String) -> Result<u64, Error> {
+        let shard = self.read().await;
+        match shard.get_sorted_set(key).await? {
+            Some(zset) => Ok(zset.len().await),
+            None => Ok(0),
+        }
+    }
+
+    pub async fn zrange(&self, key: String, range: sorted_set::Range, rev: bool, limit: Option<(i64, i64)>) -> Result<Vec<(Bytes, f64)>, Error> {
+        let shard = self.read().await;
+        match shard.get_sorted_set(key).await? {
+            Some(zset) => Ok(zset.range(&range, rev, limit).await),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Append an entry to a stream, then trim it. Returns `None` when the key does not exist and `nomkstream` forbids creating it.
+    pub async fn xadd(&mut self, key: String, id: Option<(u64, Option<u64>)>, fields: Vec<(Bytes, Bytes)>, nomkstream: bool, trim: Option<stream::Trim>) -> Result<Option<(u64, u64)>, Error> {
+        let mut shard = self.write().await;
+        let (stream, created) = match shard.get_stream(key.clone()).await? {
+            Some(stream) => (stream, false),
+            None if nomkstream => return Ok(None),
+            None => (stream::Stream::new(), true),
+        };
+        let id = stream.add_entry(id, fields.clone()).await.map_err(Error::StreamError)?;
+        if created {
+            shard.engine.set(key.clone(), DataType::Stream(stream.clone()), None).await;
+        }
+        shard.engine.notify(Class::Stream, "xadd", &key);
+        let mut trimmed = None;
+        if let Some(trim) = trim {
+            if stream.trim(&trim).await > 0 {
+                shard.engine.notify(Class::Stream, "xtrim", &key);
+                trimmed = Some(stream.len().await);
+            }
+        }
+        shard.replicate(Operation::XAdd(key, Entry::new(id.0, id.1, fields), trimmed)).await;
+        Ok(Some(id))
+    }
+
+    pub async fn xlen(&self, key: String) -> Result<u64, Error> {
+        let shard = self.read().await;
+        match shard.get_stream(key).await? {
+            Some(stream) => Ok(stream.len().await),
+            None => Ok(0),
+        }
+    }
+
+    /// Delete entries of a stream, returns how many existed.
+    pub async fn xdel(&mut self, key: String, ids: Vec<(u64, u64)>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let stream = match shard.get_stream(key.clone()).await? {
+            Some(stream) => stream,
+            None => return Ok(0),
+        };
+        let deleted = stream.delete(&ids).await;
+        if deleted > 0 {
+            shard.engine.notify(Class::Stream, "xdel", &key);
+            shard.replicate(Operation::XDel(key, ids)).await;
+        }
+        Ok(deleted)
+    }
+
+    /// Trim a stream, returns how many entries were removed.
+    pub async fn xtrim(&mut self, key: String, trim: stream::Trim) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let stream = match shard.get_stream(key.clone()).await? {
+            Some(stream) => stream,
+            None => return Ok(0),
+        };
+        let removed = stream.trim(&trim).await;
+        if removed > 0 {
+            shard.engine.notify(Class::Stream, "xtrim", &key);
+            // an approximate trim depends on how the entries are stored, replicas trim to the same length
+            shard.replicate(Operation::XTrim(key, stream.len().await)).await;
+        }
+        Ok(removed)
+    }
+
+    pub async fn xsetid(&mut self, key: String, id: (u64, u64), added: Option<u64>, max_deleted_id: Option<(u64, u64)>) -> Result<(), Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_stream(key.clone()).await?.ok_or(Error::NoSuchKey)?;
+        stream.set_id(id, added, max_deleted_id).await.map_err(Error::StreamError)?;
+        shard.engine.notify(Class::Stream, "xsetid", &key);
+        let (id, added, max_deleted_id) = stream.ids().await;
+        shard.replicate(Operation::XSetID(key, id, added, max_deleted_id)).await;
+        Ok(())
+    }
+
+    /// The entries of a stream from `start` to `end`, the greatest first when `rev`.
+    pub async fn xrange(&self, key: String, start: Option<(u64, Option<u64>)>, end: Option<(u64, Option<u64>)>, count: Option<u64>, rev: bool) -> Result<Vec<Entry>, Error> {
+        let shard = self.read().await;
+        match shard.get_stream(key).await? {
+            Some(stream) => Ok(stream.range(start, end, count, rev).await),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub async fn xinfo_stream(&self, key: String) -> Result<stream::Info, Error> {
+        let shard = self.read().await;
+        let stream = shard.get_stream(key).await?.ok_or(Error::NoSuchKey)?;
+        Ok(stream.info().await)
+    }
+
+    pub async fn xinfo_groups(&self, key: String) -> Result<Vec<stream::GroupInfo>, Error> {
+        let shard = self.read().await;
+        let stream = shard.get_stream(key).await?.ok_or(Error::NoSuchKey)?;
+        Ok(stream.groups_info().await)
+    }
+
+    pub async fn xinfo_consumers(&self, key: String, group: String) -> Result<Vec<stream::ConsumerInfo>, Error> {
+        let shard = self.read().await;
+        let stream = shard.get_group_stream(&key, &group).await?;
+        stream.consumers_info(&group).await.map_err(|e| group_error(&key, &group, e))
+    }
+
+    /// The entries of each stream after its ID, `None` standing for the last ID. With `block`, waits
+    /// up to that many milliseconds, 0 meaning forever, for an entry when none is available yet.
+    pub async fn xread(&mut self, query: Vec<(String, Option<stream::ID>)>, count: Option<u64>, block: Option<u64>, closed: impl Future<Output = ()>) -> Result<Vec<Vec<Entry>>, Error> {
+        let shard = self.read().await;
+        let mut starts = Vec::with_capacity(query.len());
+        let mut entries = Vec::with_capacity(query.len());
+        for (key, id) in query.into_iter() {
+            let stream = shard.get_stream(key.clone()).await?;
+            let last = match &stream {
+                Some(stream) => stream.last_id().await,
+                None => (0, 0),
+            };
+            // reads start right after the given ID
+            let start = match id.unwrap_or((last.0, Some(last.1))) {
+                (time, Some(u64::MAX)) => (time + 1, Some(0)),
+                (time, Some(seq)) => (time, Some(seq + 1)),
+                (time, None) => (time + 1, None),
+            };
+            entries.push(match stream {
+                Some(stream) => stream.range(Some(start), None, count, false).await,
+                None => Vec::new(),
+            });
+            starts.push((key, start));
+        }
+        drop(shard);
+        let block = match block {
+            Some(block) if entries.iter().all(|entries| entries.is_empty()) => block,
+            _ => return Ok(entries),
+        };
+        let keys: Vec<String> = starts.iter().map(|(key, _)| key.clone()).collect();
+        let timeout = if block == 0 { None } else { Some(Duration::from_millis(block)) };
+        if let Some(Served::Read(key, read)) = self.block(keys.clone(), Blocking::Read(starts, count), true, timeout, closed).await? {
+            for (entries, other) in entries.iter_mut().zip(keys.iter()) {
+                if *other == key {
+                    *entries = read;
+                    break;
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    pub async fn xgroup_create(&mut self, key: String, group: String, id: Option<(u64, u64)>, mkstream: bool, entries_read: Option<u64>) -> Result<(), Error> {
+        let mut shard = self.write().await;
+        let stream = match shard.get_stream(key.clone()).await? {
+            Some(stream) => stream,
+            None if mkstream => {
+                let stream = stream::Stream::new();
+                shard.engine.set(key.clone(), DataType::Stream(stream.clone()), None).await;
+                stream
+            }
+            None => return Err(Error::NoStream),
+        };
+        let id = stream.create_group(&group, id, entries_read).await.map_err(Error::StreamError)?;
+        shard.engine.notify(Class::Stream, "xgroup-create", &key);
+        shard.replicate(Operation::XGroupCreate(key, group, id, mkstream, entries_read)).await;
+        Ok(())
+    }
+
+    /// Set the last delivered ID of a group, `None` standing for the last entry.
+    pub async fn xgroup_setid(&mut self, key: String, group: String, id: Option<(u64, u64)>, entries_read: Option<u64>) -> Result<(), Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_stream(key.clone()).await?.ok_or(Error::NoStream)?;
+        let id = stream.set_group_id(&group, id, entries_read).await.map_err(|e| group_error(&key, &group, e))?;
+        shard.engine.notify(Class::Stream, "xgroup-setid", &key);
+        shard.replicate(Operation::XGroupSetID(key, group, id, entries_read)).await;
+        Ok(())
+    }
+
+    pub async fn xgroup_destroy(&mut self, key: String, group: String) -> Result<bool, Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_stream(key.clone()).await?.ok_or(Error::NoStream)?;
+        if !stream.destroy_group(&group).await {
+            return Ok(false);
+        }
+        shard.engine.notify(Class::Stream, "xgroup-destroy", &key);
+        shard.replicate(Operation::XGroupDestroy(key, group)).await;
+        Ok(true)
+    }
+
+    pub async fn xgroup_create_consumer(&mut self, key: String, group: String, consumer: String) -> Result<bool, Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_stream(key.clone()).await?.ok_or(Error::NoStream)?;
+        let created = stream.create_consumer(&group, &consumer).await.map_err(|e| group_error(&key, &group, e))?;
+        if created {
+            shard.engine.notify(Class::Stream, "xgroup-createconsumer", &key);
+            shard.replicate(Operation::XGroupCreateConsumer(key, group, consumer)).await;
+        }
+        Ok(created)
+    }
+
+    /// Delete a consumer of a group, returns how many entries it had pending.
+    pub async fn xgroup_del_consumer(&mut self, key: String, group: String, consumer: String) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_stream(key.clone()).await?.ok_or(Error::NoStream)?;
+        match stream.delete_consumer(&group, &consumer).await.map_err(|e| group_error(&key, &group, e))? {
+            Some(pending) => {
+                shard.engine.notify(Class::Stream, "xgroup-delconsumer", &key);
+                shard.replicate(Operation::XGroupDelConsumer(key, group, consumer)).await;
+                Ok(pending)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// The entries of each stream for a consumer of `group`: the new ones for a `None` ID, otherwise the
+    /// consumer's pending entries after the ID. With `block`, waits like XREAD when every ID is `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn xreadgroup(&mut self, group: String, consumer: String, query: Vec<(String, Option<(u64, u64)>)>, count: Option<u64>, block: Option<u64>, noack: bool, closed: impl Future<Output = ()>) -> Result<Vec<Vec<Entry>>, Error> {
+        let mut shard = self.write().await;
+        // fail before delivering anything when a key or group is missing
+        for (key, _) in query.iter() {
+            shard.get_group_stream(key, &group).await?;
+        }
+        let mut entries = Vec::with_capacity(query.len());
+        for (key, start) in query.iter() {
+            entries.push(shard.read_group(key, &group, &consumer, *start, count, noack).await?);
+        }
+        drop(shard);
+        let block = match block {
+            Some(block) if query.iter().all(|(_, start)| start.is_none()) && entries.iter().all(|entries| entries.is_empty()) => block,
+            _ => return Ok(entries),
+        };
+        let keys: Vec<String> = query.into_iter().map(|(key, _)| key).collect();
+        let timeout = if block == 0 { None } else { Some(Duration::from_millis(block)) };
+        if let Some(Served::Read(key, read)) = self.block(keys.clone(), Blocking::ReadGroup(group, consumer, count, noack), true, timeout, closed).await? {
+            for (entries, other) in entries.iter_mut().zip(keys.iter()) {
+                if *other == key {
+                    *entries = read;
+                    break;
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Acknowledge pending entries of a group, returns how many were pending.
+    pub async fn xack(&mut self, key: String, group: String, ids: Vec<(u64, u64)>) -> Result<u64, Error> {
+        let mut shard = self.write().await;
+        let stream = match shard.get_stream(key.clone()).await? {
+            Some(stream) => stream,
+            None => return Ok(0),
+        };
+        let acked = match stream.ack(&group, &ids).await {
+            Ok(acked) => acked,
+            Err(_) => return Ok(0),
+        };
+        if acked > 0 {
+            shard.replicate(Operation::XAck(key, group, ids)).await;
+        }
+        Ok(acked)
+    }
+
+    pub async fn xpending_summary(&self, key: String, group: String) -> Result<stream::PendingSummary, Error> {
+        let shard = self.read().await;
+        let stream = shard.get_group_stream(&key, &group).await?;
+        stream.pending_summary(&group).await.map_err(|e| group_error(&key, &group, e))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn xpending(&self, key: String, group: String, min_idle: Option<u64>, start: Option<stream::ID>, end: Option<stream::ID>, count: u64, consumer: Option<String>) -> Result<Vec<stream::Pending>, Error> {
+        let shard = self.read().await;
+        let stream = shard.get_group_stream(&key, &group).await?;
+        stream.pending(&group, min_idle, start, end, count, consumer.as_deref()).await
+            .map_err(|e| group_error(&key, &group, e))
+    }
+
+    /// Transfer pending entries idle for at least `min_idle` milliseconds to `consumer`, returns the claimed entries.
+    pub async fn xclaim(&mut self, key: String, group: String, consumer: String, min_idle: u64, ids: Vec<(u64, u64)>, options: stream::ClaimOptions) -> Result<Vec<Entry>, Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_group_stream(&key, &group).await?;
+        let claimed = stream.claim(&group, &consumer, min_idle, &ids, &options).await
+            .map_err(|e| group_error(&key, &group, e))?;
+        shard.propagate_claimed(&key, &group, &consumer, &claimed).await;
+        if options.last_id.is_some() {
+            if let Some((last_id, entries_read)) = stream.group_progress(&group).await {
+                shard.propagate(Operation::XGroupSetID(key, group, last_id, entries_read)).await;
+            }
+        }
+        Ok(claimed.entries)
+    }
+
+    /// Claim up to `count` pending entries idle for at least `min_idle` milliseconds, scanning from `start`.
+    /// Returns the ID to continue from, the claimed entries and the IDs of the deleted entries dropped.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn xautoclaim(&mut self, key: String, group: String, consumer: String, min_idle: u64, start: (u64, u64), count: u64, justid: bool) -> Result<((u64, u64), Vec<Entry>, Vec<(u64, u64)>), Error> {
+        let mut shard = self.write().await;
+        let stream = shard.get_group_stream(&key, &group).await?;
+        let (next, claimed) = stream.auto_claim(&group, &consumer, min_idle, start, count, justid).await
+            .map_err(|e| group_error(&key, &group, e))?;
+        shard.propagate_claimed(&key, &group, &consumer, &claimed).await;
+        Ok((next, claimed.entries, claimed.deleted))
+    }
+
+    /// Run `request` on the first ready of `keys`. With `wait`, when none is ready, wait until a write
+    /// makes one of them ready, up to `timeout` or until `closed` tells the client went away. Clients
+    /// blocked on a key are served in the order they blocked.
+    pub async fn block(&mut self, keys: Vec<String>, request: Blocking, wait: bool, timeout: Option<Duration>, closed: impl Future<Output = ()>) -> Result<Option<Served>, Error> {
+        // a transaction holds the lock for its whole duration, so it never blocks
+        let blocking = wait && self.exclusive.is_none();
+        let mut shard = self.write().await;
+        for key in keys.iter() {
+            shard.check_blocking_type(key, &request).await?;
+        }
+        for key in keys.iter() {
+            if let Some(served) = shard.serve(key, &request).await? {
+                return Ok(Some(served));
+            }
+        }
+        if !blocking {
+            return Ok(None);
+        }
+        let (reply, mut served) = oneshot::channel();
+        let index = shard.engine.index();
+        let id = shard.blocked.block(index, &keys, Pending { request: request.clone(), reply });
+        drop(shard);
+        let mut hung_up = false;
+        let result = tokio::select! {
+            biased;
+            _ = closed => {
+                hung_up = true;
+                None
+            }
+            result = async {
+                match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, &mut served).await.ok(),
+                    None => Some((&mut served).await),
+                }
+            } => result,
+        };
+        match result {
+            Some(Ok(served)) => served.map(Some),
+            _ => {
+                let mut shard = self.write().await;
+                shard.blocked.unblock(id);
+                // a write may have served the request before it was unblocked
+                match served.try_recv() {
+                    Ok(Ok(served)) if hung_up => {
+                        shard.restore(&request, served).await;
+                        Ok(None)
+                    }
+                    Ok(served) => served.map(Some),
+                    Err(_) => Ok(None),
+                }
+            }
+        }
+    }
+
+    pub async fn rdb_sync(&mut self) -> crate::Result<()> {
+        let mut shard = self.write().await;
+        shard.engine.write_rdb().await?;
+        shard.role.set_offset(0);
+        Ok(())
+    }
+
+    pub async fn write_rdb_data(&mut self, data: &[u8]) -> crate::Result<()> {
+        let mut shard = self.write().await;
+        shard.engine.write_rdb_data(data).await?;
+        shard.engine.load_rdb().await?;
+        shard.role.set_offset(0);
+        // the data of the master replaces what the append only file logged
+        if shard.aof.is_some() {
+            shard.rewrite_aof(true).await?;
+        }
+        Ok(())
+    }
+
+    /// Save the databases to the RDB file, writes wait until it is written.
+    pub async fn save(&mut self) -> Result<(), Error> {
+        let shard = self.read().await;
+        if shard.engine.saving() {
+            return Err(Error::SaveInProgress);
+        }
+        shard.engine.write_rdb().await.map_err(|e| Error::SaveFailed(e.to_string()))
+    }
+
+    /// Save the databases to the RDB file in the background, from a copy taken between two commands.
+    pub async fn bgsave(&mut self) -> Result<(), Error> {
+        let shard = self.read().await;
+        match shard.engine.bgsave().await {
+            true => Ok(()),
+            false => Err(Error::SaveInProgress),
+        }
+    }
+
+    /// When the databases were last saved, in UNIX seconds.
+    pub fn lastsave(&self) -> u64 {
+        self.engine.last_save()
+    }
+
+    pub fn save_points(&self) -> SavePoints {
+        self.engine.save_points()
+    }
+
+    /// Rewrite the append only file in the background from a snapshot of the data.
+    pub async fn bgrewriteaof(&mut self) -> Result<(), Error> {
+        let mut shard = self.write().await;
+        shard.rewrite_aof(false).await
+    }
+
+    /// The settings of the append only file, `None` when it is disabled.
+    pub async fn aof_config(&self) -> Option<aof::Config> {
+        let shard = self.read().await;
+        shard.aof.as_ref().map(|aof| aof.config())
+    }
+
+    pub async fn read_rdb(&mut self) -> crate::Result<Vec<u8>> {
+        let mut shard = self.write().await;
+        shard.engine.write_rdb().await?;
+        shard.role.set_offset(0);
+        shard.engine.get_rdb().await
+    }
+
+    pub async fn role(&self) -> Role {
+        let shard = self.read().await;
+        shard.role.clone()
+    }
+
+    pub async fn add_slave(&mut self, key: String, con: &mut connection::Connection) -> crate::Result<Receiver<Command>> {
+        let mut shard = self.write().await;
+        // send RDB file to slave
+        shard.engine.write_rdb().await?;
+        shard.role.set_offset(0);
+        let data = shard.engine.get_rdb().await?;
+        let resp = Type::RDBFile(data.into());
+        con.write_all(Encoder::encode(&resp).as_slice()).await?;
+        con.flush().await?;
+        // add slave to master, its stream starts on database 0
+        shard.replicated_db = None;
+        let (tx, rx) = channel::<Command>(32);
+        shard.role.add_slave(key, tx).await;
+        Ok(rx)
+    }
+
+    pub async fn delete_slave(&mut self, key: &String) {
+        let mut shard = self.write().await;
+        shard.role.delete_slave(key).await;
+    }
+
+    pub async fn slave_count(&self) -> u64 {
+        let shard = self.read().await;
+        shard.role.slave_count().await
+    }
+
+    pub async fn sync_replication(&mut self, sync: Synchronization) {
+        let mut shard = self.write().await;
+        shard.role.replicate_data(Command::Synchronization(sync)).await;
+    }
+
+    pub async fn dir(&self) -> String {
+        let shard = self.read().await;
+        shard.engine.dir()
+    }
+
+    pub async fn file_name(&self) -> String {
+        let shard = self.read().await;
+        shard.engine.file_name()
+    }
+}
+
+impl<L: Deref<Target = Shard>> Guard<L> {
+    async fn get_string(&self, key: String) -> Result<Option<string::String>, Error> {
+        match self.engine.get(key).await {
+            Some(DataType::String(string)) => Ok(Some(string)),
+            None => Ok(None),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    async fn get_list(&self, key: String) -> Result<Option<list::List>, Error> {
+        match self.engine.get(key).await {
+            Some(DataType::List(list)) => Ok(Some(list)),
+            None => Ok(None),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    async fn get_hash(&self, key: String) -> Result<Option<hash::Hash>, Error> {
+        match self.engine.get(key).await {
+            Some(DataType::Hash(hash)) => Ok(Some(hash)),
+            None => Ok(None),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    async fn get_set(&self, key: String) -> Result<Option<set::Set>, Error> {
+        match self.engine.get(key).await {
+            Some(DataType::Set(set)) => Ok(Some(set)),
+            None => Ok(None),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    async fn get_sorted_set(&self, key: String) -> Result<Option<sorted_set::SortedSet>, Error> {
+        match self.engine.get(key).await {
+            Some(DataType::SortedSet(zset)) => Ok(Some(zset)),
+            None => Ok(None),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    async fn get_stream(&self, key: String) -> Result<Option<stream::Stream>, Error> {
+        match self.engine.get(key).await {
+            Some(DataType::Stream(stream)) => Ok(Some(stream)),
+            None => Ok(None),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    /// A blocking command fails right away on a key of the wrong type, instead of waiting on it.
+    async fn check_blocking_type(&self, key: &str, request: &Blocking) -> Result<(), Error> {
+        match request {
+            Blocking::Pop(..) | Blocking::Move(..) => self.get_list(key.to_string()).await.map(|_| ()),
+            Blocking::Read(..) | Blocking::ReadGroup(..) => self.get_stream(key.to_string()).await.map(|_| ()),
+        }
+    }
+
+    /// The stream of a consumer group command, which fails unless both the key and the group exist.
+    async fn get_group_stream(&self, key: &str, group: &str) -> Result<stream::Stream, Error> {
+        match self.get_stream(key.to_string()).await? {
+            Some(stream) if stream.has_group(group).await => Ok(stream),
+            _ => Err(Error::NoGroup(key.to_string(), group.to_string())),
+        }
+    }
+
+}
+
+impl<L: DerefMut<Target = Shard>> Guard<L> {
+    /// Run a blocking `request` on `key`, `None` while the key is not ready.
+    async fn push(&mut self, key: String, values: Vec<Bytes>, direction: list::Direction) -> Result<u64, Error> {
+        let list = match self.get_list(key.clone()).await? {
+            Some(list) => list,
+            None => {
+                let list = list::List::new();
+                self.engine.set(key.clone(), DataType::List(list.clone()), None).await;
+                list
+            }
+        };
+        let len = list.push(values.clone(), direction).await;
+        self.engine.notify(Class::List, push_event(direction), &key);
+        self.replicate(Operation::Push(key, values, direction)).await;
+        Ok(len)
+    }
+
+    /// Give back what was served to a client that closed the connection before getting the reply. Only
+    /// popped elements would be lost, a moved one stays in its destination and read entries in the stream.
+    async fn restore(&mut self, request: &Blocking, served: Served) {
+        if let (Blocking::Pop(direction, _), Served::Popped(key, mut values)) = (request, served) {
+            // pushed back in reverse to restore the order they were popped in
+            values.reverse();
+            let _ = self.push(key, values, *direction).await;
+        }
+    }
+
+    async fn serve(&mut self, key: &str, request: &Blocking) -> Result<Option<Served>, Error> {
+        match request {
+            Blocking::Pop(direction, count) => {
+                let list = match self.get_list(key.to_string()).await {
+                    Ok(Some(list)) => list,
+                    _ => return Ok(None),
+                };
+                let values = list.pop(*count, *direction).await;
+                if values.is_empty() {
+                    return Ok(None);
+                }
+                self.engine.notify(Class::List, pop_event(*direction), key);
+                self.remove_if_empty(key, list.is_empty().await).await;
+                self.propagate(Operation::Pop(key.to_string(), values.len() as u64, *direction)).await;
+                Ok(Some(Served::Popped(key.to_string(), values)))
+            }
+            Blocking::Move(from, destination, to) => {
+                let list = match self.get_list(key.to_string()).await {
+                    Ok(Some(list)) if !list.is_empty().await => list,
+                    _ => return Ok(None),
+                };
+                let target = self.get_list(destination.clone()).await?;
+                let value = match list.pop(1, *from).await.pop() {
+                    Some(value) => value,
+                    None => return Ok(None),
+                };
+                self.engine.notify(Class::List, pop_event(*from), key);
+                let target = match target {
+                    Some(target) => target,
+                    None => {
+                        let target = list::List::new();
+                        self.engine.set(destination.clone(), DataType::List(target.clone()), None).await;
+                        target
+                    }
+                };
+                target.push(vec![value.clone()], *to).await;
+                self.engine.notify(Class::List, push_event(*to), destination);
+                self.remove_if_empty(key, list.is_empty().await).await;
+                self.propagate(Operation::LMove(key.to_string(), destination.clone(), *from, *to)).await;
+                Ok(Some(Served::Moved(value)))
+            }
+            Blocking::Read(starts, count) => {
+                let start = match starts.iter().find(|(other, _)| other == key) {
+                    Some((_, start)) => *start,
+                    None => return Ok(None),
+                };
+                let stream = match self.get_stream(key.to_string()).await {
+                    Ok(Some(stream)) => stream,
+                    _ => return Ok(None),
+                };
+                let entries = stream.range(Some(start), None, *count, false).await;
+                if entries.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(Served::Read(key.to_string(), entries)))
+            }
+            Blocking::ReadGroup(group, consumer, count, noack) => {
+                if !matches!(self.get_stream(key.to_string()).await, Ok(Some(_))) {
+                    return Ok(None);
+                }
+                let entries = self.read_group(key, group, consumer, None, *count, *noack).await?;
+                if entries.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(Served::Read(key.to_string(), entries)))
+            }
+        }
+    }
+
+    /// Read the entries of `key` for a consumer of `group`, the new ones without `start`, and replicate
+    /// the deliveries.
+    async fn read_group(&mut self, key: &str, group: &str, consumer: &str, start: Option<(u64, u64)>, count: Option<u64>, noack: bool) -> Result<Vec<Entry>, Error> {
+        let stream = self.get_group_stream(key, group).await?;
+        let claimed = stream.read_group(group, consumer, start, count, noack).await
+            .map_err(|e| group_error(key, group, e))?;
+        self.propagate_claimed(key, group, consumer, &claimed).await;
+        if let (None, Some(_)) = (start, claimed.entries.last()) {
+            if let Some((last_id, entries_read)) = stream.group_progress(group).await {
+                self.propagate(Operation::XGroupSetID(key.to_string(), group.to_string(), last_id, entries_read)).await;
+            }
+        }
+        Ok(claimed.entries)
+    }
+
+    /// Replicate what a consumer group read or claim changed, as the equivalent forced XCLAIMs.
+    async fn propagate_claimed(&mut self, key: &str, group: &str, consumer: &str, claimed: &stream::Claimed) {
+        if claimed.created {
+            self.engine.notify(Class::Stream, "xgroup-createconsumer", key);
+            self.propagate(Operation::XGroupCreateConsumer(key.to_string(), group.to_string(), consumer.to_string())).await;
+        }
+        for (id, time, count) in claimed.deliveries.iter() {
+            self.propagate(Operation::XClaim(key.to_string(), group.to_string(), consumer.to_string(), *id, *time, *count)).await;
+        }
+        if !claimed.deleted.is_empty() {
+            self.propagate(Operation::XAck(key.to_string(), group.to_string(), claimed.deleted.clone())).await;
+        }
+    }
+
+    /// Serve the clients blocked on `keys` in the order they blocked, for as long as the keys stay ready.
+    async fn serve_blocked(&mut self, keys: Vec<String>) {
+        if self.blocked.is_empty() {
+            return;
+        }
+        let index = self.engine.index();
+        let mut ready: VecDeque<String> = keys.into_iter().collect();
+        while let Some(key) = ready.pop_front() {
+            for id in self.blocked.waiters(index, &key) {
+                let request = match self.blocked.get(id) {
+                    Some(pending) if !pending.reply.is_closed() => pending.request.clone(),
+                    Some(_) => {
+                        // the client went away without unblocking, nothing is served to it
+                        self.blocked.unblock(id);
+                        continue;
+                    }
+                    None => continue,
+                };
+                let served = match self.serve(&key, &request).await {
+                    Ok(Some(served)) => Ok(served),
+                    Ok(None) => continue,
+                    Err(e) => Err(e),
+                };
+                // the element moved to the destination may unblock its clients in turn
+                if let (Blocking::Move(_, destination, _), Ok(_)) = (&request, &served) {
+                    ready.push_back(destination.clone());
+                }
+                if let Some(pending) = self.blocked.unblock(id) {
+                    let _ = pending.reply.send(served);
+                }
+            }
+        }
+    }
+
+    /// Delete a collection left without elements, as Redis never keeps empty ones.
+    async fn remove_if_empty(&mut self, key: &str, empty: bool) {
+        if empty && self.engine.del(key.to_string()).await {
+            self.engine.notify(Class::Generic, "del", key);
+        }
+    }
+
+    /// Every successful write goes through here, it bumps the watched versions, replicates the operation
+    /// and serves the clients blocked on the keys it wrote.
+    async fn replicate(&mut self, operation: Operation) {
+        let keys = operation.keys();
+        // the keys that moved to other databases, where clients may be blocked on them
+        let moved: Vec<(usize, Vec<String>)> = match &operation {
+            Operation::Move(key, index) => vec![(*index, vec![key.clone()])],
+            Operation::SwapDB(a, b) => vec![(*a, self.blocked.keys(*a)), (*b, self.blocked.keys(*b))],
+            _ => Vec::new(),
+        };
+        self.propagate(operation).await;
+        self.serve_blocked(keys).await;
+        for (index, keys) in moved.into_iter() {
+            let other = self.engine.select(index);
+            let selected = std::mem::replace(&mut self.engine, other);
+            self.serve_blocked(keys).await;
+            self.engine = selected;
+        }
+    }
+
+    async fn propagate(&mut self, operation: Operation) {
+        // publishes only reach the replicas, they change no data to save or log
+        let changed = !matches!(operation, Operation::Publish(..));
+        if changed {
+            self.engine.touch(&operation.keys()).await;
+        }
+        let replicated = self.role.is_master();
+        let logged = self.aof.is_some() && changed;
+        if !replicated && !logged {
+            return;
+        }
+        let propagated = Propagated {
+            index: self.engine.index(),
+            operation: operation.encode(),
+            replicated,
+            logged,
+        };
+        match &mut self.transaction {
+            Some(buffered) => buffered.push(propagated),
+            None => self.flush(vec![propagated], false).await,
+        }
+    }
+
+    /// Send operations to the replicas and append them to the append only file, as one MULTI/EXEC block
+    /// for a transaction. The file is rewritten once it grew too much.
+    async fn flush(&mut self, operations: Vec<Propagated>, transaction: bool) {
+        let shard = &mut *self.lock;
+        let replicated = operations.iter().filter(|propagated| propagated.replicated);
+        if let Some(data) = encode_stream(&mut shard.replicated_db, replicated, transaction) {
+            shard.role.replicate_data(Command::Simple(Simple::new(data.into()))).await;
+        }
+        let aof = match shard.aof.clone() {
+            Some(aof) => aof,
+            None => return,
+        };
+        let logged = operations.iter().filter(|propagated| propagated.logged);
+        if let Some(data) = encode_stream(&mut shard.aof_db, logged, transaction) {
+            aof.append(&data).await;
+        }
+        if aof.wants_rewrite().await {
+            let _ = self.rewrite_aof(false).await;
+        }
+    }
+
+    /// Snapshot the data and write it to a new append only file in the background, the writes appended
+    /// meanwhile are added after it. With `restart` a running rewrite is dropped for this one.
+    async fn rewrite_aof(&mut self, restart: bool) -> Result<(), Error> {
+        let aof = self.aof.clone().ok_or(Error::AofDisabled)?;
+        let generation = aof.begin_rewrite(restart).await.ok_or(Error::RewriteInProgress)?;
+        // the writes that follow the snapshot start by selecting their database
+        self.aof_db = None;
+        let snapshot = self.engine.snapshot().await;
+        tokio::spawn(async move {
+            let mut base = Vec::new();
+            match snapshot.write(&mut base).await {
+                Ok(()) => {
+                    let _ = aof.finish_rewrite(generation, base).await;
+                }
+                Err(_) => aof.abort_rewrite(generation).await,
+            }
+        });
+        Ok(())
+    }
+}
+
+enum Operation {
+    Set(String, string::String, Option<SystemTime>),
+    Del(Vec<String>),
+    Unlink(Vec<String>),
+    Rename(String, String),
+    Copy(String, String),
+    Move(String, usize),
+    SwapDB(usize, usize),
+    FlushDB(bool),
+    FlushAll(bool),
+    Publish(Bytes, Bytes),
+    PExpireAt(String, SystemTime),
+    Persist(String),
+    IncrBy(String, i64),
+    MSet(Vec<(String, Bytes)>),
+    Append(String, Bytes),
+    SetRange(String, u64, Bytes),
+    XAdd(String, Entry, Option<u64>),
+    XDel(String, Vec<(u64, u64)>),
+    XTrim(String, u64),
+    XSetID(String, (u64, u64), u64, (u64, u64)),
+    XGroupCreate(String, String, (u64, u64), bool, Option<u64>),
+    XGroupSetID(String, String, (u64, u64), Option<u64>),
+    XGroupDestroy(String, String),
+    XGroupCreateConsumer(String, String, String),
+    XGroupDelConsumer(String, String, String),
+    XClaim(String, String, String, (u64, u64), u64, u64),
+    XAck(String, String, Vec<(u64, u64)>),
+    Push(String, Vec<Bytes>, list::Direction),
+    Pop(String, u64, list::Direction),
+    LMove(String, String, list::Direction, list::Direction),
+    LSet(String, i64, Bytes),
+    LRem(String, i64, Bytes),
+    LTrim(String, i64, i64),
+    HSet(String, Vec<(Bytes, Bytes)>),
+    HDel(String, Vec<Bytes>),
+    HIncrBy(String, Bytes, i64),
+    SAdd(String, Vec<Bytes>),
+    SRem(String, Vec<Bytes>),
+    SetCombineStore(String, Vec<String>, set::Algebra),
+    ZAdd(String, Vec<(f64, Bytes)>),
+    ZRem(String, Vec<Bytes>),
+}
 
 impl Operation {
+    /// The keys modified by the operation.
+    fn keys(&self) -> Vec<String> {
+        match self {
+            Operation::Del(keys) | Operation::Unlink(keys) => keys.clone(),
+            Operation::Rename(src, dst) | Operation::Copy(src, dst) | Operation::LMove(src, dst, ..) => vec![src.clone(), dst.clone()],
+            // the engine marks the keys of the other databases involved
+            Operation::Move(key, _) => vec![key.clone()],
+            Operation::SwapDB(..) | Operation::FlushDB(_) | Operation::FlushAll(_) | Operation::Publish(..) => Vec::new(),
+            Operation::MSet(pairs) => pairs.iter().map(|(key, _)| key.clone()).collect(),
+            Operation::Set(key, ..)
+            | Operation::PExpireAt(key, _)
+            | Operation::Persist(key)
+            | Operation::IncrBy(key, _)
+            | Operation::Append(key, _)
+            | Operation::SetRange(key, ..)
+            | Operation::XAdd(key, ..)
+            | Operation::XDel(key, _)
+            | Operation::XTrim(key, _)
+            | Operation::XSetID(key, ..)
+            | Operation::XGroupCreate(key, ..)
+            | Operation::XGroupSetID(key, ..)
+            | Operation::XGroupDestroy(key, _)
+            | Operation::XGroupCreateConsumer(key, ..)
+            | Operation::XGroupDelConsumer(key, ..)
+            | Operation::XClaim(key, ..)
+            | Operation::XAck(key, ..)
+            | Operation::Push(key, ..)
+            | Operation::Pop(key, ..)
+            | Operation::LSet(key, ..)
+            | Operation::LRem(key, ..)
+            | Operation::LTrim(key, ..)
+            | Operation::HSet(key, _)
+            | Operation::HDel(key, _)
+            | Operation::HIncrBy(key, ..)
+            | Operation::SAdd(key, _)
+            | Operation::SRem(key, _)
+            | Operation::SetCombineStore(key, ..)
+            | Operation::ZAdd(key, _)
+            | Operation::ZRem(key, _) => vec![key.clone()],
+        }
+    }
+
     fn encode(self) -> Type {
         match self {
-            Operation::Set(key, value) => {
-                Type::Array(vec![
+            Operation::Set(key, value, expire) => {
+                let mut arr = vec![
                     Type::BulkString("SET".into()),
                     Type::BulkString(key.into()),
                     value.encode(),
-                ])
+                ];
+                if let Some(when) = expire {
+                    arr.push(Type::BulkString("PXAT".into()));
+                    arr.push(Type::BulkString(unix_millis(when).to_string().into()));
+                }
+                Type::Array(arr)
             }
             Operation::Del(key) => {
                 let mut arr = vec![Type::BulkString("DEL".into())];
@@ -322,28 +1971,454 @@ impl Operation {
                 }
                 Type::Array(arr)
             }
-            Operation::XAdd(key, entry) => {
+            Operation::Unlink(keys) => {
+                let mut arr = vec![Type::BulkString("UNLINK".into())];
+                for k in keys.into_iter() {
+                    arr.push(Type::BulkString(k.into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::Rename(src, dst) => {
+                Type::Array(vec![
+                    Type::BulkString("RENAME".into()),
+                    Type::BulkString(src.into()),
+                    Type::BulkString(dst.into()),
+                ])
+            }
+            Operation::Copy(src, dst) => {
+                // the copy was already allowed on the master, replicas always replace
+                Type::Array(vec![
+                    Type::BulkString("COPY".into()),
+                    Type::BulkString(src.into()),
+                    Type::BulkString(dst.into()),
+                    Type::BulkString("REPLACE".into()),
+                ])
+            }
+            Operation::Move(key, index) => {
+                Type::Array(vec![
+                    Type::BulkString("MOVE".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(index.to_string().into()),
+                ])
+            }
+            Operation::SwapDB(a, b) => {
+                Type::Array(vec![
+                    Type::BulkString("SWAPDB".into()),
+                    Type::BulkString(a.to_string().into()),
+                    Type::BulkString(b.to_string().into()),
+                ])
+            }
+            Operation::FlushDB(lazy) => {
+                let mut arr = vec![Type::BulkString("FLUSHDB".into())];
+                if lazy {
+                    arr.push(Type::BulkString("ASYNC".into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::FlushAll(lazy) => {
+                let mut arr = vec![Type::BulkString("FLUSHALL".into())];
+                if lazy {
+                    arr.push(Type::BulkString("ASYNC".into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::Publish(channel, message) => {
+                Type::Array(vec![
+                    Type::BulkString("PUBLISH".into()),
+                    Type::BulkString(channel),
+                    Type::BulkString(message),
+                ])
+            }
+            Operation::PExpireAt(key, when) => {
+                Type::Array(vec![
+                    Type::BulkString("PEXPIREAT".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(unix_millis(when).to_string().into()),
+                ])
+            }
+            Operation::Persist(key) => {
                 Type::Array(vec![
-                    Type::BulkString("XADD".into()),
+                    Type::BulkString("PERSIST".into()),
                     Type::BulkString(key.into()),
-                    entry.encode(),
                 ])
             }
+            Operation::IncrBy(key, increment) => {
+                Type::Array(vec![
+                    Type::BulkString("INCRBY".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(increment.to_string().into()),
+                ])
+            }
+            Operation::MSet(pairs) => {
+                let mut arr = vec![Type::BulkString("MSET".into())];
+                for (key, value) in pairs.into_iter() {
+                    arr.push(Type::BulkString(key.into()));
+                    arr.push(Type::BulkString(value));
+                }
+                Type::Array(arr)
+            }
+            Operation::Append(key, value) => {
+                Type::Array(vec![
+                    Type::BulkString("APPEND".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(value),
+                ])
+            }
+            Operation::SetRange(key, offset, value) => {
+                Type::Array(vec![
+                    Type::BulkString("SETRANGE".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(offset.to_string().into()),
+                    Type::BulkString(value),
+                ])
+            }
+            Operation::XAdd(key, entry, trimmed) => {
+                let mut arr = vec![Type::BulkString("XADD".into()), Type::BulkString(key.into())];
+                if let Some(len) = trimmed {
+                    arr.push(Type::BulkString("MAXLEN".into()));
+                    arr.push(Type::BulkString(len.to_string().into()));
+                }
+                arr.push(entry.encode());
+                Type::Array(arr)
+            }
+            Operation::XDel(key, ids) => {
+                let mut arr = vec![Type::BulkString("XDEL".into()), Type::BulkString(key.into())];
+                for id in ids.into_iter() {
+                    arr.push(Type::BulkString(format!("{}-{}", id.0, id.1).into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::XTrim(key, len) => {
+                Type::Array(vec![
+                    Type::BulkString("XTRIM".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString("MAXLEN".into()),
+                    Type::BulkString(len.to_string().into()),
+                ])
+            }
+            Operation::XSetID(key, id, added, max_deleted_id) => {
+                Type::Array(vec![
+                    Type::BulkString("XSETID".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(format!("{}-{}", id.0, id.1).into()),
+                    Type::BulkString("ENTRIESADDED".into()),
+                    Type::BulkString(added.to_string().into()),
+                    Type::BulkString("MAXDELETEDID".into()),
+                    Type::BulkString(format!("{}-{}", max_deleted_id.0, max_deleted_id.1).into()),
+                ])
+            }
+            Operation::XGroupCreate(key, group, id, mkstream, entries_read) => {
+                let mut arr = vec![
+                    Type::BulkString("XGROUP".into()),
+                    Type::BulkString("CREATE".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                    Type::BulkString(format!("{}-{}", id.0, id.1).into()),
+                ];
+                if mkstream {
+                    arr.push(Type::BulkString("MKSTREAM".into()));
+                }
+                if let Some(entries_read) = entries_read {
+                    arr.push(Type::BulkString("ENTRIESREAD".into()));
+                    arr.push(Type::BulkString(entries_read.to_string().into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::XGroupSetID(key, group, id, entries_read) => {
+                let mut arr = vec![
+                    Type::BulkString("XGROUP".into()),
+                    Type::BulkString("SETID".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                    Type::BulkString(format!("{}-{}", id.0, id.1).into()),
+                ];
+                if let Some(entries_read) = entries_read {
+                    arr.push(Type::BulkString("ENTRIESREAD".into()));
+                    arr.push(Type::BulkString(entries_read.to_string().into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::XGroupDestroy(key, group) => {
+                Type::Array(vec![
+                    Type::BulkString("XGROUP".into()),
+                    Type::BulkString("DESTROY".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                ])
+            }
+            Operation::XGroupCreateConsumer(key, group, consumer) => {
+                Type::Array(vec![
+                    Type::BulkString("XGROUP".into()),
+                    Type::BulkString("CREATECONSUMER".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                    Type::BulkString(consumer.into()),
+                ])
+            }
+            Operation::XGroupDelConsumer(key, group, consumer) => {
+                Type::Array(vec![
+                    Type::BulkString("XGROUP".into()),
+                    Type::BulkString("DELCONSUMER".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                    Type::BulkString(consumer.into()),
+                ])
+            }
+            Operation::XClaim(key, group, consumer, id, time, count) => {
+                // replicas take over the exact delivery time and count, whatever the idle time
+                Type::Array(vec![
+                    Type::BulkString("XCLAIM".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                    Type::BulkString(consumer.into()),
+                    Type::BulkString("0".into()),
+                    Type::BulkString(format!("{}-{}", id.0, id.1).into()),
+                    Type::BulkString("TIME".into()),
+                    Type::BulkString(time.to_string().into()),
+                    Type::BulkString("RETRYCOUNT".into()),
+                    Type::BulkString(count.to_string().into()),
+                    Type::BulkString("FORCE".into()),
+                    Type::BulkString("JUSTID".into()),
+                ])
+            }
+            Operation::XAck(key, group, ids) => {
+                let mut arr = vec![
+                    Type::BulkString("XACK".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(group.into()),
+                ];
+                for id in ids.into_iter() {
+                    arr.push(Type::BulkString(format!("{}-{}", id.0, id.1).into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::Push(key, values, direction) => {
+                let name = match direction {
+                    list::Direction::Left => "LPUSH",
+                    list::Direction::Right => "RPUSH",
+                };
+                let mut arr = vec![Type::BulkString(name.into()), Type::BulkString(key.into())];
+                for v in values.into_iter() {
+                    arr.push(Type::BulkString(v));
+                }
+                Type::Array(arr)
+            }
+            Operation::Pop(key, count, direction) => {
+                let name = match direction {
+                    list::Direction::Left => "LPOP",
+                    list::Direction::Right => "RPOP",
+                };
+                Type::Array(vec![
+                    Type::BulkString(name.into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(count.to_string().into()),
+                ])
+            }
+            Operation::LMove(src, dst, from, to) => {
+                let side = |direction| match direction {
+                    list::Direction::Left => "LEFT",
+                    list::Direction::Right => "RIGHT",
+                };
+                Type::Array(vec![
+                    Type::BulkString("LMOVE".into()),
+                    Type::BulkString(src.into()),
+                    Type::BulkString(dst.into()),
+                    Type::BulkString(side(from).into()),
+                    Type::BulkString(side(to).into()),
+                ])
+            }
+            Operation::LSet(key, index, value) => {
+                Type::Array(vec![
+                    Type::BulkString("LSET".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(index.to_string().into()),
+                    Type::BulkString(value),
+                ])
+            }
+            Operation::LRem(key, count, value) => {
+                Type::Array(vec![
+                    Type::BulkString("LREM".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(count.to_string().into()),
+                    Type::BulkString(value),
+                ])
+            }
+            Operation::LTrim(key, start, stop) => {
+                Type::Array(vec![
+                    Type::BulkString("LTRIM".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(start.to_string().into()),
+                    Type::BulkString(stop.to_string().into()),
+                ])
+            }
+            Operation::HSet(key, pairs) => {
+                let mut arr = vec![Type::BulkString("HSET".into()), Type::BulkString(key.into())];
+                for (field, value) in pairs.into_iter() {
+                    arr.push(Type::BulkString(field));
+                    arr.push(Type::BulkString(value));
+                }
+                Type::Array(arr)
+            }
+            Operation::HDel(key, fields) => {
+                let mut arr = vec![Type::BulkString("HDEL".into()), Type::BulkString(key.into())];
+                for field in fields.into_iter() {
+                    arr.push(Type::BulkString(field));
+                }
+                Type::Array(arr)
+            }
+            Operation::HIncrBy(key, field, increment) => {
+                Type::Array(vec![
+                    Type::BulkString("HINCRBY".into()),
+                    Type::BulkString(key.into()),
+                    Type::BulkString(field),
+                    Type::BulkString(increment.to_string().into()),
+                ])
+            }
+            Operation::SAdd(key, members) => {
+                let mut arr = vec![Type::BulkString("SADD".into()), Type::BulkString(key.into())];
+                for member in members.into_iter() {
+                    arr.push(Type::BulkString(member));
+                }
+                Type::Array(arr)
+            }
+            Operation::SRem(key, members) => {
+                let mut arr = vec![Type::BulkString("SREM".into()), Type::BulkString(key.into())];
+                for member in members.into_iter() {
+                    arr.push(Type::BulkString(member));
+                }
+                Type::Array(arr)
+            }
+            Operation::SetCombineStore(destination, keys, algebra) => {
+                let name = match algebra {
+                    set::Algebra::Inter => "SINTERSTORE",
+                    set::Algebra::Union => "SUNIONSTORE",
+                    set::Algebra::Diff => "SDIFFSTORE",
+                };
+                let mut arr = vec![Type::BulkString(name.into()), Type::BulkString(destination.into())];
+                for key in keys.into_iter() {
+                    arr.push(Type::BulkString(key.into()));
+                }
+                Type::Array(arr)
+            }
+            Operation::ZAdd(key, pairs) => {
+                let mut arr = vec![Type::BulkString("ZADD".into()), Type::BulkString(key.into())];
+                for (score, member) in pairs.into_iter() {
+                    arr.push(Type::BulkString(strings::format_float(score).into()));
+                    arr.push(Type::BulkString(member));
+                }
+                Type::Array(arr)
+            }
+            Operation::ZRem(key, members) => {
+                let mut arr = vec![Type::BulkString("ZREM".into()), Type::BulkString(key.into())];
+                for member in members.into_iter() {
+                    arr.push(Type::BulkString(member));
+                }
+                Type::Array(arr)
+            }
+        }
+    }
+}
+
+/// Save the databases in the background whenever a save point is reached.
+async fn save_points_task(mut db: DB) {
+    let mut interval = time::interval(Duration::from_secs(1));
+    loop {
+        interval.tick().await;
+        if db.engine.save_due() {
+            let _ = db.bgsave().await;
+        }
+    }
+}
+
+/// Encode operations as the commands of a stream applied on a single connection, selecting the database
+/// of an operation when it is not the one `selected` last. `None` when there is nothing to send.
+fn encode_stream<'a>(selected: &mut Option<usize>, operations: impl Iterator<Item=&'a Propagated>, transaction: bool) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    for propagated in operations {
+        if *selected != Some(propagated.index) {
+            *selected = Some(propagated.index);
+            data.extend(Encoder::encode(&Type::Array(vec![
+                Type::BulkString("SELECT".into()),
+                Type::BulkString(propagated.index.to_string().into()),
+            ])));
         }
+        data.extend(Encoder::encode(&propagated.operation));
+    }
+    if data.is_empty() {
+        return None;
+    }
+    if transaction {
+        let mut block = Encoder::encode(&Type::Array(vec![Type::BulkString("MULTI".into())]));
+        block.extend(data);
+        block.extend(Encoder::encode(&Type::Array(vec![Type::BulkString("EXEC".into())])));
+        data = block;
     }
+    Some(data)
+}
+
+fn push_event(direction: list::Direction) -> &'static str {
+    match direction {
+        list::Direction::Left => "lpush",
+        list::Direction::Right => "rpush",
+    }
+}
+
+fn pop_event(direction: list::Direction) -> &'static str {
+    match direction {
+        list::Direction::Left => "lpop",
+        list::Direction::Right => "rpop",
+    }
+}
+
+/// Name the key and group in the errors of the consumer group commands.
+fn group_error(key: &str, group: &str, e: stream::Error) -> Error {
+    match e {
+        stream::Error::NoGroup => Error::NoGroup(key.to_string(), group.to_string()),
+        e => Error::StreamError(e),
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_millis()
 }
 
 #[derive(Debug)]
 pub enum Error {
     InvalidType,
+    NoSuchKey,
+    SameObject,
+    OutOfRange,
+    NoGroup(String, String),
+    NoStream,
+    SaveInProgress,
+    SaveFailed(String),
+    AofDisabled,
+    RewriteInProgress,
+    StringError(string::Error),
     StreamError(stream::Error),
+    ListError(list::Error),
+    HashError(hash::Error),
+    SortedSetError(sorted_set::Error),
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Error::InvalidType => write!(f, "WRONGTYPE Operation against a key holding the wrong kind of value"),
+            Error::NoSuchKey => write!(f, "ERR no such key"),
+            Error::SameObject => write!(f, "ERR source and destination objects are the same"),
+            Error::OutOfRange => write!(f, "ERR DB index is out of range"),
+            Error::NoGroup(key, group) => write!(f, "NOGROUP No such key '{}' or consumer group '{}'", key, group),
+            Error::NoStream => write!(f, "ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically."),
+            Error::SaveInProgress => write!(f, "ERR Background save already in progress"),
+            Error::SaveFailed(e) => write!(f, "ERR {}", e),
+            Error::AofDisabled => write!(f, "ERR The append only file is disabled, start the server with --appendonly yes"),
+            Error::RewriteInProgress => write!(f, "ERR Background append only file rewriting already in progress"),
+            Error::StringError(e) => write!(f, "{}", e),
             Error::StreamError(e) => write!(f, "{}", e),
+            Error::ListError(e) => write!(f, "{}", e),
+            Error::HashError(e) => write!(f, "{}", e),
+            Error::SortedSetError(e) => write!(f, "{}", e),
         }
     }
 }
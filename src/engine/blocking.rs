@@ -0,0 +1,85 @@
+//! The registry of clients blocked until one of their keys is ready, like BLPOP or XREAD BLOCK.
+//! Writes look up the clients blocked on the keys they touched and serve them in the order
+//! they blocked.
+
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug)]
+pub(crate) struct Blocked<T> {
+    next_id: u64,
+    // the blocked clients of each key of each database, oldest first
+    queues: HashMap<(usize, String), VecDeque<u64>>,
+    waiters: HashMap<u64, Waiter<T>>,
+}
+
+#[derive(Debug)]
+struct Waiter<T> {
+    index: usize,
+    keys: Vec<String>,
+    request: T,
+}
+
+impl<T> Default for Blocked<T> {
+    fn default() -> Self {
+        Blocked {
+            next_id: 0,
+            queues: HashMap::new(),
+            waiters: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Blocked<T> {
+    /// Block `request` on `keys` of the database `index`, returns its id.
+    pub(crate) fn block(&mut self, index: usize, keys: &[String], request: T) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut unique: Vec<String> = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            if !unique.contains(key) {
+                unique.push(key.clone());
+            }
+        }
+        for key in unique.iter() {
+            self.queues.entry((index, key.clone())).or_default().push_back(id);
+        }
+        self.waiters.insert(id, Waiter { index, keys: unique, request });
+        id
+    }
+
+    /// Remove a blocked request from every queue, `None` if it was already served.
+    pub(crate) fn unblock(&mut self, id: u64) -> Option<T> {
+        let waiter = self.waiters.remove(&id)?;
+        for key in waiter.keys.into_iter() {
+            let queue_key = (waiter.index, key);
+            if let Some(queue) = self.queues.get_mut(&queue_key) {
+                queue.retain(|other| *other != id);
+                if queue.is_empty() {
+                    self.queues.remove(&queue_key);
+                }
+            }
+        }
+        Some(waiter.request)
+    }
+
+    /// The ids of the requests blocked on `key`, oldest first.
+    pub(crate) fn waiters(&self, index: usize, key: &str) -> Vec<u64> {
+        match self.queues.get(&(index, key.to_string())) {
+            Some(queue) => queue.iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The keys of the database `index` with blocked requests.
+    pub(crate) fn keys(&self, index: usize) -> Vec<String> {
+        self.queues.keys().filter(|(other, _)| *other == index).map(|(_, key)| key.clone()).collect()
+    }
+
+    pub(crate) fn get(&self, id: u64) -> Option<&T> {
+        self.waiters.get(&id).map(|waiter| &waiter.request)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+}
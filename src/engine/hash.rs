@@ -0,0 +1,168 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use bytes::Bytes;
+use tokio::sync::RwLock;
+use crate::utils::strings;
+use super::scan;
+
+#[derive(Debug, Clone)]
+pub struct Hash {
+    entries: Arc<RwLock<Entries>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Entries {
+    pairs: HashMap<Bytes, Bytes>,
+    // every field ordered by its scan position, so HSCAN cursors stay valid across modifications
+    index: BTreeSet<(u64, Bytes)>,
+}
+
+impl Default for Hash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash {
+    pub fn new() -> Self {
+        Hash {
+            entries: Arc::new(RwLock::new(Entries::default())),
+        }
+    }
+
+    /// An independent copy of the hash.
+    pub async fn duplicate(&self) -> Self {
+        Hash {
+            entries: Arc::new(RwLock::new(self.entries.read().await.clone())),
+        }
+    }
+
+    /// Set every field to its value, returns the number of fields that were newly added.
+    pub async fn set(&self, pairs: Vec<(Bytes, Bytes)>) -> u64 {
+        let mut entries = self.entries.write().await;
+        let mut added = 0;
+        for (field, value) in pairs.into_iter() {
+            if entries.insert(field, value).is_none() {
+                added += 1;
+            }
+        }
+        added
+    }
+
+    pub async fn get(&self, field: &Bytes) -> Option<Bytes> {
+        self.entries.read().await.pairs.get(field).cloned()
+    }
+
+    pub async fn get_multiple(&self, fields: &[Bytes]) -> Vec<Option<Bytes>> {
+        let entries = self.entries.read().await;
+        fields.iter().map(|field| entries.pairs.get(field).cloned()).collect()
+    }
+
+    /// Remove the given fields, returns the number of fields that existed.
+    pub async fn remove(&self, fields: &[Bytes]) -> u64 {
+        let mut entries = self.entries.write().await;
+        let mut removed = 0;
+        for field in fields.iter() {
+            if entries.remove(field).is_some() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    pub async fn contains(&self, field: &Bytes) -> bool {
+        self.entries.read().await.pairs.contains_key(field)
+    }
+
+    pub async fn len(&self) -> u64 {
+        self.entries.read().await.pairs.len() as u64
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.entries.read().await.pairs.is_empty()
+    }
+
+    pub async fn fields(&self) -> Vec<Bytes> {
+        self.entries.read().await.pairs.keys().cloned().collect()
+    }
+
+    pub async fn values(&self) -> Vec<Bytes> {
+        self.entries.read().await.pairs.values().cloned().collect()
+    }
+
+    pub async fn pairs(&self) -> Vec<(Bytes, Bytes)> {
+        self.entries.read().await.pairs.iter().map(|(field, value)| (field.clone(), value.clone())).collect()
+    }
+
+    /// A page of field-value pairs in scan order, see `scan::page`.
+    pub async fn scan(&self, cursor: u64, count: u64) -> (u64, Vec<(Bytes, Bytes)>) {
+        let entries = self.entries.read().await;
+        let (next, fields) = scan::page_of(&entries.index, cursor, count);
+        let page = fields.into_iter()
+            .filter_map(|field| entries.pairs.get(field).map(|value| (field.clone(), value.clone())))
+            .collect();
+        (next, page)
+    }
+
+    /// Add `increment` to the integer stored at `field`, a missing field counts as 0.
+    pub async fn incr_by(&self, field: Bytes, increment: i64) -> Result<i64, Error> {
+        let mut entries = self.entries.write().await;
+        let current = match entries.pairs.get(&field) {
+            Some(value) => std::str::from_utf8(value)
+                .ok()
+                .and_then(strings::parse_int)
+                .ok_or(Error::NotInteger)?,
+            None => 0,
+        };
+        let value = current.checked_add(increment).ok_or(Error::Overflow)?;
+        entries.insert(field, value.to_string().into());
+        Ok(value)
+    }
+}
+
+impl From<Vec<(Bytes, Bytes)>> for Hash {
+    fn from(pairs: Vec<(Bytes, Bytes)>) -> Self {
+        let mut entries = Entries::default();
+        for (field, value) in pairs.into_iter() {
+            entries.insert(field, value);
+        }
+        Hash {
+            entries: Arc::new(RwLock::new(entries)),
+        }
+    }
+}
+
+impl Entries {
+    /// Insert a field, keeping the scan index in sync.
+    fn insert(&mut self, field: Bytes, value: Bytes) -> Option<Bytes> {
+        let prev = self.pairs.insert(field.clone(), value);
+        if prev.is_none() {
+            self.index.insert((scan::position(&field), field));
+        }
+        prev
+    }
+
+    /// Remove a field, keeping the scan index in sync.
+    fn remove(&mut self, field: &Bytes) -> Option<Bytes> {
+        let value = self.pairs.remove(field)?;
+        self.index.remove(&(scan::position(field), field.clone()));
+        Some(value)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NotInteger,
+    Overflow,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotInteger => write!(f, "ERR hash value is not an integer"),
+            Error::Overflow => write!(f, "ERR increment or decrement would overflow"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
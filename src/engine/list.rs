@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use bytes::Bytes;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone)]
+pub struct List {
+    entries: Arc<RwLock<VecDeque<Bytes>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl Default for List {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl List {
+    pub fn new() -> Self {
+        List {
+            entries: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    /// An independent copy of the list.
+    pub async fn duplicate(&self) -> Self {
+        List {
+            entries: Arc::new(RwLock::new(self.entries.read().await.clone())),
+        }
+    }
+
+    /// Insert every value at the given end of the list in order, returns the new length.
+    pub async fn push(&self, values: Vec<Bytes>, direction: Direction) -> u64 {
+        let mut entries = self.entries.write().await;
+        for value in values.into_iter() {
+            match direction {
+                Direction::Left => entries.push_front(value),
+                Direction::Right => entries.push_back(value),
+            }
+        }
+        entries.len() as u64
+    }
+
+    /// Remove up to `count` values from the given end of the list.
+    pub async fn pop(&self, count: u64, direction: Direction) -> Vec<Bytes> {
+        let mut entries = self.entries.write().await;
+        let len = entries.len();
+        let count = std::cmp::min(count as usize, len);
+        match direction {
+            Direction::Left => entries.drain(..count).collect(),
+            Direction::Right => entries.drain(len - count..).rev().collect(),
+        }
+    }
+
+    pub async fn len(&self) -> u64 {
+        self.entries.read().await.len() as u64
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.entries.read().await.is_empty()
+    }
+
+    pub async fn range(&self, start: i64, stop: i64) -> Vec<Bytes> {
+        let entries = self.entries.read().await;
+        match normalize_range(start, stop, entries.len()) {
+            Some((start, stop)) => entries.range(start..=stop).cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub async fn index(&self, index: i64) -> Option<Bytes> {
+        let entries = self.entries.read().await;
+        normalize_index(index, entries.len()).and_then(|index| entries.get(index).cloned())
+    }
+
+    pub async fn set(&self, index: i64, value: Bytes) -> Result<(), Error> {
+        let mut entries = self.entries.write().await;
+        match normalize_index(index, entries.len()) {
+            Some(index) => {
+                entries[index] = value;
+                Ok(())
+            }
+            None => Err(Error::IndexOutOfRange),
+        }
+    }
+
+    /// Remove the first `count` occurrences of `value` from head to tail if `count` is positive,
+    /// from tail to head if `count` is negative, or all of them if `count` is zero.
+    pub async fn remove(&self, count: i64, value: &Bytes) -> u64 {
+        let mut entries = self.entries.write().await;
+        let limit = if count == 0 { usize::MAX } else { count.unsigned_abs() as usize };
+        let mut removed = 0;
+        if count >= 0 {
+            let mut i = 0;
+            while i < entries.len() && removed < limit {
+                if entries[i] == value {
+                    entries.remove(i);
+                    removed += 1;
+                } else {
+                    i += 1;
+                }
+            }
+        } else {
+            let mut i = entries.len();
+            while i > 0 && removed < limit {
+                i -= 1;
+                if entries[i] == value {
+                    entries.remove(i);
+                    removed += 1;
+                }
+            }
+        }
+        removed as u64
+    }
+
+    /// Keep only the elements between `start` and `stop` (both inclusive).
+    pub async fn trim(&self, start: i64, stop: i64) {
+        let mut entries = self.entries.write().await;
+        match normalize_range(start, stop, entries.len()) {
+            Some((start, stop)) => {
+                entries.truncate(stop + 1);
+                entries.drain(..start);
+            }
+            None => entries.clear(),
+        }
+    }
+
+    pub async fn values(&self) -> Vec<Bytes> {
+        self.entries.read().await.iter().cloned().collect()
+    }
+}
+
+impl From<Vec<Bytes>> for List {
+    fn from(values: Vec<Bytes>) -> Self {
+        List {
+            entries: Arc::new(RwLock::new(values.into())),
+        }
+    }
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+    if index < 0 || index >= len as i64 {
+        None
+    } else {
+        Some(index as usize)
+    }
+}
+
+fn normalize_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 { (start + len).max(0) } else { start };
+    let stop = if stop < 0 { stop + len } else { stop.min(len - 1) };
+    if start > stop || start >= len {
+        None
+    } else {
+        Some((start as usize, stop as usize))
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    IndexOutOfRange,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::IndexOutOfRange => write!(f, "ERR index out of range"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,22 +1,43 @@
 pub mod string;
 pub mod stream;
+pub mod list;
+pub mod hash;
+pub mod set;
+pub mod sorted_set;
+pub mod scan;
+pub(crate) mod blocking;
+mod save_points;
+
+pub use save_points::SavePoints;
 
 use std::collections::{BTreeSet, HashMap};
-use std::path::PathBuf;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, SystemTime};
-use tokio::sync::{Notify, RwLock};
+use rand::seq::IteratorRandom;
+use tokio::sync::{Mutex, Notify, RwLock, RwLockWriteGuard};
 use tokio::{fs, time};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::time::Instant;
+use crate::pubsub::{Class, PubSub};
 use crate::rdb::serializer::Serializer;
 use crate::rdb::{self, types::Order};
 use crate::rdb::parser::Parser;
+use crate::utils;
+
+// seconds before a save point tries again after a failed save
+const SAVE_RETRY_DELAY: u64 = 5;
 
 #[derive(Debug, Clone)]
 pub enum DataType {
     String(string::String),
     Stream(stream::Stream),
+    List(list::List),
+    Hash(hash::Hash),
+    Set(set::Set),
+    SortedSet(sorted_set::SortedSet),
 }
 
 impl DataType {
@@ -24,13 +45,52 @@ impl DataType {
         match self {
             DataType::String(_) => "string",
             DataType::Stream(_) => "stream",
+            DataType::List(_) => "list",
+            DataType::Hash(_) => "hash",
+            DataType::Set(_) => "set",
+            DataType::SortedSet(_) => "zset",
+        }
+    }
+
+    /// A copy of the value that does not share its contents with the original.
+    pub(crate) async fn duplicate(&self) -> DataType {
+        match self {
+            DataType::String(s) => DataType::String(s.clone()),
+            DataType::Stream(stream) => DataType::Stream(stream.duplicate().await),
+            DataType::List(list) => DataType::List(list.duplicate().await),
+            DataType::Hash(hash) => DataType::Hash(hash.duplicate().await),
+            DataType::Set(set) => DataType::Set(set.duplicate().await),
+            DataType::SortedSet(zset) => DataType::SortedSet(zset.duplicate().await),
         }
     }
 }
 
+/// The NX/XX/GT/LT conditions of the EXPIRE family, a key without a TTL counts as an infinite TTL.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExpireOptions {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+}
+
+impl ExpireOptions {
+    pub(crate) fn allows(&self, current: Option<SystemTime>, when: SystemTime) -> bool {
+        match current {
+            None => !self.xx && !self.gt,
+            Some(_) if self.nx => false,
+            Some(current) if self.gt => when > current,
+            Some(current) if self.lt => when < current,
+            Some(_) => true,
+        }
+    }
+}
+
+/// A handle on one of the logical databases of the storage, see `select`.
 #[derive(Debug, Clone)]
 pub struct Engine {
     shard: Arc<Shard>,
+    index: usize,
 }
 
 #[derive(Debug)]
@@ -38,15 +98,40 @@ struct Shard {
     dir: String,
     file_name: String,
     path: PathBuf,
-    kv: RwLock<KV>,
+    // the append only file, which holds the data instead of the RDB file when it exists
+    aof_path: Option<PathBuf>,
+    databases: Vec<RwLock<KV>>,
+    shutdown: AtomicBool,
     background_task: Notify,
+    // where the keyspace events of every database are published
+    pubsub: PubSub,
+    save_points: SavePoints,
+    // the writes since the last save, and when it was in UNIX seconds
+    dirty: AtomicU64,
+    last_save: AtomicU64,
+    // when the last save was tried, in UNIX seconds, and whether it failed
+    last_attempt: AtomicU64,
+    save_failed: AtomicBool,
+    // set while a background save runs
+    saving: AtomicBool,
+    // held while the RDB file is written or read, so that saves do not interleave
+    file: Mutex<()>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct KV {
     entries: HashMap<String, Entry>,
     expirations: BTreeSet<(Instant, String)>,
-    shutdown: bool,
+    // every key ordered by its scan position, so SCAN cursors stay valid across modifications
+    index: BTreeSet<(u64, String)>,
+    // modification versions of the keys watched by at least one transaction
+    watched: HashMap<String, Watch>,
+}
+
+#[derive(Debug)]
+struct Watch {
+    version: u64,
+    watchers: usize,
 }
 
 #[derive(Debug)]
@@ -55,36 +140,82 @@ struct Entry {
     expiration: Option<Instant>,
 }
 
+/// A point-in-time copy of the databases, serialized once their locks are released.
+#[derive(Debug, Default)]
+pub(crate) struct Snapshot {
+    databases: Vec<Vec<Order>>,
+}
+
 impl Engine {
-    pub(crate) async fn new(dir: String, file_name: String) -> Engine {
+    pub(crate) async fn new(dir: String, file_name: String, databases: usize, pubsub: PubSub, aof_file_name: Option<String>, save_points: SavePoints) -> crate::Result<Engine> {
         let shard = Arc::new(Shard {
             dir: dir.clone(),
             file_name: file_name.clone(),
-            path: PathBuf::from(dir).join(file_name),
-            kv: RwLock::new({
-                KV {
-                    entries: HashMap::new(),
-                    expirations: BTreeSet::new(),
-                    shutdown: false,
-                }
-            }),
+            path: PathBuf::from(&dir).join(file_name),
+            aof_path: aof_file_name.map(|name| PathBuf::from(&dir).join(name)),
+            databases: (0..databases.max(1)).map(|_| RwLock::new(KV::default())).collect(),
+            shutdown: AtomicBool::new(false),
             background_task: Notify::new(),
+            pubsub,
+            save_points,
+            dirty: AtomicU64::new(0),
+            last_save: AtomicU64::new(unix_secs()),
+            last_attempt: AtomicU64::new(0),
+            save_failed: AtomicBool::new(false),
+            saving: AtomicBool::new(false),
+            file: Mutex::new(()),
         });
-        let engine = Engine { shard: shard.clone() };
-        if shard.path.exists() {
-            engine.load_rdb().await.unwrap();
+        let engine = Engine { shard: shard.clone(), index: 0 };
+        // an existing append only file is loaded by replaying it, see `load_aof`
+        if shard.path.exists() && !engine.has_aof() {
+            engine.load_rdb_at_startup().await?;
         }
         tokio::spawn(purge_expired_tasks(shard));
-        engine
+        Ok(engine)
+    }
+
+    /// A handle on the database `index` of the same storage, which must be below `databases`.
+    pub(crate) fn select(&self, index: usize) -> Engine {
+        Engine { shard: self.shard.clone(), index }
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn databases(&self) -> usize {
+        self.shard.databases.len()
+    }
+
+    pub(crate) fn aof_path(&self) -> Option<PathBuf> {
+        self.shard.aof_path.clone()
+    }
+
+    /// Whether the append only file is enabled and exists.
+    pub(crate) fn has_aof(&self) -> bool {
+        self.shard.aof_path.as_ref().map(|path| path.exists()).unwrap_or(false)
+    }
+
+    pub(crate) fn pubsub(&self) -> PubSub {
+        self.shard.pubsub.clone()
+    }
+
+    /// Publish a keyspace event for `key` of this database.
+    pub(crate) fn notify(&self, class: Class, event: &str, key: &str) {
+        self.shard.pubsub.notify(class, event, key, self.index);
+    }
+
+    fn kv(&self) -> &RwLock<KV> {
+        &self.shard.databases[self.index]
     }
 
     pub(crate) async fn get(&self, key: String) -> Option<DataType> {
-        let kv = self.shard.kv.read().await;
+        let kv = self.kv().read().await;
         kv.entries.get(&key).map(|entry| entry.data.clone())
     }
 
     pub(crate) async fn set(&mut self, key: String, value: DataType, expire: Option<Duration>) {
-        let mut kv = self.shard.kv.write().await;
+        let mut kv = self.kv().write().await;
         let mut notify = false;
         let expiration = expire.map(|duration| {
             let when = Instant::now() + duration;
@@ -94,105 +225,395 @@ impl Engine {
                 .unwrap_or(true);
             when
         });
-        let prev = kv.entries.insert(
-            key.clone(),
-            Entry {
-                data: value,
-                expiration,
-            },
-        );
-        if let Some(prev) = prev {
-            if let Some(when) = prev.expiration {
-                // clear expiration
-                kv.expirations.remove(&(when, key.clone()));
+        kv.insert(key, Entry {
+            data: value,
+            expiration,
+        });
+        drop(kv);
+        if notify {
+            self.shard.background_task.notify_one();
+        }
+    }
+
+    /// Replace the value of `key` keeping its expiration, a new key is created without one.
+    pub(crate) async fn replace(&mut self, key: String, value: DataType) {
+        let mut kv = self.kv().write().await;
+        match kv.entries.get_mut(&key) {
+            Some(entry) => entry.data = value,
+            None => {
+                kv.insert(key, Entry {
+                    data: value,
+                    expiration: None,
+                });
+            }
+        }
+    }
+
+    pub(crate) async fn del(&mut self, key: String) -> bool {
+        let mut kv = self.kv().write().await;
+        kv.remove(&key).is_some()
+    }
+
+    /// Remove `keys` and drop their values on a background task, returns the keys removed.
+    pub(crate) async fn unlink(&mut self, keys: &[String]) -> Vec<String> {
+        let mut kv = self.kv().write().await;
+        let mut removed = Vec::new();
+        let mut entries = Vec::new();
+        for key in keys.iter() {
+            if let Some(entry) = kv.remove(key) {
+                removed.push(key.clone());
+                entries.push(entry);
             }
         }
+        free(entries, true);
+        removed
+    }
+
+    /// Move `key` and its expiration to the database `index`. Returns false if `key` does not exist
+    /// or already exists there.
+    pub(crate) async fn move_key(&mut self, key: &str, index: usize) -> bool {
+        if index == self.index {
+            return false;
+        }
+        let (mut src, mut dst) = self.shard.lock_pair(self.index, index).await;
+        if dst.entries.contains_key(key) {
+            return false;
+        }
+        match src.remove(key) {
+            Some(entry) => {
+                dst.insert(key.to_string(), entry);
+                dst.touch(key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Exchange the contents of the databases `a` and `b`.
+    pub(crate) async fn swap_databases(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        let (mut a, mut b) = self.shard.lock_pair(a, b).await;
+        a.swap(&mut b);
+    }
+
+    /// Remove every key of the database, with `lazy` the values are freed in the background.
+    pub(crate) async fn flush(&mut self, lazy: bool) {
+        let mut kv = self.kv().write().await;
+        free(kv.clear(), lazy);
+    }
+
+    /// Remove every key of every database, with `lazy` the values are freed in the background.
+    pub(crate) async fn flush_all(&mut self, lazy: bool) {
+        for kv in self.shard.databases.iter() {
+            free(kv.write().await.clear(), lazy);
+        }
+    }
+
+    /// Move `src` and its expiration to `dst`, overwriting `dst`. Returns false if `src` does not exist.
+    pub(crate) async fn rename(&mut self, src: &str, dst: String) -> bool {
+        let mut kv = self.kv().write().await;
+        match kv.remove(src) {
+            Some(entry) => {
+                kv.insert(dst, entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Copy `src` and its expiration to `dst`. Returns false if `src` does not exist,
+    /// or if `dst` exists and `replace` is not set.
+    pub(crate) async fn copy(&mut self, src: &str, dst: String, replace: bool) -> bool {
+        let mut kv = self.kv().write().await;
+        if !replace && kv.entries.contains_key(&dst) {
+            return false;
+        }
+        let (data, expiration) = match kv.entries.get(src) {
+            Some(entry) => (entry.data.duplicate().await, entry.expiration),
+            None => return false,
+        };
+        kv.insert(dst, Entry { data, expiration });
+        true
+    }
+
+    pub(crate) async fn exists(&self, key: &str) -> bool {
+        let kv = self.kv().read().await;
+        kv.entries.contains_key(key)
+    }
+
+    pub(crate) async fn size(&self) -> u64 {
+        let kv = self.kv().read().await;
+        kv.entries.len() as u64
+    }
+
+    pub(crate) async fn random_key(&self) -> Option<String> {
+        let kv = self.kv().read().await;
+        kv.entries.keys().choose(&mut rand::thread_rng()).cloned()
+    }
+
+    /// The absolute expiration time of `key`, `None` if the key does not exist.
+    pub(crate) async fn expiration(&self, key: &str) -> Option<Option<SystemTime>> {
+        let kv = self.kv().read().await;
+        kv.entries.get(key).map(|entry| instant_to_system_time(entry.expiration))
+    }
+
+    /// Set or clear the expiration of an existing key, returns false if the key does not exist.
+    pub(crate) async fn expire(&mut self, key: String, when: Option<SystemTime>) -> bool {
+        let mut kv = self.kv().write().await;
+        let expiration = when.map(|when| {
+            let now = Instant::now();
+            match when.duration_since(SystemTime::now()) {
+                Ok(duration) => now + duration,
+                Err(_) => now,
+            }
+        });
+        let prev = match kv.entries.get_mut(&key) {
+            Some(entry) => std::mem::replace(&mut entry.expiration, expiration),
+            None => return false,
+        };
+        if let Some(prev) = prev {
+            kv.expirations.remove(&(prev, key.clone()));
+        }
+        let mut notify = false;
         if let Some(when) = expiration {
+            notify = kv
+                .next_expiration()
+                .map(|expiration| expiration > when)
+                .unwrap_or(true);
             kv.expirations.insert((when, key));
         }
         drop(kv);
         if notify {
             self.shard.background_task.notify_one();
         }
+        true
     }
 
-    pub(crate) async fn del(&mut self, key: String) -> bool {
-        let mut kv = self.shard.kv.write().await;
-        if let Some(entry) = kv.entries.remove(&key) {
-            if let Some(when) = entry.expiration {
-                kv.expirations.remove(&(when, key));
+    /// Register a watcher of `key`, returns the current version of the key.
+    pub(crate) async fn watch(&mut self, key: String) -> u64 {
+        let mut kv = self.kv().write().await;
+        let watch = kv.watched.entry(key).or_insert(Watch { version: 0, watchers: 0 });
+        watch.watchers += 1;
+        watch.version
+    }
+
+    pub(crate) async fn unwatch(&mut self, key: &str) {
+        let mut kv = self.kv().write().await;
+        if let Some(watch) = kv.watched.get_mut(key) {
+            watch.watchers -= 1;
+            if watch.watchers == 0 {
+                kv.watched.remove(key);
             }
-            true
-        } else {
-            false
         }
     }
 
+    pub(crate) async fn version(&self, key: &str) -> u64 {
+        let kv = self.kv().read().await;
+        kv.watched.get(key).map(|watch| watch.version).unwrap_or_default()
+    }
+
+    /// Mark the keys as modified for the transactions watching them, and count the write for the save points.
+    pub(crate) async fn touch(&mut self, keys: &[String]) {
+        self.shard.dirty.fetch_add(keys.len().max(1) as u64, Ordering::Relaxed);
+        let mut kv = self.kv().write().await;
+        if kv.watched.is_empty() {
+            return;
+        }
+        for key in keys.iter() {
+            kv.touch(key);
+        }
+    }
+
+    /// The next page of keys from `cursor` with their values, see `scan::page` for the guarantees.
+    pub(crate) async fn scan(&self, cursor: u64, count: u64) -> (u64, Vec<(String, DataType)>) {
+        let kv = self.kv().read().await;
+        let elements = kv.index
+            .range((cursor, String::new())..)
+            .map(|(position, key)| (*position, key));
+        let (next, keys) = scan::page(elements, count);
+        let page = keys.into_iter()
+            .filter_map(|key| kv.entries.get(key).map(|entry| (key.clone(), entry.data.clone())))
+            .collect();
+        (next, page)
+    }
+
     pub(crate) async fn keys(&self) -> Vec<String> {
-        let kv = self.shard.kv.read().await;
+        let kv = self.kv().read().await;
         kv.entries.keys().cloned().collect()
     }
 
+    /// Write a point-in-time copy of the databases to the RDB file, they are only locked while copied.
     pub(crate) async fn write_rdb(&self) -> crate::Result<()> {
-        let kv = self.shard.kv.write().await;
-        if self.shard.path.exists() {
-            let bak = self.shard.path.with_extension("bak");
-            fs::rename(&self.shard.path, &bak).await?;
+        let (snapshot, dirty) = self.save_snapshot().await;
+        self.write_snapshot(snapshot, dirty).await
+    }
+
+    /// Start writing a point-in-time copy of the databases to the RDB file in the background, returns false
+    /// if a background save is already running.
+    pub(crate) async fn bgsave(&self) -> bool {
+        if self.shard.saving.swap(true, Ordering::SeqCst) {
+            return false;
         }
-        let file = fs::File::create(&self.shard.path).await?;
-        let mut serializer = Serializer::new(file);
-        serializer.init().await?;
-        for (key, entry) in kv.entries.iter() {
-            let rtype = match &entry.data {
-                DataType::String(str) => rdb::types::Type::String(key.clone().into(), str.clone().into()),
-                DataType::Stream(_) => { continue; }
-            };
-            serializer.write_order(&Order {
-                dataset: 0,
-                rtype,
-                expire: instant_to_system_time(entry.expiration),
-            }).await?;
+        let (snapshot, dirty) = self.save_snapshot().await;
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let _ = engine.write_snapshot(snapshot, dirty).await;
+            engine.shard.saving.store(false, Ordering::SeqCst);
+        });
+        true
+    }
+
+    pub(crate) fn saving(&self) -> bool {
+        self.shard.saving.load(Ordering::SeqCst)
+    }
+
+    /// When the last successful save finished, in UNIX seconds.
+    pub(crate) fn last_save(&self) -> u64 {
+        self.shard.last_save.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn save_points(&self) -> SavePoints {
+        self.shard.save_points.clone()
+    }
+
+    /// Whether a save point is reached. A failed save is not retried for a few seconds, like Redis does.
+    pub(crate) fn save_due(&self) -> bool {
+        let now = unix_secs();
+        if self.saving() || (self.shard.save_failed.load(Ordering::Relaxed) && now < self.shard.last_attempt.load(Ordering::Relaxed) + SAVE_RETRY_DELAY) {
+            return false;
         }
-        serializer.finish().await?;
-        Ok(())
+        let dirty = self.shard.dirty.load(Ordering::Relaxed);
+        let elapsed = now.saturating_sub(self.last_save());
+        self.shard.save_points.0.iter().any(|(seconds, changes)| dirty >= *changes && elapsed >= *seconds)
+    }
+
+    /// Forget the writes counted so far, the data was just loaded from a file.
+    pub(crate) fn reset_dirty(&self) {
+        self.shard.dirty.store(0, Ordering::Relaxed);
+    }
+
+    /// A snapshot to save, with the count of writes it holds.
+    async fn save_snapshot(&self) -> (Snapshot, u64) {
+        let databases = self.shard.lock_all().await;
+        let dirty = self.shard.dirty.load(Ordering::Relaxed);
+        (Snapshot::of(&databases).await, dirty)
+    }
+
+    async fn write_snapshot(&self, snapshot: Snapshot, dirty: u64) -> crate::Result<()> {
+        let _file = self.shard.file.lock().await;
+        self.shard.last_attempt.store(unix_secs(), Ordering::Relaxed);
+        let result = self.write_file(snapshot).await;
+        self.shard.save_failed.store(result.is_err(), Ordering::Relaxed);
+        if result.is_ok() {
+            self.shard.dirty.fetch_sub(dirty, Ordering::Relaxed);
+            self.shard.last_save.store(unix_secs(), Ordering::Relaxed);
+        }
+        result
+    }
+
+    async fn write_file(&self, snapshot: Snapshot) -> crate::Result<()> {
+        let temp = utils::fs::temp_path(&self.shard.path);
+        let mut file = fs::File::create(&temp).await?;
+        let result = match snapshot.write(&mut file).await {
+            Ok(()) => utils::fs::commit(file, &temp, &self.shard.path).await,
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            let _ = fs::remove_file(&temp).await;
+        }
+        result
+    }
+
+    /// A point-in-time copy of every database, taken under the locks of all of them.
+    pub(crate) async fn snapshot(&self) -> Snapshot {
+        let databases = self.shard.lock_all().await;
+        Snapshot::of(&databases).await
     }
 
     pub(crate) async fn write_rdb_data(&self, data: &[u8]) -> crate::Result<()> {
-        let _kv = self.shard.kv.write().await;
-        if self.shard.path.exists() {
-            let bak = self.shard.path.with_extension("bak");
-            fs::rename(&self.shard.path, &bak).await?;
+        let _file = self.shard.file.lock().await;
+        let temp = utils::fs::temp_path(&self.shard.path);
+        let mut file = fs::File::create(&temp).await?;
+        let result = match file.write_all(data).await {
+            Ok(()) => utils::fs::commit(file, &temp, &self.shard.path).await,
+            Err(e) => Err(e.into()),
+        };
+        if result.is_err() {
+            let _ = fs::remove_file(&temp).await;
         }
-        let mut file = fs::File::create(&self.shard.path).await?;
-        file.write_all(data).await?;
-        Ok(())
+        result
     }
 
     pub(crate) async fn load_rdb(&self) -> crate::Result<()> {
-        let mut kv = self.shard.kv.write().await;
-        let file = fs::File::open(&self.shard.path).await?;
+        self.load_rdb_file(&self.shard.path).await
+    }
+
+    /// Load the RDB file at startup, an empty file holds no data.
+    async fn load_rdb_at_startup(&self) -> crate::Result<()> {
+        let path = &self.shard.path;
+        if fs::metadata(path).await?.len() == 0 {
+            return Ok(());
+        }
+        self.load_rdb().await.map_err(|e| format!("failed to load the RDB file {}: {}", path.display(), e).into())
+    }
+
+    async fn load_rdb_file(&self, path: &Path) -> crate::Result<()> {
+        let file = fs::File::open(path).await?;
         let mut parser = Parser::new(file);
         parser.parse().await?;
-        for order in parser.orders().map(|v| v.clone()) {
+        self.load_orders(parser.orders().cloned()).await
+    }
+
+    /// Load the RDB preamble of the append only file when it starts with one, returns the commands that
+    /// follow it with their offset in the file.
+    pub(crate) async fn load_aof(&self) -> crate::Result<(Vec<u8>, u64)> {
+        let path = match &self.shard.aof_path {
+            Some(path) => path,
+            None => return Ok((Vec::new(), 0)),
+        };
+        let data = fs::read(path).await?;
+        if !data.starts_with(rdb::consts::constant::RDB_MAGIC.as_bytes()) {
+            return Ok((data, 0));
+        }
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().await?;
+        let orders: Vec<Order> = parser.orders().cloned().collect();
+        let input = parser.into_inner();
+        let offset = input.position();
+        let mut data = input.into_inner();
+        self.load_orders(orders.into_iter()).await?;
+        Ok((data.split_off(offset as usize), offset))
+    }
+
+    async fn load_orders(&self, orders: impl Iterator<Item=Order>) -> crate::Result<()> {
+        let mut databases = self.shard.lock_all().await;
+        // nothing is loaded from a file that does not fit the databases
+        let orders: Vec<Order> = orders.collect();
+        if let Some(order) = orders.iter().find(|order| order.dataset as usize >= databases.len()) {
+            return Err(format!("the RDB file holds database {} but only {} databases are configured", order.dataset, self.databases()).into());
+        }
+        for order in orders {
+            let kv = &mut databases[order.dataset as usize];
             let expiration = match system_time_to_instant(order.expire) {
                 Ok(expiration) => expiration,
                 Err(_) => continue,
             };
-            let key = match order.rtype {
-                rdb::types::Type::String(key, val) => {
-                    kv.entries.insert(key.clone(), Entry {
-                        data: DataType::String(string::String::new(val)),
-                        expiration,
-                    });
-                    key
-                }
-                _ => continue,
+            let (key, data) = match order.rtype {
+                rdb::types::Type::String(key, val) => (key, DataType::String(string::String::new(val))),
+                rdb::types::Type::List(key, values) => (key, DataType::List(list::List::from(values))),
+                rdb::types::Type::Hash(key, pairs) => (key, DataType::Hash(hash::Hash::from(pairs))),
+                rdb::types::Type::Set(key, members) => (key, DataType::Set(set::Set::from(members))),
+                rdb::types::Type::SortedSet(key, pairs) => (key, DataType::SortedSet(sorted_set::SortedSet::from(pairs))),
+                rdb::types::Type::Stream(key, val) => (key, DataType::Stream(stream::Stream::from(val))),
             };
-            if let Some(when) = expiration {
-                kv.expirations.insert((when, key));
-            }
+            kv.insert(key, Entry {
+                data,
+                expiration,
+            });
         }
-        drop(kv);
+        drop(databases);
         self.shard.background_task.notify_one();
         Ok(())
     }
@@ -206,7 +627,7 @@ impl Engine {
     }
 
     pub(crate) async fn get_rdb(&self) -> crate::Result<Vec<u8>> {
-        let _kv = self.shard.kv.write().await;
+        let _file = self.shard.file.lock().await;
         match fs::read(&self.shard.path).await {
             Ok(data) => Ok(data),
             Err(e) => Err(e.into()),
@@ -214,29 +635,169 @@ impl Engine {
     }
 }
 
+impl Snapshot {
+    async fn of(databases: &[RwLockWriteGuard<'_, KV>]) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        for (dataset, kv) in databases.iter().enumerate() {
+            snapshot.databases.push(kv.orders(dataset as u32).await);
+        }
+        snapshot
+    }
+
+    /// Serialize the copy in the RDB format.
+    pub(crate) async fn write<W: AsyncWrite + Unpin>(&self, output: W) -> crate::Result<()> {
+        let mut serializer = Serializer::new(output);
+        serializer.init().await?;
+        for (dataset, orders) in self.databases.iter().enumerate() {
+            if orders.is_empty() {
+                continue;
+            }
+            let expires = orders.iter().filter(|order| order.expire.is_some()).count();
+            serializer.write_resize_db(dataset as u32, orders.len() as u32, expires as u32).await?;
+            for order in orders.iter() {
+                serializer.write_order(order).await?;
+            }
+        }
+        serializer.finish().await?;
+        Ok(())
+    }
+}
+
 impl Shard {
+    /// Remove the expired keys of every database, returns the next time a key expires.
     async fn purge_expired_keys(&self) -> Option<Instant> {
-        let kv = &mut *self.kv.write().await;
-        if kv.shutdown {
+        if self.is_shutdown() {
             return None;
         }
         let now = Instant::now();
-        while let Some(&(when, ref key)) = kv.expirations.iter().next() {
-            if when > now {
-                return Some(when);
+        let mut next: Option<Instant> = None;
+        for (index, kv) in self.databases.iter().enumerate() {
+            let kv = &mut *kv.write().await;
+            while let Some(&(when, ref key)) = kv.expirations.iter().next() {
+                if when > now {
+                    next = Some(next.map_or(when, |next| next.min(when)));
+                    break;
+                }
+                let key = key.clone();
+                kv.remove(&key);
+                kv.touch(&key);
+                self.dirty.fetch_add(1, Ordering::Relaxed);
+                self.pubsub.notify(Class::Expired, "expired", &key, index);
             }
-            kv.entries.remove(key);
-            kv.expirations.remove(&(when, key.clone()));
         }
-        None
+        next
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    async fn lock_all(&self) -> Vec<RwLockWriteGuard<'_, KV>> {
+        let mut databases = Vec::with_capacity(self.databases.len());
+        for kv in self.databases.iter() {
+            databases.push(kv.write().await);
+        }
+        databases
     }
 
-    async fn is_shutdown(&self) -> bool {
-        self.kv.read().await.shutdown
+    /// Lock two different databases, always in index order so concurrent callers can not deadlock.
+    async fn lock_pair(&self, a: usize, b: usize) -> (RwLockWriteGuard<'_, KV>, RwLockWriteGuard<'_, KV>) {
+        if a < b {
+            let first = self.databases[a].write().await;
+            (first, self.databases[b].write().await)
+        } else {
+            let first = self.databases[b].write().await;
+            (self.databases[a].write().await, first)
+        }
     }
 }
 
 impl KV {
+    /// The entries as the orders of the RDB file, for the database `dataset`.
+    async fn orders(&self, dataset: u32) -> Vec<Order> {
+        let mut orders = Vec::with_capacity(self.entries.len());
+        for (key, entry) in self.entries.iter() {
+            let rtype = match &entry.data {
+                DataType::String(str) => rdb::types::Type::String(key.clone(), str.clone().into()),
+                DataType::List(list) => rdb::types::Type::List(key.clone(), list.values().await),
+                DataType::Hash(hash) => rdb::types::Type::Hash(key.clone(), hash.pairs().await),
+                DataType::Set(set) => rdb::types::Type::Set(key.clone(), set.members().await),
+                DataType::SortedSet(zset) => rdb::types::Type::SortedSet(key.clone(), zset.pairs().await),
+                DataType::Stream(stream) => rdb::types::Type::Stream(key.clone(), stream.snapshot().await),
+            };
+            orders.push(Order {
+                dataset,
+                rtype,
+                expire: instant_to_system_time(entry.expiration),
+            });
+        }
+        orders
+    }
+
+    /// Insert an entry, keeping the expiration and scan indexes in sync.
+    fn insert(&mut self, key: String, entry: Entry) -> Option<Entry> {
+        let expiration = entry.expiration;
+        let prev = self.entries.insert(key.clone(), entry);
+        match &prev {
+            Some(prev) => {
+                if let Some(when) = prev.expiration {
+                    self.expirations.remove(&(when, key.clone()));
+                }
+            }
+            None => {
+                self.index.insert((scan::position(key.as_bytes()), key.clone()));
+            }
+        }
+        if let Some(when) = expiration {
+            self.expirations.insert((when, key));
+        }
+        prev
+    }
+
+    /// Remove an entry, keeping the expiration and scan indexes in sync.
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        if let Some(when) = entry.expiration {
+            self.expirations.remove(&(when, key.to_string()));
+        }
+        self.index.remove(&(scan::position(key.as_bytes()), key.to_string()));
+        Some(entry)
+    }
+
+    /// Remove every entry, the watched keys that existed are marked as modified.
+    fn clear(&mut self) -> HashMap<String, Entry> {
+        let existing: Vec<String> = self.watched.keys().filter(|key| self.entries.contains_key(*key)).cloned().collect();
+        for key in existing.iter() {
+            self.touch(key);
+        }
+        self.expirations.clear();
+        self.index.clear();
+        std::mem::take(&mut self.entries)
+    }
+
+    /// Exchange the data with `other`, the watches stay with their database and the watched keys
+    /// present on either side are marked as modified.
+    fn swap(&mut self, other: &mut KV) {
+        std::mem::swap(&mut self.entries, &mut other.entries);
+        std::mem::swap(&mut self.expirations, &mut other.expirations);
+        std::mem::swap(&mut self.index, &mut other.index);
+        let changed = |kv: &KV, other: &KV| -> Vec<String> {
+            kv.watched.keys().filter(|key| kv.entries.contains_key(*key) || other.entries.contains_key(*key)).cloned().collect()
+        };
+        for key in changed(self, other) {
+            self.touch(&key);
+        }
+        for key in changed(other, self) {
+            other.touch(&key);
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(watch) = self.watched.get_mut(key) {
+            watch.version += 1;
+        }
+    }
+
     fn next_expiration(&self) -> Option<Instant> {
         self.expirations
             .iter()
@@ -246,7 +807,7 @@ impl KV {
 }
 
 async fn purge_expired_tasks(shard: Arc<Shard>) {
-    while !shard.is_shutdown().await {
+    while !shard.is_shutdown() {
         if let Some(when) = shard.purge_expired_keys().await {
             tokio::select! {
                 _ = time::sleep_until(when) => {}
@@ -258,8 +819,21 @@ async fn purge_expired_tasks(shard: Arc<Shard>) {
     }
 }
 
+/// Drop a removed value, on a blocking task with `lazy` so large values do not stall the caller.
+fn free<T: Send + 'static>(value: T, lazy: bool) {
+    if lazy {
+        tokio::task::spawn_blocking(move || drop(value));
+    } else {
+        drop(value);
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
 fn instant_to_system_time(instant: Option<Instant>) -> Option<SystemTime> {
-    instant.map(|instant| SystemTime::UNIX_EPOCH + instant.duration_since(Instant::now()))
+    instant.map(|instant| SystemTime::now() + instant.saturating_duration_since(Instant::now()))
 }
 
 fn system_time_to_instant(system_time: Option<SystemTime>) -> crate::Result<Option<Instant>> {
@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+/// The `save <seconds> <changes>` rules, the databases are saved once `changes` writes were made and the
+/// last save is at least `seconds` old.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavePoints(pub Vec<(u64, u64)>);
+
+impl FromStr for SavePoints {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s.split_whitespace()
+            .map(|value| value.parse::<u64>().map_err(|_| format!("invalid save point '{}'", value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() % 2 != 0 {
+            return Err("save points are pairs of seconds and changes".to_string());
+        }
+        Ok(SavePoints(values.chunks(2).map(|pair| (pair[0], pair[1])).collect()))
+    }
+}
+
+impl std::fmt::Display for SavePoints {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let values: Vec<String> = self.0.iter().map(|(seconds, changes)| format!("{} {}", seconds, changes)).collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_save_points() {
+        let points: SavePoints = "3600 1 300 100".parse().unwrap();
+        assert_eq!(points.0, vec![(3600, 1), (300, 100)]);
+        assert_eq!(points.to_string(), "3600 1 300 100");
+        assert!("".parse::<SavePoints>().unwrap().0.is_empty());
+        assert_eq!("  60\t10000 ".parse::<SavePoints>().unwrap().0, vec![(60, 10000)]);
+        assert!("3600".parse::<SavePoints>().is_err());
+        assert!("3600 x".parse::<SavePoints>().is_err());
+        assert!("-1 1".parse::<SavePoints>().is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::BTreeSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use bytes::Bytes;
+
+/// The position of an element in scan order, stable for the lifetime of the process.
+pub fn position(element: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    element.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Take a page of about `count` elements from `elements`, which must be ordered by position.
+/// Returns the cursor of the following page (the position of the first element left out),
+/// 0 once the iteration is complete.
+///
+/// Pages never split elements sharing a position, so an element present for the whole
+/// iteration is returned exactly once whatever is added or removed in between.
+pub fn page<T>(elements: impl Iterator<Item = (u64, T)>, count: u64) -> (u64, Vec<T>) {
+    let mut page = Vec::new();
+    let mut last = None;
+    for (position, element) in elements {
+        if page.len() as u64 >= count.max(1) && last != Some(position) {
+            return (position, page);
+        }
+        page.push(element);
+        last = Some(position);
+    }
+    (0, page)
+}
+
+/// A page of a collection from `cursor`, `index` holding its elements ordered by position.
+pub fn page_of(index: &BTreeSet<(u64, Bytes)>, cursor: u64, count: u64) -> (u64, Vec<&Bytes>) {
+    page(index.range((cursor, Bytes::new())..).map(|(position, element)| (*position, element)), count)
+}
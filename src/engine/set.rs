@@ -0,0 +1,151 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use bytes::Bytes;
+use tokio::sync::RwLock;
+use super::scan;
+
+#[derive(Debug, Clone)]
+pub struct Set {
+    members: Arc<RwLock<Members>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Members {
+    set: HashSet<Bytes>,
+    // every member ordered by its scan position, so SSCAN cursors stay valid across modifications
+    index: BTreeSet<(u64, Bytes)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algebra {
+    Inter,
+    Union,
+    Diff,
+}
+
+impl Default for Set {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Set {
+    pub fn new() -> Self {
+        Set {
+            members: Arc::new(RwLock::new(Members::default())),
+        }
+    }
+
+    /// An independent copy of the set.
+    pub async fn duplicate(&self) -> Self {
+        Set {
+            members: Arc::new(RwLock::new(self.members.read().await.clone())),
+        }
+    }
+
+    /// Add the given members, returns the ones that were not already present.
+    pub async fn add(&self, members: Vec<Bytes>) -> Vec<Bytes> {
+        let mut set = self.members.write().await;
+        let mut added = Vec::new();
+        for member in members.into_iter() {
+            if set.insert(member.clone()) {
+                added.push(member);
+            }
+        }
+        added
+    }
+
+    /// Remove the given members, returns the number of members that were present.
+    pub async fn remove(&self, members: &[Bytes]) -> u64 {
+        let mut set = self.members.write().await;
+        let mut removed = 0;
+        for member in members.iter() {
+            if set.remove(member) {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    pub async fn contains(&self, member: &Bytes) -> bool {
+        self.members.read().await.set.contains(member)
+    }
+
+    pub async fn len(&self) -> u64 {
+        self.members.read().await.set.len() as u64
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.members.read().await.set.is_empty()
+    }
+
+    /// A page of members in scan order, see `scan::page`.
+    pub async fn scan(&self, cursor: u64, count: u64) -> (u64, Vec<Bytes>) {
+        let members = self.members.read().await;
+        let (next, page) = scan::page_of(&members.index, cursor, count);
+        (next, page.into_iter().cloned().collect())
+    }
+
+    pub async fn members(&self) -> Vec<Bytes> {
+        self.members.read().await.set.iter().cloned().collect()
+    }
+}
+
+impl From<Vec<Bytes>> for Set {
+    fn from(members: Vec<Bytes>) -> Self {
+        let mut set = Members::default();
+        for member in members.into_iter() {
+            set.insert(member);
+        }
+        Set {
+            members: Arc::new(RwLock::new(set)),
+        }
+    }
+}
+
+impl Members {
+    /// Insert a member, keeping the scan index in sync.
+    fn insert(&mut self, member: Bytes) -> bool {
+        if !self.set.insert(member.clone()) {
+            return false;
+        }
+        self.index.insert((scan::position(&member), member));
+        true
+    }
+
+    /// Remove a member, keeping the scan index in sync.
+    fn remove(&mut self, member: &Bytes) -> bool {
+        if !self.set.remove(member) {
+            return false;
+        }
+        self.index.remove(&(scan::position(member), member.clone()));
+        true
+    }
+}
+
+/// Combine the sets stored at several keys, a missing key (`None`) behaves as an empty set.
+pub async fn combine(sets: &[Option<Set>], algebra: Algebra) -> Vec<Bytes> {
+    let mut iter = sets.iter();
+    let mut result: HashSet<Bytes> = match iter.next() {
+        Some(Some(first)) => first.members.read().await.set.clone(),
+        _ => HashSet::new(),
+    };
+    for set in iter {
+        match (algebra, set) {
+            (Algebra::Inter, Some(set)) => {
+                let set = set.members.read().await;
+                result.retain(|member| set.set.contains(member));
+            }
+            (Algebra::Inter, None) => result.clear(),
+            (Algebra::Union, Some(set)) => {
+                result.extend(set.members.read().await.set.iter().cloned());
+            }
+            (Algebra::Diff, Some(set)) => {
+                let set = set.members.read().await;
+                result.retain(|member| !set.set.contains(member));
+            }
+            (_, None) => {}
+        }
+    }
+    result.into_iter().collect()
+}
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+use std::sync::Arc;
+use bytes::Bytes;
+use tokio::sync::RwLock;
+use super::scan;
+
+#[derive(Debug, Clone)]
+pub struct SortedSet {
+    entries: Arc<RwLock<Entries>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Entries {
+    scores: HashMap<Bytes, f64>,
+    // members in score order, which seeks to a score or member bound but does not count the members
+    // before one, so anything by rank walks the index
+    index: BTreeSet<(Score, Bytes)>,
+    // every member ordered by its scan position, so ZSCAN cursors stay valid across modifications
+    positions: BTreeSet<(u64, Bytes)>,
+}
+
+/// A score ordered by `f64::total_cmp`, NaN is never stored.
+#[derive(Debug, Clone, Copy)]
+struct Score(f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // treat -0.0 and 0.0 as the same score
+        (self.0 + 0.0).total_cmp(&(other.0 + 0.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AddOptions {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+    pub incr: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Added(f64),
+    Updated(f64),
+    Unchanged(f64),
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Range {
+    /// Ranks from 0, negative ones counting from the end. Reaching the start of the range walks
+    /// the members before it, O(N) rather than O(log N) as in Redis.
+    Rank(i64, i64),
+    Score(Bound<f64>, Bound<f64>),
+    Lex(Bound<Bytes>, Bound<Bytes>),
+}
+
+impl Outcome {
+    /// The score of the member once added or updated, `None` when the options skipped it.
+    pub fn score(&self) -> Option<f64> {
+        match self {
+            Outcome::Added(score) | Outcome::Updated(score) | Outcome::Unchanged(score) => Some(*score),
+            Outcome::Skipped => None,
+        }
+    }
+}
+
+impl Default for SortedSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        SortedSet {
+            entries: Arc::new(RwLock::new(Entries::default())),
+        }
+    }
+
+    /// An independent copy of the sorted set.
+    pub async fn duplicate(&self) -> Self {
+        SortedSet {
+            entries: Arc::new(RwLock::new(self.entries.read().await.clone())),
+        }
+    }
+
+    /// Add or update `member` following the ZADD options, with `incr` the score is added to the current one.
+    pub async fn add(&self, member: Bytes, score: f64, options: AddOptions) -> Result<Outcome, Error> {
+        let mut entries = self.entries.write().await;
+        match entries.scores.get(&member).copied() {
+            Some(current) => {
+                if options.nx {
+                    return Ok(Outcome::Skipped);
+                }
+                let score = if options.incr { current + score } else { score };
+                if score.is_nan() {
+                    return Err(Error::NaN);
+                }
+                if (options.gt && score <= current) || (options.lt && score >= current) {
+                    return Ok(Outcome::Skipped);
+                }
+                if Score(score) == Score(current) {
+                    return Ok(Outcome::Unchanged(current));
+                }
+                entries.index.remove(&(Score(current), member.clone()));
+                entries.index.insert((Score(score), member.clone()));
+                entries.scores.insert(member, score);
+                Ok(Outcome::Updated(score))
+            }
+            None => {
+                if options.xx {
+                    return Ok(Outcome::Skipped);
+                }
+                entries.index.insert((Score(score), member.clone()));
+                entries.positions.insert((scan::position(&member), member.clone()));
+                entries.scores.insert(member, score);
+                Ok(Outcome::Added(score))
+            }
+        }
+    }
+
+    /// Remove the given members, returns the members that were present.
+    pub async fn remove(&self, members: &[Bytes]) -> Vec<Bytes> {
+        let mut entries = self.entries.write().await;
+        let mut removed = Vec::new();
+        for member in members.iter() {
+            if let Some(score) = entries.scores.remove(member) {
+                entries.index.remove(&(Score(score), member.clone()));
+                entries.positions.remove(&(scan::position(member), member.clone()));
+                removed.push(member.clone());
+            }
+        }
+        removed
+    }
+
+    pub async fn score(&self, member: &Bytes) -> Option<f64> {
+        self.entries.read().await.scores.get(member).copied()
+    }
+
+    /// The 0-based position of `member` in ascending (or descending with `rev`) score order. Counting
+    /// walks the members on the nearer side of it, O(N) in the worst case rather than O(log N) as in Redis.
+    pub async fn rank(&self, member: &Bytes, rev: bool) -> Option<u64> {
+        let entries = self.entries.read().await;
+        let key = (Score(*entries.scores.get(member)?), member.clone());
+        // walk both sides of the member at once and stop at the end of the shorter one
+        let mut lower = entries.index.range(..key.clone());
+        let mut upper = entries.index.range((Bound::Excluded(key), Bound::Unbounded));
+        let last = entries.index.len() as u64 - 1;
+        let mut steps = 0;
+        let rank = loop {
+            if lower.next().is_none() {
+                break steps;
+            }
+            if upper.next().is_none() {
+                break last - steps;
+            }
+            steps += 1;
+        };
+        if rev {
+            Some(last - rank)
+        } else {
+            Some(rank)
+        }
+    }
+
+    pub async fn len(&self) -> u64 {
+        self.entries.read().await.scores.len() as u64
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.entries.read().await.scores.is_empty()
+    }
+
+    /// Members with their scores, `limit` is an (offset, count) pair where a negative count means all.
+    pub async fn range(&self, range: &Range, rev: bool, limit: Option<(i64, i64)>) -> Vec<(Bytes, f64)> {
+        let entries = self.entries.read().await;
+        let (offset, count) = match limit {
+            Some((offset, _)) if offset < 0 => return Vec::new(),
+            Some((offset, count)) => (offset as usize, if count < 0 { usize::MAX } else { count as usize }),
+            None => (0, usize::MAX),
+        };
+        match range {
+            Range::Rank(start, stop) => {
+                let len = entries.index.len() as i64;
+                let start = if *start < 0 { (start + len).max(0) } else { *start };
+                let stop = if *stop < 0 { stop + len } else { (*stop).min(len - 1) };
+                if start > stop || start >= len {
+                    return Vec::new();
+                }
+                let (skip, take) = (start as usize, (stop - start + 1) as usize);
+                if rev {
+                    entries.index.iter().rev().skip(skip).take(take).skip(offset).take(count).map(|(score, member)| (member.clone(), score.0)).collect()
+                } else {
+                    entries.index.iter().skip(skip).take(take).skip(offset).take(count).map(|(score, member)| (member.clone(), score.0)).collect()
+                }
+            }
+            Range::Score(min, max) => {
+                if rev {
+                    let end = match max {
+                        Bound::Included(max) if *max < f64::INFINITY => Bound::Excluded((Score(max.next_up()), Bytes::new())),
+                        Bound::Excluded(max) => Bound::Excluded((Score(*max), Bytes::new())),
+                        _ => Bound::Unbounded,
+                    };
+                    entries.index.range((Bound::Unbounded, end)).rev()
+                        .skip_while(|(score, _)| !below(max, &score.0))
+                        .take_while(|(score, _)| above(min, &score.0))
+                        .skip(offset)
+                        .take(count)
+                        .map(|(score, member)| (member.clone(), score.0))
+                        .collect()
+                } else {
+                    let start = match min {
+                        Bound::Included(min) | Bound::Excluded(min) => Bound::Included((Score(*min), Bytes::new())),
+                        Bound::Unbounded => Bound::Unbounded,
+                    };
+                    entries.index.range((start, Bound::Unbounded))
+                        .skip_while(|(score, _)| !above(min, &score.0))
+                        .take_while(|(score, _)| below(max, &score.0))
+                        .skip(offset)
+                        .take(count)
+                        .map(|(score, member)| (member.clone(), score.0))
+                        .collect()
+                }
+            }
+            Range::Lex(min, max) => {
+                // lex ranges expect every member to have the same score, the walk starts from the
+                // bound within the score of the first (or last with `rev`) member
+                if rev {
+                    let end = match (entries.index.last(), max) {
+                        (Some((score, _)), Bound::Included(max)) => Bound::Included((*score, max.clone())),
+                        (Some((score, _)), Bound::Excluded(max)) => Bound::Excluded((*score, max.clone())),
+                        _ => Bound::Unbounded,
+                    };
+                    entries.index.range((Bound::Unbounded, end)).rev()
+                        .take_while(|(_, member)| above(min, member))
+                        .skip(offset)
+                        .take(count)
+                        .map(|(score, member)| (member.clone(), score.0))
+                        .collect()
+                } else {
+                    let start = match (entries.index.first(), min) {
+                        (Some((score, _)), Bound::Included(min)) => Bound::Included((*score, min.clone())),
+                        (Some((score, _)), Bound::Excluded(min)) => Bound::Excluded((*score, min.clone())),
+                        _ => Bound::Unbounded,
+                    };
+                    entries.index.range((start, Bound::Unbounded))
+                        .take_while(|(_, member)| below(max, member))
+                        .skip(offset)
+                        .take(count)
+                        .map(|(score, member)| (member.clone(), score.0))
+                        .collect()
+                }
+            }
+        }
+    }
+
+    /// A page of members with their scores in scan order, see `scan::page`.
+    pub async fn scan(&self, cursor: u64, count: u64) -> (u64, Vec<(Bytes, f64)>) {
+        let entries = self.entries.read().await;
+        let (next, members) = scan::page_of(&entries.positions, cursor, count);
+        let page = members.into_iter()
+            .filter_map(|member| entries.scores.get(member).map(|score| (member.clone(), *score)))
+            .collect();
+        (next, page)
+    }
+
+    pub async fn pairs(&self) -> Vec<(Bytes, f64)> {
+        self.entries.read().await.index.iter().map(|(score, member)| (member.clone(), score.0)).collect()
+    }
+}
+
+impl From<Vec<(Bytes, f64)>> for SortedSet {
+    fn from(pairs: Vec<(Bytes, f64)>) -> Self {
+        let mut entries = Entries::default();
+        for (member, score) in pairs.into_iter() {
+            match entries.scores.insert(member.clone(), score) {
+                Some(prev) => {
+                    entries.index.remove(&(Score(prev), member.clone()));
+                }
+                None => {
+                    entries.positions.insert((scan::position(&member), member.clone()));
+                }
+            }
+            entries.index.insert((Score(score), member));
+        }
+        SortedSet {
+            entries: Arc::new(RwLock::new(entries)),
+        }
+    }
+}
+
+fn above<T: PartialOrd>(min: &Bound<T>, value: &T) -> bool {
+    match min {
+        Bound::Included(min) => value >= min,
+        Bound::Excluded(min) => value > min,
+        Bound::Unbounded => true,
+    }
+}
+
+fn below<T: PartialOrd>(max: &Bound<T>, value: &T) -> bool {
+    match max {
+        Bound::Included(max) => value <= max,
+        Bound::Excluded(max) => value < max,
+        Bound::Unbounded => true,
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NaN,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NaN => write!(f, "ERR resulting score is not a number (NaN)"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
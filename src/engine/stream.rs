@@ -1,37 +1,250 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use std::time::SystemTime;
 use bytes::Bytes;
-use tokio::sync::{mpsc, RwLock};
-use crate::{resp, utils};
+use tokio::sync::RwLock;
+use crate::rdb;
+use crate::resp;
 
 #[derive(Debug, Clone)]
 pub struct Stream {
     shard: Arc<Shard>,
 }
 
+pub type ID = (u64, Option<u64>);
+
+type Fields = Vec<(Bytes, Bytes)>;
+
 #[derive(Debug)]
 struct Shard {
-    entries: RwLock<BTreeMap<(u64, u64), Vec<(Bytes, Bytes)>>>,
-    listener: RwLock<HashMap<String, mpsc::Sender<Entry>>>,
+    entries: RwLock<Entries>,
+    groups: RwLock<BTreeMap<String, Group>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Entries {
+    items: BTreeMap<(u64, u64), Fields>,
+    // the greatest ID ever added, new entries must be greater even once it is deleted
+    last_id: (u64, u64),
+    // the number of entries ever added
+    added: u64,
+    max_deleted_id: (u64, u64),
+}
+
+/// How to trim a stream, from its oldest entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trim {
+    pub threshold: Threshold,
+    /// Only remove whole blocks of entries, keeping a few more than asked, as `~` does.
+    pub approximate: bool,
+    /// The maximum number of entries removed by an approximate trim, 0 for no limit.
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Threshold {
+    /// Keep at most that many entries.
+    MaxLen(u64),
+    /// Remove the entries with a smaller ID.
+    MinID((u64, u64)),
+}
+
+// the number of entries of the blocks an approximate trim removes, as Redis stores them by blocks
+const BLOCK_ENTRIES: u64 = 100;
+
+/// A consumer group, which hands every entry after its last delivered ID to one of its consumers
+/// and tracks it until acknowledged.
+#[derive(Debug, Clone)]
+struct Group {
+    last_id: (u64, u64),
+    // the number of entries of the stream read by the group up to its last ID, `None` when unknown
+    entries_read: Option<u64>,
+    // the delivered entries not acknowledged yet
+    pending: BTreeMap<(u64, u64), Delivery>,
+    consumers: BTreeMap<String, Consumer>,
+}
+
+#[derive(Debug, Clone)]
+struct Delivery {
+    consumer: String,
+    // unix milliseconds of the last delivery
+    time: u64,
+    count: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Consumer {
+    // unix milliseconds of the last interaction and of the last successful read or claim
+    seen: u64,
+    active: Option<u64>,
+    pending: BTreeSet<(u64, u64)>,
+}
+
+/// What a consumer group read or claim handed over to a consumer.
+#[derive(Debug, Default)]
+pub struct Claimed {
+    pub entries: Vec<Entry>,
+    /// The ID, delivery time and delivery count of every entry added to the consumer's pending entries.
+    pub deliveries: Vec<((u64, u64), u64, u64)>,
+    /// The pending entries dropped because they were deleted from the stream.
+    pub deleted: Vec<(u64, u64)>,
+    /// Whether the consumer was created on the fly.
+    pub created: bool,
+}
+
+/// How XCLAIM updates the entries it claims.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaimOptions {
+    /// The idle time to set, instead of resetting it.
+    pub idle: Option<u64>,
+    /// The unix milliseconds of the delivery to set, instead of now.
+    pub time: Option<u64>,
+    /// The delivery count to set, instead of incrementing it.
+    pub retry_count: Option<u64>,
+    /// Claim entries of the stream that are not pending yet.
+    pub force: bool,
+    /// Reply only the IDs and leave the delivery count alone.
+    pub justid: bool,
+    /// Move the last delivered ID of the group forward to it.
+    pub last_id: Option<(u64, u64)>,
+}
+
+/// A pending entry as listed by XPENDING.
+#[derive(Debug, Clone)]
+pub struct Pending {
+    pub id: (u64, u64),
+    pub consumer: String,
+    pub idle: u64,
+    pub count: u64,
+}
+
+/// The pending entries of a group: their count, smallest and greatest IDs, and count per consumer.
+#[derive(Debug, Clone)]
+pub struct PendingSummary {
+    pub count: u64,
+    pub bounds: Option<((u64, u64), (u64, u64))>,
+    pub consumers: Vec<(String, u64)>,
+}
+
+/// The state of a stream as reported by XINFO STREAM.
+#[derive(Debug, Clone)]
+pub struct Info {
+    pub length: u64,
+    pub last_id: (u64, u64),
+    pub max_deleted_id: (u64, u64),
+    pub added: u64,
+    /// The ID of the first entry, 0-0 for an empty stream.
+    pub first_id: (u64, u64),
+    pub groups: u64,
+    pub first_entry: Option<Entry>,
+    pub last_entry: Option<Entry>,
+}
+
+/// A consumer group as reported by XINFO GROUPS.
+#[derive(Debug, Clone)]
+pub struct GroupInfo {
+    pub name: String,
+    pub consumers: u64,
+    pub pending: u64,
+    pub last_id: (u64, u64),
+    pub entries_read: Option<u64>,
+    /// The number of entries still to be delivered to the group, `None` when it cannot be told.
+    pub lag: Option<u64>,
+}
+
+/// A consumer as reported by XINFO CONSUMERS.
+#[derive(Debug, Clone)]
+pub struct ConsumerInfo {
+    pub name: String,
+    pub pending: u64,
+    /// Milliseconds since the last interaction of the consumer.
+    pub idle: u64,
+    /// Milliseconds since the last successful read or claim, `None` if there was none.
+    pub inactive: Option<u64>,
+}
+
+impl Default for Stream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<rdb::types::Stream> for Stream {
+    fn from(val: rdb::types::Stream) -> Self {
+        let entries = Entries {
+            items: val.entries.into_iter().collect(),
+            last_id: val.last_id,
+            added: val.added,
+            max_deleted_id: val.max_deleted_id,
+        };
+        let mut groups = BTreeMap::new();
+        for group in val.groups.into_iter() {
+            let times: BTreeMap<(u64, u64), (u64, u64)> = group.pending.into_iter()
+                .map(|(id, time, count)| (id, (time, count)))
+                .collect();
+            let mut pending = BTreeMap::new();
+            let mut consumers = BTreeMap::new();
+            for consumer in group.consumers.into_iter() {
+                for id in consumer.pending.iter() {
+                    let (time, count) = times.get(id).copied().unwrap_or((consumer.seen, 1));
+                    pending.insert(*id, Delivery { consumer: consumer.name.clone(), time, count });
+                }
+                consumers.insert(consumer.name, Consumer {
+                    seen: consumer.seen,
+                    active: consumer.active,
+                    pending: consumer.pending.into_iter().collect(),
+                });
+            }
+            groups.insert(group.name, Group { last_id: group.last_id, entries_read: group.entries_read, pending, consumers });
+        }
+        Stream {
+            shard: Arc::new(Shard {
+                entries: RwLock::new(entries),
+                groups: RwLock::new(groups),
+            })
+        }
+    }
 }
 
 impl Stream {
     pub fn new() -> Self {
         Stream {
             shard: Arc::new(Shard {
-                entries: RwLock::new(BTreeMap::new()),
-                listener: RwLock::new(HashMap::new()),
+                entries: RwLock::new(Entries::default()),
+                groups: RwLock::new(BTreeMap::new()),
+            }),
+        }
+    }
+
+    /// An independent copy of the stream entries and consumer groups.
+    pub async fn duplicate(&self) -> Self {
+        Stream {
+            shard: Arc::new(Shard {
+                entries: RwLock::new(self.shard.entries.read().await.clone()),
+                groups: RwLock::new(self.shard.groups.read().await.clone()),
             }),
         }
     }
 
     pub async fn add_entry(&self, id: Option<(u64, Option<u64>)>, fields: Vec<(Bytes, Bytes)>) -> Result<(u64, u64), Error> {
         let mut shard = self.shard.entries.write().await;
-        let (last_time, last_seq) = shard.iter().last().map(|(k, _)| k).unwrap_or(&(0, 0)).clone();
-        let id = id.unwrap_or((SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64, None));
-        let time = id.0;
-        let seq = id.1.unwrap_or(if time <= last_time { last_seq + 1 } else { 0 });
+        let (last_time, last_seq) = shard.last_id;
+        let (time, seq) = match id {
+            Some((time, Some(seq))) => (time, seq),
+            Some((time, None)) if time <= last_time => (time, last_seq.checked_add(1).ok_or(Error::InvalidID)?),
+            Some((time, None)) => (time, 0),
+            None => {
+                let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64;
+                // the clock may be behind the last ID, after XSETID or a clock change
+                if now > last_time {
+                    (now, 0)
+                } else if last_seq < u64::MAX {
+                    (last_time, last_seq + 1)
+                } else {
+                    (last_time.checked_add(1).ok_or(Error::ExhaustedID)?, 0)
+                }
+            }
+        };
         if time == 0 && seq == 0 {
             return Err(Error::ZeroID);
         }
@@ -41,81 +254,617 @@ impl Stream {
         if time <= last_time && seq <= last_seq {
             return Err(Error::InvalidID);
         }
-        shard.insert((time, seq), fields.clone());
-        let entry = Entry::new(time, seq, fields);
-        let listeners = self.shard.listener.read().await;
-        for listener in listeners.iter() {
-            let _ = listener.1.send(entry.clone()).await;
-        }
+        shard.items.insert((time, seq), fields);
+        shard.last_id = (time, seq);
+        shard.added += 1;
         Ok((time, seq))
     }
 
-    pub async fn range(&self, start: Option<(u64, Option<u64>)>, end: Option<(u64, Option<u64>)>, count: Option<u64>) -> Vec<Entry> {
+    pub async fn len(&self) -> u64 {
+        self.shard.entries.read().await.items.len() as u64
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.shard.entries.read().await.items.is_empty()
+    }
+
+    /// Delete entries by ID, returns how many existed.
+    pub async fn delete(&self, ids: &[(u64, u64)]) -> u64 {
+        let mut shard = self.shard.entries.write().await;
+        let mut deleted = 0;
+        for id in ids.iter() {
+            if shard.items.remove(id).is_some() {
+                deleted += 1;
+                if *id > shard.max_deleted_id {
+                    shard.max_deleted_id = *id;
+                }
+            }
+        }
+        deleted
+    }
+
+    /// Remove the oldest entries as `trim` asks, returns how many were removed.
+    pub async fn trim(&self, trim: &Trim) -> u64 {
+        let mut shard = self.shard.entries.write().await;
+        let limit = match (trim.approximate, trim.limit) {
+            (false, _) | (true, Some(0)) => u64::MAX,
+            (true, Some(limit)) => limit,
+            (true, None) => BLOCK_ENTRIES * BLOCK_ENTRIES,
+        };
+        let mut removed = 0;
+        loop {
+            let len = shard.items.len() as u64;
+            let block = if trim.approximate { BLOCK_ENTRIES.min(len) } else { 1.min(len) };
+            if block == 0 || removed + block > limit {
+                break;
+            }
+            // the last entry of the block decides whether all of it goes
+            let last = match shard.items.keys().nth(block as usize - 1) {
+                Some(last) => *last,
+                None => break,
+            };
+            let remove = match trim.threshold {
+                Threshold::MaxLen(max_len) => len - block >= max_len,
+                Threshold::MinID(min_id) => last < min_id,
+            };
+            if !remove {
+                break;
+            }
+            for _ in 0..block {
+                shard.items.pop_first();
+            }
+            removed += block;
+        }
+        removed
+    }
+
+    /// Set the last ID and, optionally, the number of entries ever added and the greatest deleted ID.
+    pub async fn set_id(&self, id: (u64, u64), added: Option<u64>, max_deleted_id: Option<(u64, u64)>) -> Result<(), Error> {
+        let mut shard = self.shard.entries.write().await;
+        if let Some(top) = shard.items.keys().next_back() {
+            if id < *top {
+                return Err(Error::SetIDTooSmall);
+            }
+        }
+        if let Some(added) = added {
+            if added < shard.items.len() as u64 {
+                return Err(Error::SetIDAddedTooSmall);
+            }
+        }
+        if let Some(max_deleted_id) = max_deleted_id {
+            if id < max_deleted_id {
+                return Err(Error::SetIDBelowDeleted);
+            }
+        }
+        shard.last_id = id;
+        if let Some(added) = added {
+            shard.added = added;
+        }
+        if let Some(max_deleted_id) = max_deleted_id {
+            shard.max_deleted_id = max_deleted_id;
+        }
+        Ok(())
+    }
+
+    /// The last ID, the number of entries ever added and the greatest deleted ID.
+    pub async fn ids(&self) -> ((u64, u64), u64, (u64, u64)) {
+        let shard = self.shard.entries.read().await;
+        (shard.last_id, shard.added, shard.max_deleted_id)
+    }
+
+    /// The entries from `start` to `end`, the greatest first when `rev`.
+    pub async fn range(&self, start: Option<(u64, Option<u64>)>, end: Option<(u64, Option<u64>)>, count: Option<u64>, rev: bool) -> Vec<Entry> {
         let shard = self.shard.entries.read().await;
-        let mut entries = Vec::new();
         let start = start.unwrap_or((0, None));
         let start = (start.0, start.1.unwrap_or(0));
         let end = end.unwrap_or((u64::MAX, None));
         let end = (end.0, end.1.unwrap_or(u64::MAX));
-        let mut iter = shard.range(start..=end);
-        while let Some(((time, seq), fields)) = iter.next() {
-            entries.push(Entry::new(*time, *seq, fields.clone()));
-            if let Some(c) = count {
-                if entries.len() >= c as usize {
-                    break;
+        if start > end {
+            return Vec::new();
+        }
+        let limit = count.map(|count| count as usize).unwrap_or(usize::MAX);
+        let range = shard.items.range(start..=end);
+        let entries: Box<dyn Iterator<Item = _>> = if rev { Box::new(range.rev()) } else { Box::new(range) };
+        entries.take(limit).map(|((time, seq), fields)| Entry::new(*time, *seq, fields.clone())).collect()
+    }
+
+    pub async fn info(&self) -> Info {
+        let entries = self.shard.entries.read().await;
+        let groups = self.shard.groups.read().await.len() as u64;
+        let entry = |(id, fields): (&(u64, u64), &Fields)| Entry::new(id.0, id.1, fields.clone());
+        Info {
+            length: entries.items.len() as u64,
+            last_id: entries.last_id,
+            max_deleted_id: entries.max_deleted_id,
+            added: entries.added,
+            first_id: entries.first_id(),
+            groups,
+            first_entry: entries.items.iter().next().map(entry),
+            last_entry: entries.items.iter().next_back().map(entry),
+        }
+    }
+
+    pub async fn groups_info(&self) -> Vec<GroupInfo> {
+        let entries = self.shard.entries.read().await;
+        let groups = self.shard.groups.read().await;
+        groups.iter().map(|(name, group)| GroupInfo {
+            name: name.clone(),
+            consumers: group.consumers.len() as u64,
+            pending: group.pending.len() as u64,
+            last_id: group.last_id,
+            entries_read: group.entries_read,
+            lag: entries.lag(group),
+        }).collect()
+    }
+
+    pub async fn consumers_info(&self, group: &str) -> Result<Vec<ConsumerInfo>, Error> {
+        let groups = self.shard.groups.read().await;
+        let group = groups.get(group).ok_or(Error::NoGroup)?;
+        let now = now_millis();
+        Ok(group.consumers.iter().map(|(name, consumer)| ConsumerInfo {
+            name: name.clone(),
+            pending: consumer.pending.len() as u64,
+            idle: now.saturating_sub(consumer.seen),
+            inactive: consumer.active.map(|active| now.saturating_sub(active)),
+        }).collect())
+    }
+
+    /// The greatest ID ever added, 0-0 for a new stream.
+    pub async fn last_id(&self) -> (u64, u64) {
+        self.shard.entries.read().await.last_id
+    }
+
+    pub async fn has_group(&self, group: &str) -> bool {
+        self.shard.groups.read().await.contains_key(group)
+    }
+
+    /// The last ID delivered to a group and the number of entries it read, `None` if it does not exist.
+    pub async fn group_progress(&self, group: &str) -> Option<((u64, u64), Option<u64>)> {
+        self.shard.groups.read().await.get(group).map(|group| (group.last_id, group.entries_read))
+    }
+
+    /// Create a consumer group delivering the entries after `id`, the last entry when `None`, having
+    /// read `entries_read` entries if known. Returns the ID the group starts from.
+    pub async fn create_group(&self, group: &str, id: Option<(u64, u64)>, entries_read: Option<u64>) -> Result<(u64, u64), Error> {
+        let id = match id {
+            Some(id) => id,
+            None => self.last_id().await,
+        };
+        let mut groups = self.shard.groups.write().await;
+        if groups.contains_key(group) {
+            return Err(Error::BusyGroup);
+        }
+        groups.insert(group.to_string(), Group {
+            last_id: id,
+            entries_read,
+            pending: BTreeMap::new(),
+            consumers: BTreeMap::new(),
+        });
+        Ok(id)
+    }
+
+    /// Set the last delivered ID of a group, the last entry when `None`, and the number of entries it
+    /// read. Returns the ID set.
+    pub async fn set_group_id(&self, group: &str, id: Option<(u64, u64)>, entries_read: Option<u64>) -> Result<(u64, u64), Error> {
+        let id = match id {
+            Some(id) => id,
+            None => self.last_id().await,
+        };
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        group.last_id = id;
+        group.entries_read = entries_read;
+        Ok(id)
+    }
+
+    pub async fn destroy_group(&self, group: &str) -> bool {
+        self.shard.groups.write().await.remove(group).is_some()
+    }
+
+    /// Create a consumer in a group, returns false if it already exists.
+    pub async fn create_consumer(&self, group: &str, consumer: &str) -> Result<bool, Error> {
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        Ok(group.consumer(consumer, now_millis()))
+    }
+
+    /// Delete a consumer along with its pending entries, returns how many entries it had pending
+    /// or `None` if it does not exist.
+    pub async fn delete_consumer(&self, group: &str, consumer: &str) -> Result<Option<u64>, Error> {
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        let removed = match group.consumers.remove(consumer) {
+            Some(removed) => removed,
+            None => return Ok(None),
+        };
+        for id in removed.pending.iter() {
+            group.pending.remove(id);
+        }
+        Ok(Some(removed.pending.len() as u64))
+    }
+
+    /// Read entries for a consumer of a group. Without `start` the entries after the last delivered ID
+    /// are delivered and, unless `noack`, added to the consumer's pending entries. With `start` the
+    /// consumer's pending entries after it are delivered again.
+    pub async fn read_group(&self, group: &str, consumer: &str, start: Option<(u64, u64)>, count: Option<u64>, noack: bool) -> Result<Claimed, Error> {
+        let entries = self.shard.entries.read().await;
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        let now = now_millis();
+        let mut claimed = Claimed { created: group.consumer(consumer, now), ..Claimed::default() };
+        let limit = count.map(|count| count as usize).unwrap_or(usize::MAX);
+        match start {
+            None => {
+                let after = next_id(group.last_id);
+                let delivered: Vec<(u64, u64)> = match after {
+                    Some(after) => entries.items.range(after..).take(limit).map(|(id, _)| *id).collect(),
+                    None => Vec::new(),
+                };
+                for id in delivered.into_iter() {
+                    group.last_id = id;
+                    // counting on is only exact when no entry was deleted past the ones read
+                    group.entries_read = match group.entries_read {
+                        Some(read) if !entries.has_tombstones_from(id) => Some(read + 1),
+                        _ => entries.estimate_read(id),
+                    };
+                    claimed.entries.push(Entry::new(id.0, id.1, entries.items[&id].clone()));
+                    if !noack {
+                        group.deliver(id, consumer, now, 1);
+                        claimed.deliveries.push((id, now, 1));
+                    }
+                }
+            }
+            Some(start) => {
+                let history: Vec<(u64, u64)> = match next_id(start) {
+                    Some(after) => group.consumers[consumer].pending.range(after..).take(limit).copied().collect(),
+                    None => Vec::new(),
+                };
+                for id in history.into_iter() {
+                    match entries.items.get(&id) {
+                        Some(fields) => {
+                            let delivery = group.pending.get_mut(&id).expect("pending entries are tracked by the group");
+                            delivery.time = now;
+                            delivery.count += 1;
+                            claimed.deliveries.push((id, now, delivery.count));
+                            claimed.entries.push(Entry::new(id.0, id.1, fields.clone()));
+                        }
+                        // an entry deleted from the stream stays pending until acknowledged
+                        None => claimed.entries.push(Entry::deleted(id.0, id.1)),
+                    }
+                }
+            }
+        }
+        if !claimed.deliveries.is_empty() || (start.is_none() && !claimed.entries.is_empty()) {
+            group.consumers.get_mut(consumer).unwrap().active = Some(now);
+        }
+        Ok(claimed)
+    }
+
+    /// Acknowledge pending entries of a group, returns how many were pending.
+    pub async fn ack(&self, group: &str, ids: &[(u64, u64)]) -> Result<u64, Error> {
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        let mut acked = 0;
+        for id in ids.iter() {
+            if group.acknowledge(id) {
+                acked += 1;
+            }
+        }
+        Ok(acked)
+    }
+
+    /// Transfer pending entries idle for at least `min_idle` milliseconds to `consumer`.
+    pub async fn claim(&self, group: &str, consumer: &str, min_idle: u64, ids: &[(u64, u64)], options: &ClaimOptions) -> Result<Claimed, Error> {
+        let entries = self.shard.entries.read().await;
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        let now = now_millis();
+        let mut claimed = Claimed { created: group.consumer(consumer, now), ..Claimed::default() };
+        if let Some(last_id) = options.last_id {
+            if last_id > group.last_id {
+                group.last_id = last_id;
+            }
+        }
+        let time = match (options.time, options.idle) {
+            (Some(time), _) => time,
+            (None, Some(idle)) => now.saturating_sub(idle),
+            (None, None) => now,
+        };
+        for id in ids.iter() {
+            let fields = entries.items.get(id);
+            let (delivered, previous) = match group.pending.get(id) {
+                Some(delivery) => (Some(delivery.time), delivery.count),
+                // forcing makes an entry of the stream pending, whatever its idle time
+                None if options.force && fields.is_some() => (None, 0),
+                None => continue,
+            };
+            if let Some(delivered) = delivered {
+                if min_idle > 0 && now.saturating_sub(delivered) < min_idle {
+                    continue;
+                }
+            }
+            let fields = match fields {
+                Some(fields) => fields,
+                None => {
+                    group.acknowledge(id);
+                    claimed.deleted.push(*id);
+                    continue;
+                }
+            };
+            let count = match options.retry_count {
+                Some(count) => count,
+                None if options.justid => previous,
+                None => previous + 1,
+            };
+            group.deliver(*id, consumer, time, count);
+            claimed.deliveries.push((*id, time, count));
+            claimed.entries.push(Entry::new(id.0, id.1, fields.clone()));
+        }
+        if !claimed.deliveries.is_empty() {
+            group.consumers.get_mut(consumer).unwrap().active = Some(now);
+        }
+        Ok(claimed)
+    }
+
+    /// Scan the pending entries of a group from `start`, claiming up to `count` of those idle for at least
+    /// `min_idle` milliseconds. Returns the ID to continue the scan from, 0-0 once it is complete.
+    pub async fn auto_claim(&self, group: &str, consumer: &str, min_idle: u64, start: (u64, u64), count: u64, justid: bool) -> Result<((u64, u64), Claimed), Error> {
+        let entries = self.shard.entries.read().await;
+        let mut groups = self.shard.groups.write().await;
+        let group = groups.get_mut(group).ok_or(Error::NoGroup)?;
+        let now = now_millis();
+        let mut claimed = Claimed { created: group.consumer(consumer, now), ..Claimed::default() };
+        // bound the work of a single call like Redis does
+        let mut attempts = count.saturating_mul(10);
+        let mut claims = 0;
+        let mut cursor = group.pending.range(start..).map(|(id, _)| *id).next();
+        while let Some(id) = cursor {
+            if attempts == 0 || claims == count {
+                break;
+            }
+            attempts -= 1;
+            cursor = next_id(id).and_then(|next| group.pending.range(next..).map(|(id, _)| *id).next());
+            let fields = match entries.items.get(&id) {
+                Some(fields) => fields,
+                None => {
+                    group.acknowledge(&id);
+                    claimed.deleted.push(id);
+                    continue;
                 }
+            };
+            let (delivered, previous) = (group.pending[&id].time, group.pending[&id].count);
+            if min_idle > 0 && now.saturating_sub(delivered) < min_idle {
+                continue;
             }
+            let count = if justid { previous } else { previous + 1 };
+            group.deliver(id, consumer, now, count);
+            claimed.deliveries.push((id, now, count));
+            claimed.entries.push(Entry::new(id.0, id.1, fields.clone()));
+            claims += 1;
+        }
+        if !claimed.deliveries.is_empty() {
+            group.consumers.get_mut(consumer).unwrap().active = Some(now);
         }
-        entries
+        Ok((cursor.unwrap_or((0, 0)), claimed))
+    }
+
+    pub async fn pending_summary(&self, group: &str) -> Result<PendingSummary, Error> {
+        let groups = self.shard.groups.read().await;
+        let group = groups.get(group).ok_or(Error::NoGroup)?;
+        let bounds = match (group.pending.keys().next(), group.pending.keys().next_back()) {
+            (Some(min), Some(max)) => Some((*min, *max)),
+            _ => None,
+        };
+        let consumers = group.consumers.iter()
+            .filter(|(_, consumer)| !consumer.pending.is_empty())
+            .map(|(name, consumer)| (name.clone(), consumer.pending.len() as u64))
+            .collect();
+        Ok(PendingSummary { count: group.pending.len() as u64, bounds, consumers })
     }
 
-    pub async fn listen(&self) -> (String, mpsc::Receiver<Entry>) {
-        let (tx, rx) = mpsc::channel(32);
-        let mut listeners = self.shard.listener.write().await;
-        let mut key = utils::strings::generate_id(None);
-        while listeners.contains_key(&key) {
-            key = utils::strings::generate_id(None);
+    /// The pending entries of a group between `start` and `end`, optionally only those idle for
+    /// at least `min_idle` milliseconds or owned by `consumer`.
+    pub async fn pending(&self, group: &str, min_idle: Option<u64>, start: Option<ID>, end: Option<ID>, count: u64, consumer: Option<&str>) -> Result<Vec<Pending>, Error> {
+        let groups = self.shard.groups.read().await;
+        let group = groups.get(group).ok_or(Error::NoGroup)?;
+        let start = start.map(|(time, seq)| (time, seq.unwrap_or(0))).unwrap_or((0, 0));
+        let end = end.map(|(time, seq)| (time, seq.unwrap_or(u64::MAX))).unwrap_or((u64::MAX, u64::MAX));
+        if start > end {
+            return Ok(Vec::new());
         }
-        listeners.insert(key.clone(), tx);
-        (key, rx)
+        let now = now_millis();
+        let pending = group.pending.range(start..=end)
+            .filter(|(_, delivery)| consumer.map(|consumer| delivery.consumer == consumer).unwrap_or(true))
+            .map(|(id, delivery)| Pending {
+                id: *id,
+                consumer: delivery.consumer.clone(),
+                idle: now.saturating_sub(delivery.time),
+                count: delivery.count,
+            })
+            .filter(|pending| min_idle.map(|min_idle| pending.idle >= min_idle).unwrap_or(true))
+            .take(count as usize)
+            .collect();
+        Ok(pending)
     }
 
-    pub async fn unlisten(&self, key: String) {
-        let mut listeners = self.shard.listener.write().await;
-        listeners.remove(&key);
+    /// The entries, ID bookkeeping and consumer groups of the stream, as saved in RDB files.
+    pub async fn snapshot(&self) -> rdb::types::Stream {
+        let entries = self.shard.entries.read().await;
+        let groups = self.shard.groups.read().await;
+        rdb::types::Stream {
+            entries: entries.items.iter().map(|(id, fields)| (*id, fields.clone())).collect(),
+            last_id: entries.last_id,
+            max_deleted_id: entries.max_deleted_id,
+            added: entries.added,
+            groups: groups.iter().map(|(name, group)| rdb::types::StreamGroup {
+                name: name.clone(),
+                last_id: group.last_id,
+                entries_read: group.entries_read,
+                pending: group.pending.iter().map(|(id, delivery)| (*id, delivery.time, delivery.count)).collect(),
+                consumers: group.consumers.iter().map(|(name, consumer)| rdb::types::StreamConsumer {
+                    name: name.clone(),
+                    seen: consumer.seen,
+                    active: consumer.active,
+                    pending: consumer.pending.iter().copied().collect(),
+                }).collect(),
+            }).collect(),
+        }
     }
 
     pub async fn encode(&self) -> resp::Type {
         let shard = self.shard.entries.read().await;
         let mut entries = Vec::new();
-        for ((time, seq), fields) in shard.iter() {
+        for ((time, seq), fields) in shard.items.iter() {
             entries.push(Entry::new(*time, *seq, fields.clone()).encode());
         }
         resp::Type::Array(entries)
     }
 }
 
+impl Entries {
+    fn first_id(&self) -> (u64, u64) {
+        self.items.keys().next().copied().unwrap_or((0, 0))
+    }
+
+    /// Whether an entry from `id` on may have been deleted.
+    fn has_tombstones_from(&self, id: (u64, u64)) -> bool {
+        !self.items.is_empty() && self.max_deleted_id != (0, 0) && id <= self.max_deleted_id
+    }
+
+    /// The number of entries added up to `id`, when it can be told without counting them.
+    fn estimate_read(&self, id: (u64, u64)) -> Option<u64> {
+        if self.added == 0 {
+            return Some(0);
+        }
+        if id == self.last_id || (self.items.is_empty() && id < self.last_id) {
+            return Some(self.added);
+        } else if id > self.last_id {
+            return None;
+        }
+        let first_id = self.first_id();
+        // without deletions after the first entry, the entries before it were all trimmed
+        if self.max_deleted_id == (0, 0) || self.max_deleted_id < first_id {
+            let length = self.items.len() as u64;
+            if id < first_id {
+                return Some(self.added - length);
+            } else if id == first_id {
+                return Some(self.added - length + 1);
+            }
+        }
+        None
+    }
+
+    /// The number of entries a group has still to read.
+    fn lag(&self, group: &Group) -> Option<u64> {
+        if self.added == 0 {
+            return Some(0);
+        }
+        let read = match group.entries_read {
+            Some(read) if !self.has_tombstones_from(group.last_id) && group.last_id >= self.first_id() => Some(read),
+            _ => self.estimate_read(group.last_id),
+        };
+        read.map(|read| self.added.saturating_sub(read))
+    }
+}
+
+impl Group {
+    /// Get or create a consumer, marking it as seen. Returns true if it was created.
+    fn consumer(&mut self, name: &str, now: u64) -> bool {
+        match self.consumers.get_mut(name) {
+            Some(consumer) => {
+                consumer.seen = now;
+                false
+            }
+            None => {
+                self.consumers.insert(name.to_string(), Consumer { seen: now, active: None, pending: BTreeSet::new() });
+                true
+            }
+        }
+    }
+
+    /// Make an entry pending for `consumer`, taking it from its previous owner.
+    fn deliver(&mut self, id: (u64, u64), consumer: &str, time: u64, count: u64) {
+        let previous = self.pending.insert(id, Delivery { consumer: consumer.to_string(), time, count });
+        if let Some(previous) = previous {
+            if let Some(owner) = self.consumers.get_mut(&previous.consumer) {
+                owner.pending.remove(&id);
+            }
+        }
+        if let Some(owner) = self.consumers.get_mut(consumer) {
+            owner.pending.insert(id);
+        }
+    }
+
+    fn acknowledge(&mut self, id: &(u64, u64)) -> bool {
+        match self.pending.remove(id) {
+            Some(delivery) => {
+                if let Some(owner) = self.consumers.get_mut(&delivery.consumer) {
+                    owner.pending.remove(id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The smallest ID greater than `id`, `None` past the greatest ID.
+pub fn next_id(id: (u64, u64)) -> Option<(u64, u64)> {
+    match id {
+        (u64::MAX, u64::MAX) => None,
+        (time, u64::MAX) => Some((time + 1, 0)),
+        (time, seq) => Some((time, seq + 1)),
+    }
+}
+
+/// The greatest ID smaller than `id`, `None` for 0-0.
+pub fn prev_id(id: (u64, u64)) -> Option<(u64, u64)> {
+    match id {
+        (0, 0) => None,
+        (time, 0) => Some((time - 1, u64::MAX)),
+        (time, seq) => Some((time, seq - 1)),
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
 #[derive(Debug, Clone)]
 pub struct Entry {
     time: u64,
     seq: u64,
-    fields: Vec<(Bytes, Bytes)>,
+    // `None` for a pending entry deleted from the stream
+    fields: Option<Vec<(Bytes, Bytes)>>,
 }
 
 impl Entry {
     pub fn new(time: u64, seq: u64, fields: Vec<(Bytes, Bytes)>) -> Self {
-        Entry { time, seq, fields }
+        Entry { time, seq, fields: Some(fields) }
+    }
+
+    pub fn deleted(time: u64, seq: u64) -> Self {
+        Entry { time, seq, fields: None }
+    }
+
+    pub fn id(&self) -> (u64, u64) {
+        (self.time, self.seq)
     }
 
     pub fn encode(self) -> resp::Type {
         let mut entry = Vec::new();
         entry.push(resp::Type::BulkString(format!("{}-{}", self.time, self.seq).into()));
-        let mut fields = Vec::new();
-        for v in self.fields.into_iter() {
-            fields.push(resp::Type::BulkString(v.0));
-            fields.push(resp::Type::BulkString(v.1));
+        match self.fields {
+            Some(fields) => {
+                let mut arr = Vec::new();
+                for v in fields.into_iter() {
+                    arr.push(resp::Type::BulkString(v.0));
+                    arr.push(resp::Type::BulkString(v.1));
+                }
+                entry.push(resp::Type::Array(arr));
+            }
+            None => entry.push(resp::Type::Null),
         }
-        entry.push(resp::Type::Array(fields));
         resp::Type::Array(entry)
     }
 }
@@ -124,6 +873,12 @@ impl Entry {
 pub enum Error {
     InvalidID,
     ZeroID,
+    ExhaustedID,
+    BusyGroup,
+    NoGroup,
+    SetIDTooSmall,
+    SetIDAddedTooSmall,
+    SetIDBelowDeleted,
 }
 
 impl std::fmt::Display for Error {
@@ -131,8 +886,160 @@ impl std::fmt::Display for Error {
         match self {
             Error::InvalidID => write!(f, "ERR The ID specified in XADD is equal or smaller than the target stream top item"),
             Error::ZeroID => write!(f, "ERR The ID specified in XADD must be greater than 0-0"),
+            Error::ExhaustedID => write!(f, "ERR The stream has exhausted the last possible ID, unable to add more items"),
+            Error::BusyGroup => write!(f, "BUSYGROUP Consumer Group name already exists"),
+            Error::NoGroup => write!(f, "NOGROUP No such consumer group"),
+            Error::SetIDTooSmall => write!(f, "ERR The ID specified in XSETID is smaller than the target stream top item"),
+            Error::SetIDAddedTooSmall => write!(f, "ERR The entries_added specified in XSETID is smaller than the target stream length"),
+            Error::SetIDBelowDeleted => write!(f, "ERR The ID specified in XSETID is smaller than the provided max_deleted_entry_id"),
         }
     }
 }
 
-impl std::error::Error for Error {}
\ No newline at end of file
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a stream of the entries 1-0 to n-0 with a group "g" delivering from the start
+    async fn stream_of(n: u64) -> Stream {
+        let stream = Stream::new();
+        for time in 1..=n {
+            stream.add_entry(Some((time, Some(0))), vec![("f".into(), "v".into())]).await.unwrap();
+        }
+        stream.create_group("g", Some((0, 0)), Some(0)).await.unwrap();
+        stream
+    }
+
+    fn ids(entries: &[Entry]) -> Vec<(u64, u64)> {
+        entries.iter().map(Entry::id).collect()
+    }
+
+    async fn delivery(stream: &Stream, id: (u64, u64)) -> Option<(String, u64)> {
+        let groups = stream.shard.groups.read().await;
+        groups["g"].pending.get(&id).map(|delivery| (delivery.consumer.clone(), delivery.count))
+    }
+
+    #[tokio::test]
+    async fn read_new_entries_advances_the_group() {
+        let stream = stream_of(3).await;
+        let claimed = stream.read_group("g", "alice", None, Some(2), false).await.unwrap();
+        assert!(claimed.created);
+        assert_eq!(ids(&claimed.entries), vec![(1, 0), (2, 0)]);
+        assert_eq!(stream.group_progress("g").await, Some(((2, 0), Some(2))));
+        let claimed = stream.read_group("g", "alice", None, None, false).await.unwrap();
+        assert!(!claimed.created);
+        assert_eq!(ids(&claimed.entries), vec![(3, 0)]);
+        assert_eq!(stream.group_progress("g").await, Some(((3, 0), Some(3))));
+        assert!(stream.read_group("g", "alice", None, None, false).await.unwrap().entries.is_empty());
+        assert_eq!(stream.pending_summary("g").await.unwrap().count, 3);
+        assert_eq!(delivery(&stream, (1, 0)).await, Some(("alice".to_string(), 1)));
+    }
+
+    #[tokio::test]
+    async fn read_without_ack_leaves_the_pending_entries_alone() {
+        let stream = stream_of(2).await;
+        let claimed = stream.read_group("g", "alice", None, None, true).await.unwrap();
+        assert_eq!(ids(&claimed.entries), vec![(1, 0), (2, 0)]);
+        assert!(claimed.deliveries.is_empty());
+        assert_eq!(stream.group_progress("g").await, Some(((2, 0), Some(2))));
+        assert_eq!(stream.pending_summary("g").await.unwrap().count, 0);
+    }
+
+    #[tokio::test]
+    async fn read_history_delivers_pending_entries_again() {
+        let stream = stream_of(3).await;
+        stream.read_group("g", "alice", None, None, false).await.unwrap();
+        stream.delete(&[(2, 0)]).await;
+        let claimed = stream.read_group("g", "alice", Some((0, 0)), None, false).await.unwrap();
+        assert_eq!(ids(&claimed.entries), vec![(1, 0), (2, 0), (3, 0)]);
+        // the deleted entry is listed without its fields and stays pending
+        assert!(claimed.entries[1].fields.is_none());
+        assert_eq!(claimed.deliveries.iter().map(|(id, _, count)| (*id, *count)).collect::<Vec<_>>(), vec![((1, 0), 2), ((3, 0), 2)]);
+        assert_eq!(delivery(&stream, (2, 0)).await, Some(("alice".to_string(), 1)));
+        // the history starts after the ID given, of the consumer's own pending entries only
+        let claimed = stream.read_group("g", "alice", Some((1, 0)), Some(1), false).await.unwrap();
+        assert_eq!(ids(&claimed.entries), vec![(2, 0)]);
+        assert!(stream.read_group("g", "bob", Some((0, 0)), None, false).await.unwrap().entries.is_empty());
+        assert_eq!(stream.group_progress("g").await, Some(((3, 0), Some(3))));
+    }
+
+    #[test]
+    fn deliver_moves_the_entry_to_the_new_owner() {
+        let mut group = Group { last_id: (0, 0), entries_read: None, pending: BTreeMap::new(), consumers: BTreeMap::new() };
+        group.consumer("alice", 0);
+        group.consumer("bob", 0);
+        group.deliver((1, 0), "alice", 10, 1);
+        assert!(group.consumers["alice"].pending.contains(&(1, 0)));
+        group.deliver((1, 0), "bob", 20, 2);
+        assert!(group.consumers["alice"].pending.is_empty());
+        assert!(group.consumers["bob"].pending.contains(&(1, 0)));
+        let delivery = &group.pending[&(1, 0)];
+        assert_eq!((delivery.consumer.as_str(), delivery.time, delivery.count), ("bob", 20, 2));
+        assert!(group.acknowledge(&(1, 0)));
+        assert!(group.consumers["bob"].pending.is_empty());
+        assert!(!group.acknowledge(&(1, 0)));
+    }
+
+    #[tokio::test]
+    async fn claim_follows_its_options() {
+        let stream = stream_of(4).await;
+        stream.read_group("g", "alice", None, Some(2), false).await.unwrap();
+        // entries delivered just now are not idle enough
+        let claimed = stream.claim("g", "bob", 60_000, &[(1, 0)], &ClaimOptions::default()).await.unwrap();
+        assert!(claimed.entries.is_empty());
+        assert_eq!(delivery(&stream, (1, 0)).await, Some(("alice".to_string(), 1)));
+
+        let claimed = stream.claim("g", "bob", 0, &[(1, 0)], &ClaimOptions::default()).await.unwrap();
+        assert_eq!(ids(&claimed.entries), vec![(1, 0)]);
+        assert_eq!(delivery(&stream, (1, 0)).await, Some(("bob".to_string(), 2)));
+
+        let justid = ClaimOptions { justid: true, ..ClaimOptions::default() };
+        stream.claim("g", "alice", 0, &[(1, 0)], &justid).await.unwrap();
+        assert_eq!(delivery(&stream, (1, 0)).await, Some(("alice".to_string(), 2)));
+
+        let retry = ClaimOptions { retry_count: Some(7), ..ClaimOptions::default() };
+        stream.claim("g", "bob", 0, &[(2, 0)], &retry).await.unwrap();
+        assert_eq!(delivery(&stream, (2, 0)).await, Some(("bob".to_string(), 7)));
+
+        // an entry never delivered is only claimed with FORCE
+        stream.claim("g", "bob", 0, &[(3, 0)], &ClaimOptions::default()).await.unwrap();
+        assert_eq!(delivery(&stream, (3, 0)).await, None);
+        let force = ClaimOptions { force: true, ..ClaimOptions::default() };
+        let claimed = stream.claim("g", "bob", 0, &[(3, 0), (9, 0)], &force).await.unwrap();
+        assert_eq!(ids(&claimed.entries), vec![(3, 0)]);
+        assert_eq!(delivery(&stream, (3, 0)).await, Some(("bob".to_string(), 1)));
+
+        // a pending entry deleted from the stream is dropped instead of claimed
+        stream.delete(&[(2, 0)]).await;
+        let claimed = stream.claim("g", "alice", 0, &[(2, 0)], &ClaimOptions::default()).await.unwrap();
+        assert!(claimed.entries.is_empty());
+        assert_eq!(claimed.deleted, vec![(2, 0)]);
+        assert_eq!(delivery(&stream, (2, 0)).await, None);
+    }
+
+    #[tokio::test]
+    async fn auto_claim_scans_from_the_cursor() {
+        let stream = stream_of(5).await;
+        stream.read_group("g", "alice", None, None, false).await.unwrap();
+        stream.delete(&[(2, 0)]).await;
+        let (cursor, claimed) = stream.auto_claim("g", "bob", 0, (0, 0), 2, false).await.unwrap();
+        // the deleted entry does not count towards COUNT
+        assert_eq!(ids(&claimed.entries), vec![(1, 0), (3, 0)]);
+        assert_eq!(claimed.deleted, vec![(2, 0)]);
+        assert_eq!(cursor, (4, 0));
+        assert_eq!(delivery(&stream, (1, 0)).await, Some(("bob".to_string(), 2)));
+        assert_eq!(delivery(&stream, (2, 0)).await, None);
+
+        let (cursor, claimed) = stream.auto_claim("g", "bob", 0, cursor, 2, true).await.unwrap();
+        assert_eq!(ids(&claimed.entries), vec![(4, 0), (5, 0)]);
+        assert_eq!(delivery(&stream, (4, 0)).await, Some(("bob".to_string(), 1)));
+        assert_eq!(cursor, (0, 0));
+
+        // entries not idle enough are skipped but the scan goes on
+        let (cursor, claimed) = stream.auto_claim("g", "carol", 60_000, (0, 0), 10, false).await.unwrap();
+        assert!(claimed.entries.is_empty() && claimed.deleted.is_empty());
+        assert_eq!(cursor, (0, 0));
+    }
+}
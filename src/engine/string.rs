@@ -1,5 +1,15 @@
 use bytes::Bytes;
 use crate::resp;
+use crate::utils::strings;
+
+/// The NX/XX/GET/KEEPTTL modifiers of SET.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SetOptions {
+    pub nx: bool,
+    pub xx: bool,
+    pub get: bool,
+    pub keep_ttl: bool,
+}
 
 #[derive(Debug, Clone)]
 pub struct String {
@@ -18,8 +28,105 @@ impl From<String> for Bytes {
     }
 }
 
+/// The largest string value that can be built with APPEND or SETRANGE, as `proto-max-bulk-len` in Redis.
+const MAX_LEN: usize = 512 * 1024 * 1024;
+
 impl String {
+    pub fn len(&self) -> u64 {
+        self.val.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.val.is_empty()
+    }
+
+    /// The bytes between `start` and `end` inclusive, negative offsets count from the end.
+    pub fn range(&self, start: i64, end: i64) -> Bytes {
+        let len = self.val.len() as i64;
+        if len == 0 || (start < 0 && end < 0 && start > end) {
+            return Bytes::new();
+        }
+        let start = if start < 0 { (start + len).max(0) } else { start };
+        let end = if end < 0 { (end + len).max(0) } else { end.min(len - 1) };
+        if start > end {
+            return Bytes::new();
+        }
+        self.val.slice(start as usize..end as usize + 1)
+    }
+
+    /// A copy of the value with `value` appended.
+    pub fn append(&self, value: &[u8]) -> Result<String, Error> {
+        if self.val.len() + value.len() > MAX_LEN {
+            return Err(Error::TooLarge);
+        }
+        let mut val = Vec::with_capacity(self.val.len() + value.len());
+        val.extend_from_slice(&self.val);
+        val.extend_from_slice(value);
+        Ok(String::new(val.into()))
+    }
+
+    /// A copy of the value overwritten with `value` at `offset`, zero-padded when the value is shorter.
+    pub fn set_range(&self, offset: u64, value: &[u8]) -> Result<String, Error> {
+        let end = offset.checked_add(value.len() as u64).ok_or(Error::TooLarge)?;
+        if end > MAX_LEN as u64 {
+            return Err(Error::TooLarge);
+        }
+        let mut val = self.val.to_vec();
+        if val.len() < end as usize {
+            val.resize(end as usize, 0);
+        }
+        val[offset as usize..end as usize].copy_from_slice(value);
+        Ok(String::new(val.into()))
+    }
+
+    /// Add `increment` to the value parsed as a 64 bit signed integer.
+    pub fn incr_by(&self, increment: i64) -> Result<i64, Error> {
+        let current = std::str::from_utf8(&self.val)
+            .ok()
+            .and_then(strings::parse_int)
+            .ok_or(Error::NotInteger)?;
+        current.checked_add(increment).ok_or(Error::Overflow)
+    }
+
+    /// Add `increment` to the value parsed as a float, the result must stay finite.
+    pub fn incr_by_float(&self, increment: f64) -> Result<f64, Error> {
+        let current = std::str::from_utf8(&self.val)
+            .ok()
+            .filter(|value| value.trim() == *value)
+            .and_then(strings::parse_float)
+            .ok_or(Error::NotFloat)?;
+        let value = current + increment;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(Error::NaNOrInfinity)
+        }
+    }
+
     pub fn encode(&self) -> resp::Type {
         resp::Type::BulkString(self.val.clone())
     }
 }
+
+#[derive(Debug)]
+pub enum Error {
+    NotInteger,
+    NotFloat,
+    Overflow,
+    NaNOrInfinity,
+    TooLarge,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotInteger => write!(f, "ERR value is not an integer or out of range"),
+            Error::NotFloat => write!(f, "ERR value is not a valid float"),
+            Error::Overflow => write!(f, "ERR increment or decrement would overflow"),
+            Error::NaNOrInfinity => write!(f, "ERR increment would produce NaN or Infinity"),
+            Error::TooLarge => write!(f, "ERR string exceeds maximum allowed size (proto-max-bulk-len)"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -5,10 +5,12 @@ pub mod cmd;
 pub mod connection;
 pub mod listener;
 pub mod db;
+pub mod pubsub;
 pub mod replication;
 pub mod engine;
 pub mod rdb;
 pub mod utils;
+pub mod aof;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
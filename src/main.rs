@@ -1,6 +1,6 @@
 use tokio::net::TcpListener;
 use clap::Parser;
-use redis::{listener, db, replication};
+use redis::{listener, db, replication, aof, engine};
 
 
 #[derive(Parser)]
@@ -16,6 +16,21 @@ struct Config {
 
     #[clap(long, default_value = "dump.rdb", help = "RDB file name")]
     dbfilename: String,
+
+    #[clap(long, default_value_t = 16, help = "Number of databases")]
+    databases: usize,
+
+    #[clap(long, default_value = "", help = "Save the RDB file after <seconds> if there were at least <changes> writes, as pairs of both")]
+    save: engine::SavePoints,
+
+    #[clap(long, default_value = "no", possible_values = ["yes", "no"], help = "Log every write to the append only file")]
+    appendonly: String,
+
+    #[clap(long, default_value = "appendonly.aof", help = "Append only file name, in the RDB file directory")]
+    appendfilename: String,
+
+    #[clap(long, default_value = "everysec", possible_values = ["always", "everysec", "no"], help = "When to fsync the append only file")]
+    appendfsync: aof::Fsync,
 }
 
 
@@ -27,6 +42,10 @@ async fn main() -> redis::Result<()> {
     if let Some(replica) = cfg.replica {
         role = Some(replication::role::Role::new_slave(cfg.port, replica[0].clone(), replica[1].parse().unwrap()));
     }
-    let db = db::DB::new(cfg.dir, cfg.dbfilename, role).await;
+    let aof = match cfg.appendonly.as_str() {
+        "yes" => Some(aof::Config { file_name: cfg.appendfilename, fsync: cfg.appendfsync }),
+        _ => None,
+    };
+    let db = db::DB::new(cfg.dir, cfg.dbfilename, cfg.databases, role, aof, cfg.save).await?;
     listener::Listener::new(db, listener).run().await
 }
\ No newline at end of file
@@ -57,7 +57,7 @@ impl Parse {
 
         match self.next()? {
             // An integer frame type is already stored as an integer.
-            Type::Integer(v) => Ok(v),
+            Type::Integer(v) => u64::try_from(v).map_err(|_| MSG.into()),
             // Simple and bulk frames must be parsed as integers. If the parsing
             // fails, an error is returned.
             Type::SimpleString(data) => atoi::<u64>(data.as_bytes()).ok_or_else(|| MSG.into()),
@@ -66,6 +66,18 @@ impl Parse {
         }
     }
 
+    pub(crate) fn next_signed_int(&mut self) -> Result<i64, Error> {
+        use atoi::atoi;
+        const MSG: &str = "protocol error; invalid number";
+
+        match self.next()? {
+            Type::Integer(v) => Ok(v),
+            Type::SimpleString(data) => atoi::<i64>(data.as_bytes()).ok_or_else(|| MSG.into()),
+            Type::BulkString(data) => atoi::<i64>(&data).ok_or_else(|| MSG.into()),
+            val => Err(format!("protocol error; expected int frame but got {:?}", val).into()),
+        }
+    }
+
     pub(crate) fn finish(&mut self) -> Result<(), Error> {
         if self.parts.next().is_none() {
             Ok(())
@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use bytes::Bytes;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use crate::resp::Type;
+use crate::utils::glob;
+
+/// The registry of channel and pattern subscriptions shared by every connection.
+#[derive(Debug, Clone, Default)]
+pub struct PubSub {
+    shard: Arc<Shard>,
+}
+
+#[derive(Debug, Default)]
+struct Shard {
+    registry: RwLock<Registry>,
+    next_id: AtomicU64,
+    // the `notify-keyspace-events` flags, no events are published while 0
+    keyspace_events: AtomicU32,
+}
+
+/// The classes of keyspace events, selected by the characters of `notify-keyspace-events`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Generic,
+    String,
+    List,
+    Set,
+    Hash,
+    SortedSet,
+    Expired,
+    Evicted,
+    Stream,
+}
+
+const KEYSPACE: u32 = 1 << 0;
+const KEYEVENT: u32 = 1 << 1;
+// the classes in the order they are printed, `A` stands for all of them
+const CLASSES: [(char, Class); 9] = [
+    ('g', Class::Generic),
+    ('$', Class::String),
+    ('l', Class::List),
+    ('s', Class::Set),
+    ('h', Class::Hash),
+    ('z', Class::SortedSet),
+    ('x', Class::Expired),
+    ('e', Class::Evicted),
+    ('t', Class::Stream),
+];
+
+#[derive(Debug, Default)]
+struct Registry {
+    channels: HashMap<Bytes, HashMap<u64, UnboundedSender<Message>>>,
+    patterns: HashMap<Bytes, HashMap<u64, UnboundedSender<Message>>>,
+}
+
+/// A message delivered to a subscriber, `pattern` is set when it matched a pattern subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pattern: Option<Bytes>,
+    channel: Bytes,
+    payload: Bytes,
+}
+
+/// The subscriptions of one connection, removed from the registry when dropped.
+#[derive(Debug)]
+pub struct Subscriber {
+    pubsub: PubSub,
+    id: u64,
+    sender: UnboundedSender<Message>,
+    receiver: UnboundedReceiver<Message>,
+    channels: HashSet<Bytes>,
+    patterns: HashSet<Bytes>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscriber(&self) -> Subscriber {
+        let (sender, receiver) = unbounded_channel();
+        Subscriber {
+            pubsub: self.clone(),
+            id: self.shard.next_id.fetch_add(1, Ordering::Relaxed),
+            sender,
+            receiver,
+            channels: HashSet::new(),
+            patterns: HashSet::new(),
+        }
+    }
+
+    /// Deliver `payload` to the subscribers of `channel` and of every matching pattern,
+    /// returns the number of deliveries.
+    pub fn publish(&self, channel: Bytes, payload: Bytes) -> u64 {
+        let registry = self.shard.registry.read().unwrap();
+        let mut count = 0;
+        if let Some(subscribers) = registry.channels.get(&channel) {
+            for sender in subscribers.values() {
+                let message = Message { pattern: None, channel: channel.clone(), payload: payload.clone() };
+                if sender.send(message).is_ok() {
+                    count += 1;
+                }
+            }
+        }
+        for (pattern, subscribers) in registry.patterns.iter() {
+            if !glob::matches(pattern, &channel) {
+                continue;
+            }
+            for sender in subscribers.values() {
+                let message = Message { pattern: Some(pattern.clone()), channel: channel.clone(), payload: payload.clone() };
+                if sender.send(message).is_ok() {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// The channels with at least one subscriber, optionally only those matching `pattern`.
+    pub fn channels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        let registry = self.shard.registry.read().unwrap();
+        registry.channels.keys()
+            .filter(|channel| pattern.map(|pattern| glob::matches(pattern, channel)).unwrap_or(true))
+            .cloned()
+            .collect()
+    }
+
+    /// The number of subscribers of `channel`, pattern subscriptions are not counted.
+    pub fn numsub(&self, channel: &Bytes) -> u64 {
+        let registry = self.shard.registry.read().unwrap();
+        registry.channels.get(channel).map(|subscribers| subscribers.len() as u64).unwrap_or_default()
+    }
+
+    /// The number of distinct patterns subscribed to.
+    pub fn numpat(&self) -> u64 {
+        self.shard.registry.read().unwrap().patterns.len() as u64
+    }
+
+    /// The `notify-keyspace-events` flags, `A` when every class is enabled.
+    pub fn keyspace_events(&self) -> String {
+        let flags = self.shard.keyspace_events.load(Ordering::Relaxed);
+        let mut events = String::new();
+        if flags & Class::all() == Class::all() {
+            events.push('A');
+        } else {
+            events.extend(CLASSES.iter().filter(|(_, class)| flags & class.flag() != 0).map(|(c, _)| *c));
+        }
+        if flags & KEYSPACE != 0 {
+            events.push('K');
+        }
+        if flags & KEYEVENT != 0 {
+            events.push('E');
+        }
+        events
+    }
+
+    /// Parse and apply `notify-keyspace-events` flags, returns false on an unknown character.
+    pub fn set_keyspace_events(&self, events: &str) -> bool {
+        let mut flags = 0;
+        for c in events.chars() {
+            flags |= match c {
+                'A' => Class::all(),
+                'K' => KEYSPACE,
+                'E' => KEYEVENT,
+                c => match CLASSES.iter().find(|(flag, _)| *flag == c) {
+                    Some((_, class)) => class.flag(),
+                    None => return false,
+                },
+            };
+        }
+        self.shard.keyspace_events.store(flags, Ordering::Relaxed);
+        true
+    }
+
+    /// Publish a keyspace event on `__keyspace@<db>__:<key>` and `__keyevent@<db>__:<event>`,
+    /// as enabled by `notify-keyspace-events`.
+    pub fn notify(&self, class: Class, event: &str, key: &str, db: usize) {
+        let flags = self.shard.keyspace_events.load(Ordering::Relaxed);
+        if flags & class.flag() == 0 {
+            return;
+        }
+        if flags & KEYSPACE != 0 {
+            self.publish(format!("__keyspace@{}__:{}", db, key).into(), Bytes::copy_from_slice(event.as_bytes()));
+        }
+        if flags & KEYEVENT != 0 {
+            self.publish(format!("__keyevent@{}__:{}", db, event).into(), Bytes::copy_from_slice(key.as_bytes()));
+        }
+    }
+}
+
+impl Class {
+    fn flag(self) -> u32 {
+        1 << (2 + self as u32)
+    }
+
+    fn all() -> u32 {
+        CLASSES.iter().fold(0, |flags, (_, class)| flags | class.flag())
+    }
+}
+
+impl Registry {
+    fn remove(map: &mut HashMap<Bytes, HashMap<u64, UnboundedSender<Message>>>, name: &Bytes, id: u64) {
+        if let Some(subscribers) = map.get_mut(name) {
+            subscribers.remove(&id);
+            if subscribers.is_empty() {
+                map.remove(name);
+            }
+        }
+    }
+}
+
+impl Subscriber {
+    /// Subscribe to `channel`, returns false if already subscribed.
+    pub fn subscribe(&mut self, channel: Bytes) -> bool {
+        if !self.channels.insert(channel.clone()) {
+            return false;
+        }
+        let mut registry = self.pubsub.shard.registry.write().unwrap();
+        registry.channels.entry(channel).or_default().insert(self.id, self.sender.clone());
+        true
+    }
+
+    /// Unsubscribe from `channel`, returns false if not subscribed.
+    pub fn unsubscribe(&mut self, channel: &Bytes) -> bool {
+        if !self.channels.remove(channel) {
+            return false;
+        }
+        let mut registry = self.pubsub.shard.registry.write().unwrap();
+        Registry::remove(&mut registry.channels, channel, self.id);
+        true
+    }
+
+    /// Subscribe to the channels matching `pattern`, returns false if already subscribed.
+    pub fn psubscribe(&mut self, pattern: Bytes) -> bool {
+        if !self.patterns.insert(pattern.clone()) {
+            return false;
+        }
+        let mut registry = self.pubsub.shard.registry.write().unwrap();
+        registry.patterns.entry(pattern).or_default().insert(self.id, self.sender.clone());
+        true
+    }
+
+    /// Unsubscribe from `pattern`, returns false if not subscribed.
+    pub fn punsubscribe(&mut self, pattern: &Bytes) -> bool {
+        if !self.patterns.remove(pattern) {
+            return false;
+        }
+        let mut registry = self.pubsub.shard.registry.write().unwrap();
+        Registry::remove(&mut registry.patterns, pattern, self.id);
+        true
+    }
+
+    pub fn channels(&self) -> Vec<Bytes> {
+        self.channels.iter().cloned().collect()
+    }
+
+    pub fn patterns(&self) -> Vec<Bytes> {
+        self.patterns.iter().cloned().collect()
+    }
+
+    /// The number of channels and patterns subscribed to.
+    pub fn count(&self) -> u64 {
+        (self.channels.len() + self.patterns.len()) as u64
+    }
+
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        let mut registry = match self.pubsub.shard.registry.write() {
+            Ok(registry) => registry,
+            Err(_) => return,
+        };
+        for channel in self.channels.iter() {
+            Registry::remove(&mut registry.channels, channel, self.id);
+        }
+        for pattern in self.patterns.iter() {
+            Registry::remove(&mut registry.patterns, pattern, self.id);
+        }
+    }
+}
+
+impl Message {
+    pub fn encode(self) -> Type {
+        match self.pattern {
+            Some(pattern) => Type::Array(vec![
+                Type::BulkString("pmessage".into()),
+                Type::BulkString(pattern),
+                Type::BulkString(self.channel),
+                Type::BulkString(self.payload),
+            ]),
+            None => Type::Array(vec![
+                Type::BulkString("message".into()),
+                Type::BulkString(self.channel),
+                Type::BulkString(self.payload),
+            ]),
+        }
+    }
+}
@@ -1,11 +1,12 @@
 pub(crate) mod version {
     pub const SUPPORTED_MINIMUM: u32 = 1;
-    pub const SUPPORTED_MAXIMUM: u32 = 11;
+    pub const SUPPORTED_MAXIMUM: u32 = 12;
+    pub const CHECKSUM_MINIMUM: u32 = 5;
 }
 
 pub(crate) mod constant {
     pub const RDB_MAGIC: &str = "REDIS";
-    pub const RDB_VERSION: &str = "0007";
+    pub const RDB_VERSION: &str = "0011";
 }
 
 pub(crate) mod length {
@@ -13,9 +14,16 @@ pub(crate) mod length {
     pub const RDB_14BITLEN: u8 = 0b01;
     pub const RDB_32BITLEN: u8 = 0b10;
     pub const RDB_ENCVAL: u8 = 0b11;
+    // the full first bytes of 32 and 64 bit lengths
+    pub const RDB_32BIT: u8 = 0x80;
+    pub const RDB_64BIT: u8 = 0x81;
 }
 
 pub(crate) mod op_code {
+    pub const SLOT_INFO: u8 = 244;
+    pub const FUNCTION2: u8 = 245;
+    pub const IDLE: u8 = 248;
+    pub const FREQ: u8 = 249;
     pub const AUX: u8 = 250;
     pub const RESIZEDB: u8 = 251;
     pub const EXPIRETIME_MS: u8 = 252;
@@ -30,12 +38,34 @@ pub(crate) mod encoding_type {
     pub const SET: u8 = 2;
     pub const ZSET: u8 = 3;
     pub const HASH: u8 = 4;
+    pub const ZSET_2: u8 = 5;
     pub const HASH_ZIPMAP: u8 = 9;
     pub const LIST_ZIPLIST: u8 = 10;
     pub const SET_INTSET: u8 = 11;
     pub const ZSET_ZIPLIST: u8 = 12;
     pub const HASH_ZIPLIST: u8 = 13;
     pub const LIST_QUICKLIST: u8 = 14;
+    pub const STREAM_LISTPACKS: u8 = 15;
+    pub const HASH_LISTPACK: u8 = 16;
+    pub const ZSET_LISTPACK: u8 = 17;
+    pub const LIST_QUICKLIST_2: u8 = 18;
+    pub const STREAM_LISTPACKS_2: u8 = 19;
+    pub const SET_LISTPACK: u8 = 20;
+    pub const STREAM_LISTPACKS_3: u8 = 21;
+}
+
+pub(crate) mod quicklist {
+    // a quicklist node holding a single large element as is
+    pub const CONTAINER_PLAIN: u64 = 1;
+}
+
+pub(crate) mod stream {
+    pub const ITEM_FLAG_DELETED: i64 = 1;
+    pub const ITEM_FLAG_SAMEFIELDS: i64 = 2;
+    // the entries per listpack node written, Redis' default stream-node-max-entries
+    pub const NODE_ENTRIES: usize = 100;
+    // an unknown number of entries read by a consumer group, or an active time for a consumer never active
+    pub const UNKNOWN: u64 = u64::MAX;
 }
 
 pub(crate) mod encoding {
@@ -0,0 +1,24 @@
+//! The intset format, a sorted array of integers Redis stores small sets of integers in.
+//! Layout: `<width of the integers u32> <number of integers u32> <integer>*`
+
+const HEADER_SIZE: usize = 8;
+
+/// Decode an intset into its members as their decimal representation.
+pub(crate) fn decode(data: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+    let header = data.get(..HEADER_SIZE).ok_or("invalid intset")?;
+    let width = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    if !matches!(width, 2 | 4 | 8) {
+        return Err("invalid intset encoding".into());
+    }
+    let contents = data.get(HEADER_SIZE..HEADER_SIZE + width * len).ok_or("invalid intset")?;
+    let members = contents.chunks(width).map(|raw| {
+        let value = match width {
+            2 => i16::from_le_bytes([raw[0], raw[1]]) as i64,
+            4 => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as i64,
+            _ => i64::from_le_bytes([raw[0], raw[1], raw[2], raw[3], raw[4], raw[5], raw[6], raw[7]]),
+        };
+        value.to_string().into_bytes()
+    }).collect();
+    Ok(members)
+}
@@ -0,0 +1,169 @@
+//! The listpack format, a compact array of strings and integers Redis serializes some values with.
+//! Layout: `<total bytes u32> <number of elements u16> <element>* <0xFF>`, every element being its
+//! encoding and data followed by their length, so that it can be walked backwards.
+
+const HEADER_SIZE: usize = 6;
+const EOF: u8 = 0xFF;
+
+/// Decode a listpack into its elements, integers as their decimal representation.
+pub(crate) fn decode(data: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+    if data.len() < HEADER_SIZE + 1 {
+        return Err("invalid listpack".into());
+    }
+    let mut elements = Vec::new();
+    let mut pos = HEADER_SIZE;
+    loop {
+        let byte = *data.get(pos).ok_or("invalid listpack")?;
+        if byte == EOF {
+            break;
+        }
+        let (element, size) = decode_element(&data[pos..])?;
+        elements.push(element);
+        pos += size + backlen_size(size);
+    }
+    Ok(elements)
+}
+
+/// Decode the element at the start of `data`, returns it with the size of its encoding and data.
+fn decode_element(data: &[u8]) -> crate::Result<(Vec<u8>, usize)> {
+    let byte = data[0];
+    let bytes = |from: usize, to: usize| data.get(from..to).ok_or("invalid listpack");
+    let int = |size: usize| -> crate::Result<i64> {
+        let raw = bytes(1, 1 + size)?;
+        let mut value = 0u64;
+        for (index, byte) in raw.iter().enumerate() {
+            value |= (*byte as u64) << (8 * index);
+        }
+        // sign-extend from the encoded width
+        let shift = 64 - 8 * size as u32;
+        Ok(((value << shift) as i64) >> shift)
+    };
+    let (element, size) = if byte & 0x80 == 0 {
+        ((byte & 0x7F).to_string().into_bytes(), 1)
+    } else if byte & 0xC0 == 0x80 {
+        let len = (byte & 0x3F) as usize;
+        (bytes(1, 1 + len)?.to_vec(), 1 + len)
+    } else if byte & 0xE0 == 0xC0 {
+        let value = (((byte & 0x1F) as u16) << 8) | *data.get(1).ok_or("invalid listpack")? as u16;
+        // a 13 bit two's complement integer
+        let value = ((value << 3) as i16 >> 3) as i64;
+        (value.to_string().into_bytes(), 2)
+    } else if byte & 0xF0 == 0xE0 {
+        let len = (((byte & 0x0F) as usize) << 8) | *data.get(1).ok_or("invalid listpack")? as usize;
+        (bytes(2, 2 + len)?.to_vec(), 2 + len)
+    } else {
+        match byte {
+            0xF0 => {
+                let raw = bytes(1, 5)?;
+                let len = u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize;
+                (bytes(5, 5 + len)?.to_vec(), 5 + len)
+            }
+            0xF1 => (int(2)?.to_string().into_bytes(), 3),
+            0xF2 => (int(3)?.to_string().into_bytes(), 4),
+            0xF3 => (int(4)?.to_string().into_bytes(), 5),
+            0xF4 => (int(8)?.to_string().into_bytes(), 9),
+            _ => return Err("invalid listpack encoding".into()),
+        }
+    };
+    Ok((element, size))
+}
+
+/// The number of bytes the length of an element takes after it.
+fn backlen_size(size: usize) -> usize {
+    match size {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    }
+}
+
+/// Builds a listpack, element by element.
+#[derive(Debug, Default)]
+pub(crate) struct Listpack {
+    buf: Vec<u8>,
+    len: usize,
+}
+
+impl Listpack {
+    pub fn new() -> Self {
+        Listpack { buf: vec![0; HEADER_SIZE], len: 0 }
+    }
+
+    pub fn push_int(&mut self, value: i64) {
+        let start = self.buf.len();
+        if (0..=127).contains(&value) {
+            self.buf.push(value as u8);
+        } else if (-4096..=4095).contains(&value) {
+            let value = (value as u16) & 0x1FFF;
+            self.buf.push(0xC0 | (value >> 8) as u8);
+            self.buf.push(value as u8);
+        } else {
+            let (tag, size) = match value {
+                -32768..=32767 => (0xF1, 2),
+                -8388608..=8388607 => (0xF2, 3),
+                -2147483648..=2147483647 => (0xF3, 4),
+                _ => (0xF4, 8),
+            };
+            self.buf.push(tag);
+            self.buf.extend_from_slice(&value.to_le_bytes()[..size]);
+        }
+        self.finish_element(start);
+    }
+
+    /// Push a string, as an integer when it is the canonical representation of one like Redis does.
+    pub fn push_str(&mut self, value: &[u8]) {
+        if let Some(int) = canonical_int(value) {
+            return self.push_int(int);
+        }
+        let start = self.buf.len();
+        let len = value.len();
+        if len < 64 {
+            self.buf.push(0x80 | len as u8);
+        } else if len < 4096 {
+            self.buf.push(0xE0 | (len >> 8) as u8);
+            self.buf.push(len as u8);
+        } else {
+            self.buf.push(0xF0);
+            self.buf.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+        self.buf.extend_from_slice(value);
+        self.finish_element(start);
+    }
+
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.buf.push(EOF);
+        let total = self.buf.len() as u32;
+        self.buf[0..4].copy_from_slice(&total.to_le_bytes());
+        let len = self.len.min(u16::MAX as usize) as u16;
+        self.buf[4..6].copy_from_slice(&len.to_le_bytes());
+        self.buf
+    }
+
+    /// Append the length of the element written from `start`, most significant bits first.
+    fn finish_element(&mut self, start: usize) {
+        let size = self.buf.len() - start;
+        let count = backlen_size(size);
+        for index in (0..count).rev() {
+            let mut byte = ((size >> (7 * index)) & 0x7F) as u8;
+            if index != count - 1 {
+                byte |= 0x80;
+            }
+            self.buf.push(byte);
+        }
+        self.len += 1;
+    }
+}
+
+fn canonical_int(value: &[u8]) -> Option<i64> {
+    if value.is_empty() || value.len() > 20 {
+        return None;
+    }
+    let int: i64 = std::str::from_utf8(value).ok()?.parse().ok()?;
+    if int.to_string().as_bytes() == value {
+        Some(int)
+    } else {
+        None
+    }
+}
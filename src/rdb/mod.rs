@@ -1,6 +1,10 @@
 pub mod parser;
 pub mod serializer;
 pub mod types;
-mod consts;
+pub(crate) mod consts;
+mod intset;
+mod listpack;
 mod utils;
-mod writer;
\ No newline at end of file
+mod writer;
+mod ziplist;
+mod zipmap;
\ No newline at end of file
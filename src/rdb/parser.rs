@@ -2,10 +2,12 @@ use std::collections::HashMap;
 use std::ops::Add;
 
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use bytes::Bytes;
 use tokio::io::{AsyncRead, AsyncReadExt};
 
 use lzf;
-use super::consts::{version, constant, op_code, encoding_type, encoding, length};
+use super::consts::{version, constant, op_code, encoding_type, encoding, length, stream, quicklist};
+use super::{intset, listpack, ziplist, zipmap};
 use super::utils;
 use super::types;
 
@@ -18,50 +20,66 @@ pub(crate) async fn verify_magic<R: AsyncRead + Unpin>(input: &mut R) -> crate::
     }
 }
 
-pub(crate) async fn verify_version<R: AsyncRead + Unpin>(input: &mut R) -> crate::Result<()> {
+pub(crate) async fn verify_version<R: AsyncRead + Unpin>(input: &mut R) -> crate::Result<u32> {
     let mut version = [0; 4];
     input.read_exact(&mut version).await?;
-    let version = (version[0] - '0' as u8) as u32 * 1000
-        + (version[1] - '0' as u8) as u32 * 100
-        + (version[2] - '0' as u8) as u32 * 10
-        + (version[3] - '0' as u8) as u32;
+    let version = (version[0] - b'0') as u32 * 1000
+        + (version[1] - b'0') as u32 * 100
+        + (version[2] - b'0') as u32 * 10
+        + (version[3] - b'0') as u32;
     match (version::SUPPORTED_MINIMUM..=version::SUPPORTED_MAXIMUM).contains(&version) {
-        true => Ok(()),
+        true => Ok(version),
         false => Err("unsupported version".into()),
     }
 }
 
-pub(crate) async fn read_length_with_encoding<R: AsyncRead + Unpin>(
+pub(crate) async fn read_long_length_with_encoding<R: AsyncRead + Unpin>(
     input: &mut R,
-) -> crate::Result<(u32, bool)> {
+) -> crate::Result<(u64, bool)> {
     let length;
     let mut is_encoded = false;
     let enc_type = input.read_u8().await?;
     match (enc_type & 0xC0) >> 6 {
         length::RDB_ENCVAL => {
             is_encoded = true;
-            length = (enc_type & 0x3F) as u32;
+            length = (enc_type & 0x3F) as u64;
         }
         length::RDB_6BITLEN => {
-            length = (enc_type & 0x3F) as u32;
+            length = (enc_type & 0x3F) as u64;
         }
         length::RDB_32BITLEN => {
-            length = input.read_u32().await?;
+            length = match enc_type {
+                length::RDB_32BIT => input.read_u32().await? as u64,
+                length::RDB_64BIT => input.read_u64().await?,
+                _ => return Err("invalid length encoding".into()),
+            };
         }
         length::RDB_14BITLEN => {
             let next_byte = input.read_u8().await?;
-            length = (((enc_type & 0x3F) as u32) << 8) | next_byte as u32;
+            length = (((enc_type & 0x3F) as u64) << 8) | next_byte as u64;
         }
         _ => unreachable!()
     }
     Ok((length, is_encoded))
 }
 
+pub(crate) async fn read_length_with_encoding<R: AsyncRead + Unpin>(
+    input: &mut R,
+) -> crate::Result<(u32, bool)> {
+    let (length, is_encoded) = read_long_length_with_encoding(input).await?;
+    Ok((u32::try_from(length).map_err(|_| "length out of range")?, is_encoded))
+}
+
 pub(crate) async fn read_length<R: AsyncRead + Unpin>(input: &mut R) -> crate::Result<u32> {
     let (length, _) = read_length_with_encoding(input).await?;
     Ok(length)
 }
 
+pub(crate) async fn read_long_length<R: AsyncRead + Unpin>(input: &mut R) -> crate::Result<u64> {
+    let (length, _) = read_long_length_with_encoding(input).await?;
+    Ok(length)
+}
+
 pub(crate) async fn read_blob<R: AsyncRead + Unpin>(input: &mut R) -> crate::Result<Vec<u8>> {
     let (length, is_encoded) = read_length_with_encoding(input).await?;
     if is_encoded {
@@ -105,11 +123,15 @@ impl<R: AsyncRead + Unpin> Parser<R> {
         }
     }
     pub fn orders(&self) -> impl Iterator<Item=&types::Order> {
-        return self.orders.iter();
+        self.orders.iter()
+    }
+    /// The input, positioned right after the end of the parsed file.
+    pub fn into_inner(self) -> R {
+        self.input
     }
     pub async fn parse(&mut self) -> crate::Result<()> {
         verify_magic(&mut self.input).await?;
-        verify_version(&mut self.input).await?;
+        let version = verify_version(&mut self.input).await?;
         self.last_database = 0;
         loop {
             let next_op = self.input.read_u8().await?;
@@ -125,6 +147,21 @@ impl<R: AsyncRead + Unpin> Parser<R> {
                     let expire_time = self.input.read_u32_le().await?;
                     self.last_expired = Some(UNIX_EPOCH.add(Duration::from_secs(expire_time as u64)))
                 }
+                op_code::IDLE => {
+                    read_long_length(&mut self.input).await?;
+                }
+                op_code::FREQ => {
+                    self.input.read_u8().await?;
+                }
+                op_code::FUNCTION2 => {
+                    // function libraries are not supported, their code is skipped
+                    read_blob(&mut self.input).await?;
+                }
+                op_code::SLOT_INFO => {
+                    for _ in 0..3 {
+                        read_long_length(&mut self.input).await?;
+                    }
+                }
                 op_code::AUX => {
                     let aux_key = read_blob(&mut self.input).await?;
                     let aux_val = read_blob(&mut self.input).await?;
@@ -137,9 +174,11 @@ impl<R: AsyncRead + Unpin> Parser<R> {
                     self.meta_date.insert(format!("{}-expire-size", self.last_database), expire_size.to_string());
                 }
                 op_code::EOF => {
-                    let mut _checksum = Vec::new();
-                    self.input.read_to_end(&mut _checksum).await?;
-                    // TODO: verify checksum
+                    // files end with a checksum since version 5, what follows is not part of the file
+                    if version >= version::CHECKSUM_MINIMUM {
+                        let _checksum = self.input.read_u64().await?;
+                        // TODO: verify checksum
+                    }
                     break;
                 }
                 _ => {
@@ -160,12 +199,45 @@ impl<R: AsyncRead + Unpin> Parser<R> {
             encoding_type::SET => self.read_set(key).await,
             encoding_type::ZSET => self.read_sorted_set(key).await,
             encoding_type::HASH => self.read_hash(key).await,
-            encoding_type::HASH_ZIPMAP => unimplemented!(),
-            encoding_type::LIST_ZIPLIST => unimplemented!(),
-            encoding_type::SET_INTSET => unimplemented!(),
-            encoding_type::ZSET_ZIPLIST => unimplemented!(),
-            encoding_type::HASH_ZIPLIST => unimplemented!(),
-            encoding_type::LIST_QUICKLIST => unimplemented!(),
+            encoding_type::ZSET_2 => self.read_sorted_set_2(key).await,
+            encoding_type::HASH_ZIPMAP => {
+                let pairs = zipmap::decode(&read_blob(&mut self.input).await?)?;
+                let pairs = pairs.into_iter().map(|(field, value)| (field.into(), value.into())).collect();
+                self.push_order(types::Type::Hash(key, pairs))
+            }
+            encoding_type::LIST_ZIPLIST => {
+                let values = ziplist::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::List(key, values.into_iter().map(Into::into).collect()))
+            }
+            encoding_type::SET_INTSET => {
+                let members = intset::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::Set(key, members.into_iter().map(Into::into).collect()))
+            }
+            encoding_type::SET_LISTPACK => {
+                let members = listpack::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::Set(key, members.into_iter().map(Into::into).collect()))
+            }
+            encoding_type::ZSET_ZIPLIST => {
+                let elements = ziplist::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::SortedSet(key, scored(elements)?))
+            }
+            encoding_type::ZSET_LISTPACK => {
+                let elements = listpack::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::SortedSet(key, scored(elements)?))
+            }
+            encoding_type::HASH_ZIPLIST => {
+                let elements = ziplist::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::Hash(key, paired(elements)?))
+            }
+            encoding_type::HASH_LISTPACK => {
+                let elements = listpack::decode(&read_blob(&mut self.input).await?)?;
+                self.push_order(types::Type::Hash(key, paired(elements)?))
+            }
+            encoding_type::LIST_QUICKLIST => self.read_quicklist(key, 1).await,
+            encoding_type::LIST_QUICKLIST_2 => self.read_quicklist(key, 2).await,
+            encoding_type::STREAM_LISTPACKS => self.read_stream(key, 1).await,
+            encoding_type::STREAM_LISTPACKS_2 => self.read_stream(key, 2).await,
+            encoding_type::STREAM_LISTPACKS_3 => self.read_stream(key, 3).await,
             _ => Err("invalid encoding".into()),
         }
     }
@@ -251,4 +323,181 @@ impl<R: AsyncRead + Unpin> Parser<R> {
         });
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn read_sorted_set_2(&mut self, key: String) -> crate::Result<()> {
+        let set_items = read_length(&mut self.input).await?;
+        let mut items = Vec::with_capacity(set_items as usize);
+        for _ in 0..set_items {
+            let val = read_blob(&mut self.input).await?;
+            let score = f64::from_bits(self.input.read_u64_le().await?);
+            items.push((val.into(), score));
+        }
+        self.push_order(types::Type::SortedSet(key, items))
+    }
+
+    /// Read a list stored as a quicklist, a sequence of nodes: ziplists in version 1, listpacks or
+    /// single plain elements in version 2.
+    async fn read_quicklist(&mut self, key: String, version: u8) -> crate::Result<()> {
+        let nodes = read_long_length(&mut self.input).await?;
+        let mut list = Vec::new();
+        for _ in 0..nodes {
+            let container = match version {
+                1 => None,
+                _ => Some(read_long_length(&mut self.input).await?),
+            };
+            let node = read_blob(&mut self.input).await?;
+            match container {
+                None => list.extend(ziplist::decode(&node)?.into_iter().map(Into::into)),
+                Some(quicklist::CONTAINER_PLAIN) => list.push(node.into()),
+                Some(_) => list.extend(listpack::decode(&node)?.into_iter().map(Into::into)),
+            }
+        }
+        self.push_order(types::Type::List(key, list))
+    }
+
+    fn push_order(&mut self, rtype: types::Type) -> crate::Result<()> {
+        self.orders.push(types::Order {
+            dataset: self.last_database,
+            rtype,
+            expire: self.last_expired,
+        });
+        Ok(())
+    }
+
+    /// Read a stream of the given version of the encoding: 2 adds the ID bookkeeping and the entries
+    /// read by groups, 3 the active time of consumers.
+    async fn read_stream(&mut self, key: String, version: u8) -> crate::Result<()> {
+        let mut val = types::Stream::default();
+        let nodes = read_long_length(&mut self.input).await?;
+        for _ in 0..nodes {
+            let master_id = stream_id(&read_blob(&mut self.input).await?)?;
+            let node = listpack::decode(&read_blob(&mut self.input).await?)?;
+            read_stream_node(master_id, node, &mut val.entries)?;
+        }
+        let length = read_long_length(&mut self.input).await?;
+        val.last_id = self.read_stream_id().await?;
+        if version >= 2 {
+            let _first_id = self.read_stream_id().await?;
+            val.max_deleted_id = self.read_stream_id().await?;
+            val.added = read_long_length(&mut self.input).await?;
+        } else {
+            val.added = length;
+        }
+        let groups = read_long_length(&mut self.input).await?;
+        for _ in 0..groups {
+            let name = String::from_utf8(read_blob(&mut self.input).await?)?;
+            let last_id = self.read_stream_id().await?;
+            let entries_read = match version {
+                1 => None,
+                _ => Some(read_long_length(&mut self.input).await?).filter(|read| *read != stream::UNKNOWN),
+            };
+            let pending_len = read_long_length(&mut self.input).await?;
+            let mut pending = Vec::with_capacity(pending_len as usize);
+            for _ in 0..pending_len {
+                let id = stream_id(&utils::read_exact(&mut self.input, 16).await?)?;
+                let time = self.input.read_u64_le().await?;
+                let count = read_long_length(&mut self.input).await?;
+                pending.push((id, time, count));
+            }
+            let consumers_len = read_long_length(&mut self.input).await?;
+            let mut consumers = Vec::with_capacity(consumers_len as usize);
+            for _ in 0..consumers_len {
+                let name = String::from_utf8(read_blob(&mut self.input).await?)?;
+                let seen = self.input.read_u64_le().await?;
+                let active = match version {
+                    3 => Some(self.input.read_u64_le().await?).filter(|active| *active != stream::UNKNOWN),
+                    _ => Some(seen),
+                };
+                let pending_len = read_long_length(&mut self.input).await?;
+                let mut pending = Vec::with_capacity(pending_len as usize);
+                for _ in 0..pending_len {
+                    pending.push(stream_id(&utils::read_exact(&mut self.input, 16).await?)?);
+                }
+                consumers.push(types::StreamConsumer { name, seen, active, pending });
+            }
+            val.groups.push(types::StreamGroup { name, last_id, entries_read, pending, consumers });
+        }
+        self.push_order(types::Type::Stream(key, val))
+    }
+
+    async fn read_stream_id(&mut self) -> crate::Result<(u64, u64)> {
+        Ok((read_long_length(&mut self.input).await?, read_long_length(&mut self.input).await?))
+    }
+}
+
+/// Pair up the fields and values of a compact hash.
+fn paired(elements: Vec<Vec<u8>>) -> crate::Result<Vec<(Bytes, Bytes)>> {
+    if !elements.len().is_multiple_of(2) {
+        return Err("invalid hash encoding".into());
+    }
+    let mut elements = elements.into_iter();
+    let mut pairs = Vec::new();
+    while let (Some(field), Some(value)) = (elements.next(), elements.next()) {
+        pairs.push((field.into(), value.into()));
+    }
+    Ok(pairs)
+}
+
+/// Pair up the members and scores of a compact sorted set.
+fn scored(elements: Vec<Vec<u8>>) -> crate::Result<Vec<(Bytes, f64)>> {
+    let pairs = paired(elements).map_err(|_| "invalid sorted set encoding")?;
+    let mut items = Vec::with_capacity(pairs.len());
+    for (member, score) in pairs.into_iter() {
+        items.push((member, std::str::from_utf8(&score)?.parse::<f64>()?));
+    }
+    Ok(items)
+}
+
+/// Parse a 128 bit big endian stream ID.
+fn stream_id(raw: &[u8]) -> crate::Result<(u64, u64)> {
+    if raw.len() != 16 {
+        return Err("invalid stream ID".into());
+    }
+    let time = u64::from_be_bytes(raw[..8].try_into()?);
+    let seq = u64::from_be_bytes(raw[8..].try_into()?);
+    Ok((time, seq))
+}
+
+/// Read the entries of a stream node: a master entry with the count of entries and the shared field
+/// names, then every entry, deleted ones being flagged.
+fn read_stream_node(master_id: (u64, u64), node: Vec<Vec<u8>>, entries: &mut Vec<types::StreamEntry>) -> crate::Result<()> {
+    let mut elements = node.into_iter();
+    let count = next_int(&mut elements)?;
+    let deleted = next_int(&mut elements)?;
+    let master_fields_len = next_int(&mut elements)?;
+    let mut master_fields = Vec::with_capacity(master_fields_len as usize);
+    for _ in 0..master_fields_len {
+        master_fields.push(Bytes::from(next_element(&mut elements)?));
+    }
+    next_element(&mut elements)?;
+    for _ in 0..count + deleted {
+        let flags = next_int(&mut elements)?;
+        let time = master_id.0.wrapping_add(next_int(&mut elements)? as u64);
+        let seq = master_id.1.wrapping_add(next_int(&mut elements)? as u64);
+        let mut fields = Vec::new();
+        if flags & stream::ITEM_FLAG_SAMEFIELDS != 0 {
+            for field in master_fields.iter() {
+                fields.push((field.clone(), next_element(&mut elements)?.into()));
+            }
+        } else {
+            for _ in 0..next_int(&mut elements)? {
+                let field = next_element(&mut elements)?;
+                fields.push((field.into(), next_element(&mut elements)?.into()));
+            }
+        }
+        // the number of elements of the entry, to walk the node backwards
+        next_element(&mut elements)?;
+        if flags & stream::ITEM_FLAG_DELETED == 0 {
+            entries.push(((time, seq), fields));
+        }
+    }
+    Ok(())
+}
+
+fn next_element(elements: &mut impl Iterator<Item = Vec<u8>>) -> crate::Result<Vec<u8>> {
+    Ok(elements.next().ok_or("invalid stream node")?)
+}
+
+fn next_int(elements: &mut impl Iterator<Item = Vec<u8>>) -> crate::Result<i64> {
+    Ok(std::str::from_utf8(&next_element(elements)?)?.parse()?)
+}
@@ -1,8 +1,9 @@
 use std::time::SystemTime;
 use bytes::Bytes;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
-use super::consts::{constant, op_code, encoding_type, length};
-use super::types::{Type, Order};
+use super::consts::{constant, op_code, encoding_type, length, stream};
+use super::listpack::Listpack;
+use super::types::{self, Type, Order};
 use super::writer::Crc64AsyncWriter;
 
 pub(crate) async fn write_length<W: AsyncWrite + Unpin>(output: &mut W, length: u32) -> crate::Result<()> {
@@ -22,6 +23,17 @@ pub(crate) async fn write_length<W: AsyncWrite + Unpin>(output: &mut W, length:
     Ok(())
 }
 
+pub(crate) async fn write_long_length<W: AsyncWrite + Unpin>(output: &mut W, length: u64) -> crate::Result<()> {
+    match u32::try_from(length) {
+        Ok(length) => write_length(output, length).await,
+        Err(_) => {
+            output.write_u8(length::RDB_64BIT).await?;
+            output.write_u64(length).await?;
+            Ok(())
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub(crate) async fn write_encoding<W: AsyncWrite + Unpin>(output: &mut W, encoding: u32) -> crate::Result<()> {
     output.write_u8((length::RDB_ENCVAL << 6) | (encoding as u8)).await?;
@@ -62,6 +74,7 @@ impl<W: AsyncWrite + Unpin> Serializer<W> {
 
     pub async fn write_resize_db(&mut self, db: u32, size: u32, expire: u32) -> crate::Result<()> {
         self.write_db(db).await?;
+        self.output.write_u8(op_code::RESIZEDB).await?;
         write_length(&mut self.output, size).await?;
         write_length(&mut self.output, expire).await?;
         Ok(())
@@ -112,6 +125,11 @@ impl<W: AsyncWrite + Unpin> Serializer<W> {
                     write_blob(&mut self.output, value).await?;
                 }
             }
+            Type::Stream(key, val) => {
+                self.output.write_u8(encoding_type::STREAM_LISTPACKS_3).await?;
+                write_blob(&mut self.output, &key.clone().into()).await?;
+                self.write_stream(val).await?;
+            }
         }
         Ok(())
     }
@@ -150,6 +168,53 @@ impl<W: AsyncWrite + Unpin> Serializer<W> {
         Ok(())
     }
 
+    /// Write a stream as Redis 7.2 does: its entries in listpacks keyed by their first ID, its ID
+    /// bookkeeping and its consumer groups.
+    async fn write_stream(&mut self, val: &types::Stream) -> crate::Result<()> {
+        let nodes = val.entries.chunks(stream::NODE_ENTRIES);
+        write_long_length(&mut self.output, nodes.len() as u64).await?;
+        for node in nodes {
+            let master_id = node[0].0;
+            write_blob(&mut self.output, &stream_id(master_id).to_vec().into()).await?;
+            write_blob(&mut self.output, &stream_node(node).into()).await?;
+        }
+        write_long_length(&mut self.output, val.entries.len() as u64).await?;
+        self.write_stream_id(val.last_id).await?;
+        let first_id = val.entries.first().map(|(id, _)| *id).unwrap_or((0, 0));
+        self.write_stream_id(first_id).await?;
+        self.write_stream_id(val.max_deleted_id).await?;
+        write_long_length(&mut self.output, val.added).await?;
+        write_long_length(&mut self.output, val.groups.len() as u64).await?;
+        for group in val.groups.iter() {
+            write_blob(&mut self.output, &group.name.clone().into()).await?;
+            self.write_stream_id(group.last_id).await?;
+            write_long_length(&mut self.output, group.entries_read.unwrap_or(stream::UNKNOWN)).await?;
+            write_long_length(&mut self.output, group.pending.len() as u64).await?;
+            for (id, time, count) in group.pending.iter() {
+                self.output.write_all(&stream_id(*id)).await?;
+                self.output.write_u64_le(*time).await?;
+                write_long_length(&mut self.output, *count).await?;
+            }
+            write_long_length(&mut self.output, group.consumers.len() as u64).await?;
+            for consumer in group.consumers.iter() {
+                write_blob(&mut self.output, &consumer.name.clone().into()).await?;
+                self.output.write_u64_le(consumer.seen).await?;
+                self.output.write_u64_le(consumer.active.unwrap_or(stream::UNKNOWN)).await?;
+                write_long_length(&mut self.output, consumer.pending.len() as u64).await?;
+                for id in consumer.pending.iter() {
+                    self.output.write_all(&stream_id(*id)).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_stream_id(&mut self, id: (u64, u64)) -> crate::Result<()> {
+        write_long_length(&mut self.output, id.0).await?;
+        write_long_length(&mut self.output, id.1).await?;
+        Ok(())
+    }
+
     async fn write_f64(&mut self, value: f64) -> crate::Result<()> {
         if value.is_nan() {
             self.output.write_u8(253).await?;
@@ -162,4 +227,46 @@ impl<W: AsyncWrite + Unpin> Serializer<W> {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// A stream ID as the 128 bit big endian key Redis indexes nodes and pending entries with.
+fn stream_id(id: (u64, u64)) -> [u8; 16] {
+    let mut raw = [0; 16];
+    raw[..8].copy_from_slice(&id.0.to_be_bytes());
+    raw[8..].copy_from_slice(&id.1.to_be_bytes());
+    raw
+}
+
+/// Encode the entries of a stream node in a listpack. A master entry holds the field names of the
+/// first entry, which the following entries with the same fields omit.
+fn stream_node(node: &[types::StreamEntry]) -> Vec<u8> {
+    let (master_id, master_fields) = &node[0];
+    let mut lp = Listpack::new();
+    lp.push_int(node.len() as i64);
+    lp.push_int(0);
+    lp.push_int(master_fields.len() as i64);
+    for (field, _) in master_fields.iter() {
+        lp.push_str(field);
+    }
+    lp.push_int(0);
+    for ((time, seq), fields) in node.iter() {
+        let same_fields = fields.len() == master_fields.len()
+            && fields.iter().zip(master_fields.iter()).all(|((field, _), (master, _))| field == master);
+        lp.push_int(if same_fields { stream::ITEM_FLAG_SAMEFIELDS } else { 0 });
+        lp.push_int(time.wrapping_sub(master_id.0) as i64);
+        lp.push_int(seq.wrapping_sub(master_id.1) as i64);
+        if same_fields {
+            for (_, value) in fields.iter() {
+                lp.push_str(value);
+            }
+            lp.push_int(fields.len() as i64 + 3);
+        } else {
+            lp.push_int(fields.len() as i64);
+            for (field, value) in fields.iter() {
+                lp.push_str(field);
+                lp.push_str(value);
+            }
+            lp.push_int(fields.len() as i64 * 2 + 4);
+        }
+    }
+    lp.into_bytes()
+}
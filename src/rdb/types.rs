@@ -16,4 +16,33 @@ pub enum Type {
     Set(String, Vec<Bytes>), // key, values
     SortedSet(String, Vec<(Bytes, f64)>), // key, values(value, score)
     Hash(String, Vec<(Bytes, Bytes)>), // key, values(field, value)
+    Stream(String, Stream), // key, stream
+}
+
+pub type StreamEntry = ((u64, u64), Vec<(Bytes, Bytes)>); // id, fields(field, value)
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Stream {
+    pub entries: Vec<StreamEntry>,
+    pub last_id: (u64, u64),
+    pub max_deleted_id: (u64, u64),
+    pub added: u64,
+    pub groups: Vec<StreamGroup>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StreamGroup {
+    pub name: String,
+    pub last_id: (u64, u64),
+    pub entries_read: Option<u64>,
+    pub pending: Vec<((u64, u64), u64, u64)>, // id, delivery time, delivery count
+    pub consumers: Vec<StreamConsumer>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StreamConsumer {
+    pub name: String,
+    pub seen: u64,
+    pub active: Option<u64>,
+    pub pending: Vec<(u64, u64)>,
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+//! The ziplist format, the compact array listpacks replaced in Redis 7.
+//! Layout: `<total bytes u32> <offset of the last entry u32> <number of entries u16> <entry>* <0xFF>`,
+//! every entry being the length of the previous one, its encoding and its data.
+
+const HEADER_SIZE: usize = 10;
+const END: u8 = 0xFF;
+const BIG_PREVLEN: u8 = 0xFE;
+
+/// Decode a ziplist into its entries, integers as their decimal representation.
+pub(crate) fn decode(data: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+    let mut entries = Vec::new();
+    let mut pos = HEADER_SIZE;
+    let bytes = |from: usize, len: usize| data.get(from..from + len).ok_or("invalid ziplist");
+    loop {
+        let prevlen = *data.get(pos).ok_or("invalid ziplist")?;
+        if prevlen == END {
+            break;
+        }
+        pos += if prevlen == BIG_PREVLEN { 5 } else { 1 };
+        let encoding = *data.get(pos).ok_or("invalid ziplist")?;
+        let int = |len: usize| -> crate::Result<Vec<u8>> {
+            let raw = bytes(pos + 1, len)?;
+            let mut value = 0u64;
+            for (index, byte) in raw.iter().enumerate() {
+                value |= (*byte as u64) << (8 * index);
+            }
+            let shift = 64 - 8 * len as u32;
+            Ok((((value << shift) as i64) >> shift).to_string().into_bytes())
+        };
+        let (entry, size) = match encoding >> 6 {
+            0b00 => {
+                let len = (encoding & 0x3F) as usize;
+                (bytes(pos + 1, len)?.to_vec(), 1 + len)
+            }
+            0b01 => {
+                let len = (((encoding & 0x3F) as usize) << 8) | *data.get(pos + 1).ok_or("invalid ziplist")? as usize;
+                (bytes(pos + 2, len)?.to_vec(), 2 + len)
+            }
+            0b10 => {
+                let raw = bytes(pos + 1, 4)?;
+                let len = u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize;
+                (bytes(pos + 5, len)?.to_vec(), 5 + len)
+            }
+            _ => match encoding {
+                0xC0 => (int(2)?, 3),
+                0xD0 => (int(4)?, 5),
+                0xE0 => (int(8)?, 9),
+                0xF0 => (int(3)?, 4),
+                0xFE => (int(1)?, 2),
+                // a 4 bit immediate integer from 0 to 12
+                0xF1..=0xFD => (((encoding & 0x0F) - 1).to_string().into_bytes(), 1),
+                _ => return Err("invalid ziplist encoding".into()),
+            },
+        };
+        entries.push(entry);
+        pos += size;
+    }
+    Ok(entries)
+}
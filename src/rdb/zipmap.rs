@@ -0,0 +1,47 @@
+//! The zipmap format, the compact hash of Redis before 2.6.
+//! Layout: `<number of pairs u8> (<len> field <len> <free u8> value <free bytes>)* <0xFF>`,
+//! a length being one byte below 254, or 254 followed by a 4 bytes length.
+
+const BIG_LEN: u8 = 254;
+const END: u8 = 0xFF;
+
+/// Decode a zipmap into its field and value pairs.
+pub(crate) fn decode(data: &[u8]) -> crate::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut pairs = Vec::new();
+    let mut pos = 1;
+    loop {
+        if *data.get(pos).ok_or("invalid zipmap")? == END {
+            break;
+        }
+        let field = read_string(data, &mut pos, false)?;
+        let value = read_string(data, &mut pos, true)?;
+        pairs.push((field, value));
+    }
+    Ok(pairs)
+}
+
+/// Read a length prefixed string at `pos`, values having free bytes after them to skip.
+fn read_string(data: &[u8], pos: &mut usize, value: bool) -> crate::Result<Vec<u8>> {
+    let first = *data.get(*pos).ok_or("invalid zipmap")?;
+    let len = match first {
+        BIG_LEN => {
+            let raw = data.get(*pos + 1..*pos + 5).ok_or("invalid zipmap")?;
+            *pos += 5;
+            u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize
+        }
+        END => return Err("invalid zipmap".into()),
+        len => {
+            *pos += 1;
+            len as usize
+        }
+    };
+    let free = if value {
+        *pos += 1;
+        *data.get(*pos - 1).ok_or("invalid zipmap")? as usize
+    } else {
+        0
+    };
+    let string = data.get(*pos..*pos + len).ok_or("invalid zipmap")?.to_vec();
+    *pos += len + free;
+    Ok(string)
+}
@@ -9,7 +9,7 @@ pub enum Type {
     // RESP2
     SimpleString(String),
     SimpleError(String),
-    Integer(u64),
+    Integer(i64),
     BulkString(Bytes),
     Array(Vec<Type>),
     // RESP3
@@ -66,7 +66,7 @@ impl Type {
                 Ok(())
             }
             b':' => {
-                let _ = get_decimal(cur)?;
+                let _ = get_signed_decimal(cur)?;
                 Ok(())
             }
             b'$' => {
@@ -80,6 +80,9 @@ impl Type {
                     // if is RDBFile, we don't need to skip + 2
                     if cur.remaining() >= len + 2 && cur.chunk()[len] == b'\r' && cur.chunk()[len + 1] == b'\n' {
                         skip(cur, len + 2)
+                    } else if cur.remaining() < len + 2 && !is_rdb_file(cur, len) {
+                        // the trailing \r\n of a bulk string has not arrived yet
+                        Err(Error::Incomplete)
                     } else {
                         skip(cur, len)
                     }
@@ -114,8 +117,8 @@ impl Type {
                 Ok(Type::SimpleError(string))
             }
             b':' => {
-                let len = get_decimal(cur)?;
-                Ok(Type::Integer(len))
+                let val = get_signed_decimal(cur)?;
+                Ok(Type::Integer(val))
             }
             b'$' => {
                 if b'-' == peek_u8(cur)? {
@@ -177,6 +180,10 @@ impl Type {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn flatten(self) -> Vec<Type> {
         match self {
             Type::Array(elements) => elements.into_iter().flat_map(Type::flatten).collect(),
@@ -195,6 +202,12 @@ impl PartialEq<&str> for Type {
     }
 }
 
+/// An RDB file is sent like a bulk string without the trailing \r\n, it is told apart from a
+/// bulk string cut before its \r\n by its magic string.
+fn is_rdb_file(cur: &Cursor<&[u8]>, len: usize) -> bool {
+    cur.remaining() >= len && cur.chunk()[..len].starts_with(b"REDIS")
+}
+
 fn peek_u8(cur: &mut Cursor<&[u8]>) -> Result<u8, Error> {
     if !cur.has_remaining() {
         return Err(Error::Incomplete);
@@ -225,6 +238,14 @@ fn get_decimal(cur: &mut Cursor<&[u8]>) -> Result<u64, Error> {
     atoi::<u64>(line).ok_or_else(|| "protocol error; invalid frame format".into())
 }
 
+/// Read a new-line terminated signed decimal
+fn get_signed_decimal(cur: &mut Cursor<&[u8]>) -> Result<i64, Error> {
+    use atoi::atoi;
+
+    let line = get_line(cur)?;
+    atoi::<i64>(line).ok_or_else(|| "protocol error; invalid frame format".into())
+}
+
 fn get_bool(cur: &mut Cursor<&[u8]>) -> Result<bool, Error> {
     let line = get_line(cur)?;
     if line.len() == 1 {
@@ -0,0 +1,34 @@
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+
+/// The temporary file written next to `path` before it replaces it.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from("temp-");
+    name.push(path.file_name().unwrap_or_default());
+    path.with_file_name(name)
+}
+
+/// Make the temporary file `temp`, written through `file`, replace `path`. The data is flushed to the disk
+/// before the rename and the directory after it, so that a crash leaves either file whole.
+pub async fn commit(file: File, temp: &Path, path: &Path) -> crate::Result<()> {
+    file.sync_all().await?;
+    drop(file);
+    fs::rename(temp, path).await?;
+    sync_dir(path).await
+}
+
+/// Flush the directory holding `path` to the disk, which persists the files created or renamed in it.
+#[cfg(unix)]
+pub async fn sync_dir(path: &Path) -> crate::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir).await?.sync_all().await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn sync_dir(_path: &Path) -> crate::Result<()> {
+    Ok(())
+}
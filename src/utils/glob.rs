@@ -0,0 +1,121 @@
+//! Glob-style pattern matching with the semantics of Redis' `stringmatchlen`, used by KEYS,
+//! SCAN MATCH, PSUBSCRIBE and CONFIG GET.
+//!
+//! - `*` matches any sequence of bytes, `?` matches a single byte
+//! - `[abc]`, `[a-z]` and `[^...]` match a byte in, or not in, a class
+//! - `\x` matches `x` literally, both inside and outside a class
+
+// bounds the recursion of patterns like `*?*?*?...`
+const MAX_NESTING: usize = 1000;
+
+/// Whether the whole of `string` matches `pattern`. Like Redis, an empty string matches
+/// nothing but the empty pattern, callers special-case `*` where every key is wanted.
+pub fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    let mut skip_longer = false;
+    match_impl(pattern, string, false, &mut skip_longer, 0)
+}
+
+/// Like `matches`, ignoring ASCII case.
+pub fn matches_nocase(pattern: &[u8], string: &[u8]) -> bool {
+    let mut skip_longer = false;
+    match_impl(pattern, string, true, &mut skip_longer, 0)
+}
+
+fn match_impl(pattern: &[u8], string: &[u8], nocase: bool, skip_longer: &mut bool, nesting: usize) -> bool {
+    if nesting > MAX_NESTING {
+        return false;
+    }
+    let eq = |a: u8, b: u8| if nocase { a.eq_ignore_ascii_case(&b) } else { a == b };
+    let (mut p, mut s) = (0, 0);
+    while p < pattern.len() && s < string.len() {
+        match pattern[p] {
+            b'*' => {
+                while p + 1 < pattern.len() && pattern[p + 1] == b'*' {
+                    p += 1;
+                }
+                if p + 1 == pattern.len() {
+                    return true;
+                }
+                while s < string.len() {
+                    if match_impl(&pattern[p + 1..], &string[s..], nocase, skip_longer, nesting + 1) {
+                        return true;
+                    }
+                    // the rest of the pattern failed against a suffix, it fails against any shorter one
+                    if *skip_longer {
+                        return false;
+                    }
+                    s += 1;
+                }
+                *skip_longer = true;
+                return false;
+            }
+            b'?' => s += 1,
+            b'[' => {
+                p += 1;
+                let not = pattern.get(p) == Some(&b'^');
+                if not {
+                    p += 1;
+                }
+                let c = string[s];
+                let mut matched = false;
+                loop {
+                    if p >= pattern.len() {
+                        // an unterminated class ends with the pattern
+                        p -= 1;
+                        break;
+                    }
+                    match pattern[p] {
+                        b'\\' if p + 1 < pattern.len() => {
+                            p += 1;
+                            matched |= pattern[p] == c;
+                        }
+                        b']' => break,
+                        start if p + 2 < pattern.len() && pattern[p + 1] == b'-' => {
+                            let (mut start, mut end, mut c) = (start, pattern[p + 2], c);
+                            if start > end {
+                                std::mem::swap(&mut start, &mut end);
+                            }
+                            if nocase {
+                                start = start.to_ascii_lowercase();
+                                end = end.to_ascii_lowercase();
+                                c = c.to_ascii_lowercase();
+                            }
+                            p += 2;
+                            matched |= start <= c && c <= end;
+                        }
+                        other => matched |= eq(other, c),
+                    }
+                    p += 1;
+                }
+                if not {
+                    matched = !matched;
+                }
+                if !matched {
+                    return false;
+                }
+                s += 1;
+            }
+            b'\\' if p + 1 < pattern.len() => {
+                p += 1;
+                if !eq(pattern[p], string[s]) {
+                    return false;
+                }
+                s += 1;
+            }
+            other => {
+                if !eq(other, string[s]) {
+                    return false;
+                }
+                s += 1;
+            }
+        }
+        p += 1;
+        if s == string.len() {
+            while p < pattern.len() && pattern[p] == b'*' {
+                p += 1;
+            }
+            break;
+        }
+    }
+    p == pattern.len() && s == string.len()
+}
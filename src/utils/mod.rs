@@ -1,2 +1,4 @@
 pub mod sync;
-pub mod strings;
\ No newline at end of file
+pub mod strings;
+pub mod glob;
+pub mod fs;
\ No newline at end of file
@@ -14,4 +14,30 @@ pub fn generate_id(len: Option<usize>) -> String {
         })
         .take(len.unwrap_or(16))
         .collect()
-}
\ No newline at end of file
+}
+
+/// Parse a 64 bit signed integer the way Redis does, only in its canonical form: no `+` sign,
+/// leading zeros or `-0`.
+pub fn parse_int(s: &str) -> Option<i64> {
+    match s.parse::<i64>() {
+        Ok(v) if v.to_string() == s => Some(v),
+        _ => None,
+    }
+}
+
+/// Parse a float the way Redis does, accepting `inf`/`-inf` but rejecting NaN.
+pub fn parse_float(s: &str) -> Option<f64> {
+    match s.parse::<f64>() {
+        Ok(v) if !v.is_nan() => Some(v),
+        _ => None,
+    }
+}
+
+/// Format a float the way Redis replies with it.
+pub fn format_float(v: f64) -> String {
+    if v.is_infinite() {
+        if v.is_sign_positive() { "inf".to_string() } else { "-inf".to_string() }
+    } else {
+        v.to_string()
+    }
+}
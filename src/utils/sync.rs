@@ -7,6 +7,12 @@ pub struct Notifier {
     receiver: watch::Receiver<bool>,
 }
 
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Notifier {
     pub fn new() -> Self {
         let (sender, receiver) = watch::channel(false);
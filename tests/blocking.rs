@@ -0,0 +1,115 @@
+mod common;
+
+use std::time::Duration;
+use redis::db::Blocking;
+use redis::engine::list::Direction;
+use common::{command, find};
+
+// long enough for a command sent on another connection to be blocked by then
+const SETTLE: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn test_clients_are_served_in_the_order_they_blocked() {
+    let server = common::start("block-fifo").await;
+    let mut first = server.client().await;
+    let mut second = server.client().await;
+    let mut client = server.client().await;
+    first.send(&["BLPOP", "q", "0"]).await;
+    tokio::time::sleep(SETTLE).await;
+    second.send(&["BLPOP", "q", "0"]).await;
+    tokio::time::sleep(SETTLE).await;
+    assert_eq!(client.call(&["RPUSH", "q", "a", "b", "c"]).await, ":3");
+    assert_eq!(first.read().await, "[q, a]");
+    assert_eq!(second.read().await, "[q, b]");
+    assert_eq!(client.call(&["LRANGE", "q", "0", "-1"]).await, "[c]");
+}
+
+#[tokio::test]
+async fn test_timeout_replies_nil() {
+    let server = common::start("block-timeout").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["BLPOP", "q", "0.1"]).await, "(nil)");
+    assert_eq!(client.call(&["BLMOVE", "q", "d", "LEFT", "LEFT", "0.1"]).await, "(nil)");
+    // the client was unblocked, the next push stays in the list
+    assert_eq!(client.call(&["RPUSH", "q", "a"]).await, ":1");
+    assert_eq!(client.call(&["LLEN", "q"]).await, ":1");
+}
+
+#[tokio::test]
+async fn test_client_closing_the_connection_is_unblocked() {
+    let server = common::start("block-hangup").await;
+    let mut gone = server.client().await;
+    gone.send(&["BLPOP", "jobs", "0"]).await;
+    tokio::time::sleep(SETTLE).await;
+    drop(gone);
+    tokio::time::sleep(SETTLE).await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["RPUSH", "jobs", "j1"]).await, ":1");
+    assert_eq!(client.call(&["LLEN", "jobs"]).await, ":1");
+}
+
+#[tokio::test]
+async fn test_value_served_to_a_closed_client_is_pushed_back() {
+    let server = common::start("block-push-back").await;
+    let mut blocked = server.db.clone();
+    let (close, closed) = tokio::sync::oneshot::channel::<()>();
+    let waiter = tokio::spawn(async move {
+        let closed = async {
+            let _ = closed.await;
+        };
+        blocked.block(vec!["q".to_string()], Blocking::Pop(Direction::Left, 2), true, None, closed).await
+    });
+    tokio::time::sleep(SETTLE).await;
+    // the push serves the blocked request and the connection closes before it gets the reply
+    let mut db = server.db.clone();
+    close.send(()).unwrap();
+    db.push("q".to_string(), vec!["a".into(), "b".into(), "c".into()], Direction::Right).await.unwrap();
+    assert!(waiter.await.unwrap().unwrap().is_none());
+    let values = db.lrange("q".to_string(), 0, -1).await.unwrap();
+    assert_eq!(values, vec!["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn test_move_and_swapdb_serve_clients_blocked_in_the_destination() {
+    let server = common::start("block-move").await;
+    let mut blocked = server.client().await;
+    let mut client = server.client().await;
+    blocked.call(&["SELECT", "1"]).await;
+    blocked.send(&["BLPOP", "mk", "2"]).await;
+    tokio::time::sleep(SETTLE).await;
+    client.call(&["RPUSH", "mk", "x"]).await;
+    assert_eq!(client.call(&["MOVE", "mk", "1"]).await, ":1");
+    assert_eq!(blocked.read().await, "[mk, x]");
+
+    blocked.send(&["BLPOP", "sw", "2"]).await;
+    tokio::time::sleep(SETTLE).await;
+    client.call(&["SELECT", "2"]).await;
+    client.call(&["RPUSH", "sw", "y"]).await;
+    assert_eq!(client.call(&["SWAPDB", "1", "2"]).await, "+OK");
+    assert_eq!(blocked.read().await, "[sw, y]");
+    client.call(&["SELECT", "1"]).await;
+    assert_eq!(client.call(&["EXISTS", "sw"]).await, ":0");
+}
+
+#[tokio::test]
+async fn test_served_requests_replicate_as_plain_pops() {
+    let server = common::start("block-replicate").await;
+    let mut replica = server.client().await;
+    replica.sync_as_replica().await;
+    let mut popping = server.client().await;
+    let mut moving = server.client().await;
+    let mut client = server.client().await;
+    popping.send(&["BRPOP", "q", "0"]).await;
+    moving.send(&["BLMOVE", "src", "dst", "RIGHT", "LEFT", "0"]).await;
+    tokio::time::sleep(SETTLE).await;
+    client.call(&["RPUSH", "q", "a"]).await;
+    client.call(&["RPUSH", "src", "b"]).await;
+    assert_eq!(popping.read().await, "[q, a]");
+    assert_eq!(moving.read().await, "b");
+    let stream = replica.read_raw(Duration::from_millis(200)).await;
+    let pop = [command(&["RPUSH", "q", "a"]), command(&["RPOP", "q", "1"])].concat();
+    let lmove = [command(&["RPUSH", "src", "b"]), command(&["LMOVE", "src", "dst", "RIGHT", "LEFT"])].concat();
+    assert!(find(&stream, &pop).is_some(), "{}", String::from_utf8_lossy(&stream));
+    assert!(find(&stream, &lmove).is_some(), "{}", String::from_utf8_lossy(&stream));
+    assert!(find(&stream, b"BRPOP").is_none() && find(&stream, b"BLMOVE").is_none());
+}
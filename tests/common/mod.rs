@@ -0,0 +1,155 @@
+//! An in-process server on a free port with a minimal client, for the tests that run commands
+//! the way clients send them.
+#![allow(dead_code)]
+
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::Duration;
+use bytes::BytesMut;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use redis::aof;
+use redis::db::DB;
+use redis::encoder::Encoder;
+use redis::engine::SavePoints;
+use redis::listener::Listener;
+use redis::resp::Type;
+
+pub struct Server {
+    pub port: u16,
+    pub dir: PathBuf,
+    pub db: DB,
+}
+
+/// Start a server storing its files in a fresh directory named after `name`, without save points.
+pub async fn start(name: &str) -> Server {
+    start_with(name, None).await
+}
+
+/// Like `start`, with the append only file when `aof` is set.
+pub async fn start_with(name: &str, aof: Option<aof::Fsync>) -> Server {
+    let dir = std::env::temp_dir().join(format!("mini-redis-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let aof = aof.map(|fsync| aof::Config { file_name: "appendonly.aof".to_string(), fsync });
+    let db = DB::new(dir.to_string_lossy().into_owned(), "dump.rdb".to_string(), 16, None, aof, SavePoints(Vec::new())).await.unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = Listener::new(db.clone(), listener);
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    Server { port, dir, db }
+}
+
+impl Server {
+    pub async fn client(&self) -> Client {
+        Client {
+            stream: TcpStream::connect(("127.0.0.1", self.port)).await.unwrap(),
+            buffer: BytesMut::new(),
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub struct Client {
+    stream: TcpStream,
+    buffer: BytesMut,
+}
+
+impl Client {
+    pub async fn send(&mut self, args: &[&str]) {
+        let frame = Type::Array(args.iter().map(|arg| Type::BulkString(arg.to_string().into())).collect());
+        self.stream.write_all(&Encoder::encode(&frame)).await.unwrap();
+    }
+
+    /// The next reply, written as in `show`.
+    pub async fn read(&mut self) -> String {
+        show(&self.read_frame().await)
+    }
+
+    pub async fn read_frame(&mut self) -> Type {
+        loop {
+            let mut cur = Cursor::new(&self.buffer[..]);
+            if Type::check(&mut cur).is_ok() {
+                let len = cur.position() as usize;
+                cur.set_position(0);
+                let frame = Type::parse(&mut cur).unwrap();
+                let _ = self.buffer.split_to(len);
+                return frame;
+            }
+            if self.stream.read_buf(&mut self.buffer).await.unwrap() == 0 {
+                panic!("the server closed the connection");
+            }
+        }
+    }
+
+    /// The next reply, `None` if none comes within `timeout`.
+    pub async fn read_within(&mut self, timeout: Duration) -> Option<String> {
+        tokio::time::timeout(timeout, self.read()).await.ok()
+    }
+
+    pub async fn call(&mut self, args: &[&str]) -> String {
+        self.send(args).await;
+        self.read().await
+    }
+
+    pub async fn call_frame(&mut self, args: &[&str]) -> Type {
+        self.send(args).await;
+        self.read_frame().await
+    }
+
+    /// Everything the server sends within `timeout`, unparsed.
+    pub async fn read_raw(&mut self, timeout: Duration) -> Vec<u8> {
+        let mut data = self.buffer.split().to_vec();
+        let _ = tokio::time::timeout(timeout, async {
+            let mut chunk = [0u8; 4096];
+            loop {
+                match self.stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => data.extend_from_slice(&chunk[..n]),
+                }
+            }
+        }).await;
+        data
+    }
+
+    /// Register as a replica, returns once the snapshot was sent so that what follows is the
+    /// replication stream.
+    pub async fn sync_as_replica(&mut self) {
+        assert_eq!(self.call(&["REPLCONF", "listening-port", "7000"]).await, "+OK");
+        self.send(&["PSYNC", "?", "-1"]).await;
+        assert!(self.read().await.starts_with("+FULLRESYNC"));
+        // the snapshot is a bulk string without the trailing CRLF
+        self.read_raw(Duration::from_millis(200)).await;
+    }
+}
+
+/// A reply written the way a test expects it: `+OK` for simple strings, `-ERR ...` for errors,
+/// `:1` for integers, the text of bulk strings, `(nil)` and arrays as `[a, b]`.
+pub fn show(frame: &Type) -> String {
+    match frame {
+        Type::SimpleString(s) => format!("+{}", s),
+        Type::SimpleError(s) => format!("-{}", s),
+        Type::Integer(i) => format!(":{}", i),
+        Type::BulkString(b) => String::from_utf8_lossy(b).into_owned(),
+        Type::Array(items) => format!("[{}]", items.iter().map(show).collect::<Vec<_>>().join(", ")),
+        Type::Null => "(nil)".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// The RESP encoding of a command, as the replication stream and the append only file hold it.
+pub fn command(args: &[&str]) -> Vec<u8> {
+    Encoder::encode(&Type::Array(args.iter().map(|arg| Type::BulkString(arg.to_string().into())).collect()))
+}
+
+/// Where `needle` first appears in `data`.
+pub fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|window| window == needle)
+}
@@ -0,0 +1,92 @@
+mod common;
+
+#[tokio::test]
+async fn test_expire_conditions() {
+    let server = common::start("expire-conditions").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "k", "v"]).await;
+    // without a TTL the key never expires: XX and GT do not apply, LT does
+    assert_eq!(client.call(&["EXPIRE", "k", "100", "XX"]).await, ":0");
+    assert_eq!(client.call(&["EXPIRE", "k", "100", "GT"]).await, ":0");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":-1");
+    assert_eq!(client.call(&["EXPIRE", "k", "100", "NX"]).await, ":1");
+    assert_eq!(client.call(&["EXPIRE", "k", "200", "NX"]).await, ":0");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":100");
+    assert_eq!(client.call(&["EXPIRE", "k", "50", "GT"]).await, ":0");
+    assert_eq!(client.call(&["EXPIRE", "k", "200", "GT"]).await, ":1");
+    assert_eq!(client.call(&["EXPIRE", "k", "300", "LT"]).await, ":0");
+    assert_eq!(client.call(&["EXPIRE", "k", "150", "LT"]).await, ":1");
+    assert_eq!(client.call(&["EXPIRE", "k", "120", "XX"]).await, ":1");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":120");
+    client.call(&["SET", "n", "v"]).await;
+    assert_eq!(client.call(&["EXPIRE", "n", "100", "LT"]).await, ":1");
+    assert_eq!(client.call(&["EXPIRE", "missing", "100"]).await, ":0");
+    assert!(client.call(&["EXPIRE", "k", "100", "NX", "XX"]).await.starts_with("-ERR"));
+    assert!(client.call(&["EXPIRE", "k", "100", "GT", "LT"]).await.starts_with("-ERR"));
+    assert!(client.call(&["EXPIRE", "k", "100", "NX", "GT"]).await.starts_with("-ERR"));
+}
+
+#[tokio::test]
+async fn test_expire_in_the_past_deletes_the_key() {
+    let server = common::start("expire-past").await;
+    let mut client = server.client().await;
+    for key in ["a", "b", "c", "d"] {
+        client.call(&["SET", key, "v"]).await;
+    }
+    assert_eq!(client.call(&["EXPIRE", "a", "-1"]).await, ":1");
+    assert_eq!(client.call(&["PEXPIRE", "b", "0"]).await, ":1");
+    assert_eq!(client.call(&["EXPIREAT", "c", "1"]).await, ":1");
+    assert_eq!(client.call(&["PEXPIREAT", "d", "1000"]).await, ":1");
+    assert_eq!(client.call(&["EXISTS", "a", "b", "c", "d"]).await, ":0");
+    // the conditions still apply to deadlines in the past
+    client.call(&["SET", "e", "v"]).await;
+    assert_eq!(client.call(&["EXPIRE", "e", "-1", "XX"]).await, ":0");
+    assert_eq!(client.call(&["EXISTS", "e"]).await, ":1");
+}
+
+#[tokio::test]
+async fn test_ttl_of_missing_and_persistent_keys() {
+    let server = common::start("expire-ttl").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["TTL", "missing"]).await, ":-2");
+    assert_eq!(client.call(&["PTTL", "missing"]).await, ":-2");
+    assert_eq!(client.call(&["EXPIRETIME", "missing"]).await, ":-2");
+    assert_eq!(client.call(&["PEXPIRETIME", "missing"]).await, ":-2");
+    client.call(&["SET", "k", "v"]).await;
+    assert_eq!(client.call(&["TTL", "k"]).await, ":-1");
+    assert_eq!(client.call(&["PTTL", "k"]).await, ":-1");
+    assert_eq!(client.call(&["EXPIRETIME", "k"]).await, ":-1");
+    assert_eq!(client.call(&["PEXPIRETIME", "k"]).await, ":-1");
+    client.call(&["PEXPIRE", "k", "10000"]).await;
+    let pttl: i64 = client.call(&["PTTL", "k"]).await[1..].parse().unwrap();
+    assert!(pttl > 9000 && pttl <= 10000);
+}
+
+#[tokio::test]
+async fn test_expiretime_replies_the_deadline() {
+    let server = common::start("expire-time").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "k", "v"]).await;
+    assert_eq!(client.call(&["EXPIREAT", "k", "4102444800"]).await, ":1");
+    assert_eq!(client.call(&["EXPIRETIME", "k"]).await, ":4102444800");
+    assert_eq!(client.call(&["PEXPIRETIME", "k"]).await, ":4102444800000");
+    assert_eq!(client.call(&["PEXPIREAT", "k", "4102444800123"]).await, ":1");
+    assert_eq!(client.call(&["PEXPIRETIME", "k"]).await, ":4102444800123");
+    assert_eq!(client.call(&["EXPIRETIME", "k"]).await, ":4102444800");
+}
+
+#[tokio::test]
+async fn test_persist_removes_the_ttl() {
+    let server = common::start("expire-persist").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["PERSIST", "missing"]).await, ":0");
+    client.call(&["SET", "k", "v", "EX", "100"]).await;
+    assert_eq!(client.call(&["PERSIST", "k"]).await, ":1");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":-1");
+    assert_eq!(client.call(&["PERSIST", "k"]).await, ":0");
+    // a key that expired while its TTL was set is gone for good
+    client.call(&["SET", "short", "v", "PX", "50"]).await;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(client.call(&["PERSIST", "short"]).await, ":0");
+    assert_eq!(client.call(&["GET", "short"]).await, "(nil)");
+}
@@ -0,0 +1,21 @@
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use redis::utils::fs::{commit, temp_path};
+
+#[tokio::test]
+async fn test_commit_replaces_file() {
+    let dir = std::env::temp_dir().join(format!("mini-redis-fs-{}", std::process::id()));
+    fs::create_dir_all(&dir).await.unwrap();
+    let path = dir.join("dump.rdb");
+    fs::write(&path, b"previous").await.unwrap();
+    let temp = temp_path(&path);
+    assert_eq!(temp, dir.join("temp-dump.rdb"));
+    let mut file = fs::File::create(&temp).await.unwrap();
+    file.write_all(b"snapshot").await.unwrap();
+    // the previous file stays whole until the new one is committed
+    assert_eq!(fs::read(&path).await.unwrap(), b"previous");
+    commit(file, &temp, &path).await.unwrap();
+    assert_eq!(fs::read(&path).await.unwrap(), b"snapshot");
+    assert!(!temp.exists());
+    fs::remove_dir_all(&dir).await.unwrap();
+}
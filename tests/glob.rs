@@ -0,0 +1,120 @@
+use redis::utils::glob::{matches, matches_nocase};
+
+fn check(pattern: &str, string: &str, expected: bool) {
+    assert_eq!(matches(pattern.as_bytes(), string.as_bytes()), expected, "{:?} against {:?}", pattern, string);
+}
+
+#[test]
+fn test_literal() {
+    check("foo", "foo", true);
+    check("foo", "xfoo", false);
+    check("foo", "foox", false);
+    check("foo", "fo", false);
+    check("", "", true);
+    check("", "a", false);
+    check("a", "", false);
+}
+
+#[test]
+fn test_star() {
+    check("*", "anything", true);
+    check("foo*", "foo", true);
+    check("foo*", "foo_a", true);
+    check("foo*", "key_x", false);
+    check("*foo", "xfoo", true);
+    check("*foo", "foox", false);
+    check("f*o", "fo", true);
+    check("f*o", "fxxxo", true);
+    check("f**o", "fxo", true);
+    check("a*b*c", "aXXbYYc", true);
+    check("a*b*c", "aXXcYYb", false);
+    check("{a}*", "{a}x", true);
+    check("*{b}*", "{b}a", true);
+    check("*{b}*", "{a}x", false);
+}
+
+#[test]
+fn test_question_mark() {
+    check("h?llo", "hello", true);
+    check("h?llo", "hallo", true);
+    check("h?llo", "hllo", false);
+    check("h?llo", "heello", false);
+    check("???", "abc", true);
+    check("?*", "a", true);
+}
+
+#[test]
+fn test_class() {
+    check("h[ae]llo", "hello", true);
+    check("h[ae]llo", "hallo", true);
+    check("h[ae]llo", "hillo", false);
+    check("h[^e]llo", "hallo", true);
+    check("h[^e]llo", "hello", false);
+    check("h[a-b]llo", "hallo", true);
+    check("h[a-b]llo", "hbllo", true);
+    check("h[a-b]llo", "hcllo", false);
+    check("user:[0-9]*", "user:42", true);
+    check("user:[0-9]*", "user:x42", false);
+    // reversed ranges are normalized
+    check("[z-a]", "m", true);
+    check("[^a-c]", "d", true);
+    check("[^a-c]", "b", false);
+    // a leading `-` is taken literally, a trailing one still forms a range with `]`
+    check("[-a]", "-", true);
+    check("[a-]", "-", false);
+    check("[]", "a", false);
+    check("[^]", "a", true);
+}
+
+#[test]
+fn test_unterminated_class() {
+    check("[abc", "a", true);
+    check("[abc", "d", false);
+    check("a[", "a", false);
+    check("[^", "a", true);
+}
+
+#[test]
+fn test_escape() {
+    check("h\\*llo", "h*llo", true);
+    check("h\\*llo", "hello", false);
+    check("\\?", "?", true);
+    check("\\?", "a", false);
+    check("\\[a]", "[a]", true);
+    check("[\\]]", "]", true);
+    check("[\\-]", "-", true);
+    check("[\\^a]", "^", true);
+    // a trailing backslash matches itself
+    check("a\\", "a\\", true);
+}
+
+#[test]
+fn test_nocase() {
+    assert!(matches_nocase(b"DIR", b"dir"));
+    assert!(matches_nocase(b"*FILE*", b"dbfilename"));
+    assert!(matches_nocase(b"[A-C]x", b"bX"));
+    assert!(matches_nocase(b"[b]", b"B"));
+    assert!(!matches(b"DIR", b"dir"));
+    assert!(!matches(b"[A-C]", b"b"));
+}
+
+#[test]
+fn test_binary() {
+    assert!(matches(b"\xff*", b"\xff\x00\x01"));
+    assert!(matches(b"?", b"\x00"));
+    assert!(!matches(b"a", b"\xe1"));
+}
+
+#[test]
+fn test_long_nested_loops() {
+    let string = "a".repeat(64);
+    let pattern = format!("{}b", "a*".repeat(52));
+    check(&pattern, &string, false);
+}
+
+#[test]
+fn test_very_long_nested_loops() {
+    let string = "a".repeat(50000);
+    let pattern = "*?".repeat(50000);
+    check(&pattern, &string, false);
+}
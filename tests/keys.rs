@@ -0,0 +1,90 @@
+mod common;
+
+#[tokio::test]
+async fn test_rename_moves_the_ttl() {
+    let server = common::start("keys-rename-ttl").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "a", "v", "EXAT", "4102444800"]).await;
+    client.call(&["SET", "b", "old"]).await;
+    assert_eq!(client.call(&["RENAME", "a", "b"]).await, "+OK");
+    assert_eq!(client.call(&["EXISTS", "a"]).await, ":0");
+    assert_eq!(client.call(&["GET", "b"]).await, "v");
+    assert_eq!(client.call(&["EXPIRETIME", "b"]).await, ":4102444800");
+    // the destination's TTL goes away with its value
+    client.call(&["SET", "c", "v"]).await;
+    assert_eq!(client.call(&["RENAME", "c", "b"]).await, "+OK");
+    assert_eq!(client.call(&["TTL", "b"]).await, ":-1");
+    assert_eq!(client.call(&["RENAME", "missing", "b"]).await, "-ERR no such key");
+}
+
+#[tokio::test]
+async fn test_rename_onto_itself() {
+    let server = common::start("keys-rename-self").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "k", "v", "EX", "100"]).await;
+    assert_eq!(client.call(&["RENAME", "k", "k"]).await, "+OK");
+    assert_eq!(client.call(&["GET", "k"]).await, "v");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":100");
+    assert_eq!(client.call(&["RENAMENX", "k", "k"]).await, ":0");
+    assert_eq!(client.call(&["GET", "k"]).await, "v");
+    assert_eq!(client.call(&["RENAME", "missing", "missing"]).await, "-ERR no such key");
+}
+
+#[tokio::test]
+async fn test_renamenx_onto_an_existing_key() {
+    let server = common::start("keys-renamenx").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "a", "1"]).await;
+    client.call(&["RPUSH", "b", "x"]).await;
+    assert_eq!(client.call(&["RENAMENX", "a", "b"]).await, ":0");
+    assert_eq!(client.call(&["GET", "a"]).await, "1");
+    assert_eq!(client.call(&["LRANGE", "b", "0", "-1"]).await, "[x]");
+    assert_eq!(client.call(&["RENAMENX", "a", "c"]).await, ":1");
+    assert_eq!(client.call(&["EXISTS", "a"]).await, ":0");
+    assert_eq!(client.call(&["GET", "c"]).await, "1");
+}
+
+#[tokio::test]
+async fn test_copy_without_replace_keeps_the_destination() {
+    let server = common::start("keys-copy-replace").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "a", "new", "EXAT", "4102444800"]).await;
+    client.call(&["SET", "b", "old"]).await;
+    assert_eq!(client.call(&["COPY", "a", "b"]).await, ":0");
+    assert_eq!(client.call(&["GET", "b"]).await, "old");
+    assert_eq!(client.call(&["TTL", "b"]).await, ":-1");
+    assert_eq!(client.call(&["COPY", "a", "b", "REPLACE"]).await, ":1");
+    assert_eq!(client.call(&["GET", "b"]).await, "new");
+    assert_eq!(client.call(&["EXPIRETIME", "b"]).await, ":4102444800");
+    assert_eq!(client.call(&["COPY", "missing", "b", "REPLACE"]).await, ":0");
+    assert_eq!(client.call(&["GET", "b"]).await, "new");
+    assert_eq!(client.call(&["COPY", "a", "a"]).await, "-ERR source and destination objects are the same");
+}
+
+#[tokio::test]
+async fn test_copied_collections_are_independent() {
+    let server = common::start("keys-copy-duplicate").await;
+    let mut client = server.client().await;
+    client.call(&["RPUSH", "l", "a"]).await;
+    client.call(&["HSET", "h", "f", "1"]).await;
+    client.call(&["SADD", "s", "a"]).await;
+    client.call(&["ZADD", "z", "1", "a"]).await;
+    client.call(&["XADD", "x", "1-1", "f", "v"]).await;
+    client.call(&["XGROUP", "CREATE", "x", "g", "0"]).await;
+    for key in ["l", "h", "s", "z", "x"] {
+        assert_eq!(client.call(&["COPY", key, &format!("{}2", key)]).await, ":1");
+    }
+    client.call(&["RPUSH", "l2", "b"]).await;
+    client.call(&["HSET", "h2", "f", "2"]).await;
+    client.call(&["SADD", "s2", "b"]).await;
+    client.call(&["ZADD", "z2", "5", "a"]).await;
+    client.call(&["XADD", "x2", "1-2", "f", "w"]).await;
+    client.call(&["XREADGROUP", "GROUP", "g", "c", "STREAMS", "x2", ">"]).await;
+    assert_eq!(client.call(&["LRANGE", "l", "0", "-1"]).await, "[a]");
+    assert_eq!(client.call(&["HGET", "h", "f"]).await, "1");
+    assert_eq!(client.call(&["SMEMBERS", "s"]).await, "[a]");
+    assert_eq!(client.call(&["ZSCORE", "z", "a"]).await, "1");
+    assert_eq!(client.call(&["XLEN", "x"]).await, ":1");
+    assert_eq!(client.call(&["XPENDING", "x", "g"]).await, "[:0, (nil), (nil), (nil)]");
+    assert_eq!(client.call(&["XPENDING", "x2", "g"]).await, "[:2, 1-1, 1-2, [[c, 2]]]");
+}
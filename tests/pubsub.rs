@@ -0,0 +1,70 @@
+use bytes::Bytes;
+use redis::encoder::Encoder;
+use redis::pubsub::{Class, Message, PubSub};
+use redis::resp::Type;
+
+fn check(message: Option<Message>, kind: &str, parts: &[&str]) {
+    let mut arr = vec![Type::BulkString(Bytes::copy_from_slice(kind.as_bytes()))];
+    arr.extend(parts.iter().map(|part| Type::BulkString(Bytes::copy_from_slice(part.as_bytes()))));
+    assert_eq!(Encoder::encode(&message.unwrap().encode()), Encoder::encode(&Type::Array(arr)));
+}
+
+#[tokio::test]
+async fn test_publish_to_channels_and_patterns() {
+    let pubsub = PubSub::new();
+    let mut channel = pubsub.subscriber();
+    let mut pattern = pubsub.subscriber();
+    assert!(channel.subscribe("news".into()));
+    assert!(!channel.subscribe("news".into()));
+    assert!(pattern.psubscribe("n*".into()));
+    assert_eq!(pubsub.publish("news".into(), "hello".into()), 2);
+    assert_eq!(pubsub.publish("other".into(), "ignored".into()), 0);
+    check(channel.recv().await, "message", &["news", "hello"]);
+    check(pattern.recv().await, "pmessage", &["n*", "news", "hello"]);
+    assert_eq!(pubsub.numsub(&"news".into()), 1);
+    assert_eq!(pubsub.numpat(), 1);
+}
+
+#[tokio::test]
+async fn test_dropped_subscriber_is_unregistered() {
+    let pubsub = PubSub::new();
+    let mut subscriber = pubsub.subscriber();
+    subscriber.subscribe("news".into());
+    subscriber.psubscribe("*".into());
+    assert_eq!(pubsub.channels(None), vec![Bytes::from("news")]);
+    drop(subscriber);
+    assert!(pubsub.channels(None).is_empty());
+    assert_eq!(pubsub.numpat(), 0);
+    assert_eq!(pubsub.publish("news".into(), "hello".into()), 0);
+}
+
+#[test]
+fn test_keyspace_events_flags() {
+    let pubsub = PubSub::new();
+    assert_eq!(pubsub.keyspace_events(), "");
+    assert!(pubsub.set_keyspace_events("KEA"));
+    assert_eq!(pubsub.keyspace_events(), "AKE");
+    assert!(pubsub.set_keyspace_events("Ex$g"));
+    assert_eq!(pubsub.keyspace_events(), "g$xE");
+    assert!(!pubsub.set_keyspace_events("Kq"));
+    assert_eq!(pubsub.keyspace_events(), "g$xE");
+    assert!(pubsub.set_keyspace_events(""));
+    assert_eq!(pubsub.keyspace_events(), "");
+}
+
+#[tokio::test]
+async fn test_keyspace_notifications() {
+    let pubsub = PubSub::new();
+    let mut subscriber = pubsub.subscriber();
+    subscriber.psubscribe("__key*__:*".into());
+    // no event is published until enabled
+    pubsub.notify(Class::String, "set", "foo", 0);
+    pubsub.set_keyspace_events("K$");
+    // only the enabled classes are published
+    pubsub.notify(Class::List, "lpush", "list", 0);
+    pubsub.notify(Class::String, "set", "foo", 0);
+    pubsub.set_keyspace_events("Ex");
+    pubsub.notify(Class::Expired, "expired", "foo", 3);
+    check(subscriber.recv().await, "pmessage", &["__key*__:*", "__keyspace@0__:foo", "set"]);
+    check(subscriber.recv().await, "pmessage", &["__key*__:*", "__keyevent@3__:expired", "foo"]);
+}
@@ -1,12 +1,13 @@
+use std::io::Cursor;
 use std::ops::Add;
 use std::path::Path;
 use std::time::{Duration, UNIX_EPOCH};
 use tokio::fs::File;
 use tokio::io::{BufReader};
 use tokio::io::duplex;
-use redis_starter_rust::rdb::parser::Parser;
-use redis_starter_rust::rdb::serializer::Serializer;
-use redis_starter_rust::rdb::types::{Type, Order};
+use redis::rdb::parser::Parser;
+use redis::rdb::serializer::Serializer;
+use redis::rdb::types::{Type, Order, Stream, StreamGroup, StreamConsumer};
 
 #[tokio::test]
 async fn test_string_parser() {
@@ -23,7 +24,7 @@ async fn test_string_parser() {
             Order { dataset: 0, rtype: Type::String("43947".into(), "Positive 16 bit integer".into()), expire: None },
             Order { dataset: 0, rtype: Type::String("-183358245".into(), "Negative 32 bit integer".into()), expire: None },
         ],
-        parser.orders().map(|v| v.clone()).collect::<Vec<_>>()
+        parser.orders().cloned().collect::<Vec<_>>()
     );
 }
 
@@ -65,4 +66,232 @@ async fn test_serializer() {
         orders,
         parser.orders().cloned().collect::<Vec<_>>()
     );
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_resize_db_serializer() {
+    let (mut tx, rx) = duplex(4 * 1024);
+    let mut serializer = Serializer::new(&mut tx);
+    serializer.init().await.unwrap();
+    let orders = vec![
+        Order { dataset: 0, rtype: Type::String("a".into(), "1".into()), expire: None },
+        Order { dataset: 3, rtype: Type::String("a".into(), "2".into()), expire: Some(UNIX_EPOCH.add(Duration::from_secs(1671963072))) },
+        Order { dataset: 3, rtype: Type::String("b".into(), "3".into()), expire: None },
+    ];
+    serializer.write_resize_db(0, 1, 0).await.unwrap();
+    serializer.write_order(&orders[0]).await.unwrap();
+    serializer.write_resize_db(3, 2, 1).await.unwrap();
+    serializer.write_order(&orders[1]).await.unwrap();
+    serializer.write_order(&orders[2]).await.unwrap();
+    serializer.finish().await.unwrap();
+    drop(tx);
+    let mut parser = Parser::new(rx);
+    parser.parse().await.unwrap();
+    assert_eq!(
+        orders,
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_list_serializer() {
+    let (mut tx, rx) = duplex(4 * 1024);
+    let mut serializer = Serializer::new(&mut tx);
+    serializer.init().await.unwrap();
+    let orders = vec![
+        Order { dataset: 0, rtype: Type::List("list".into(), vec!["a".into(), "b".into(), "c".into()]), expire: None },
+        Order { dataset: 0, rtype: Type::List("empty".into(), vec![]), expire: Some(UNIX_EPOCH.add(Duration::from_secs(1671963072))) },
+    ];
+    for order in orders.iter() {
+        serializer.write_order(order).await.unwrap();
+    }
+    serializer.finish().await.unwrap();
+    drop(tx);
+    let mut parser = Parser::new(rx);
+    parser.parse().await.unwrap();
+    assert_eq!(
+        orders,
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_hash_serializer() {
+    let (mut tx, rx) = duplex(4 * 1024);
+    let mut serializer = Serializer::new(&mut tx);
+    serializer.init().await.unwrap();
+    let orders = vec![
+        Order { dataset: 0, rtype: Type::Hash("user:1".into(), vec![("name".into(), "bob".into()), ("age".into(), "42".into())]), expire: None },
+    ];
+    for order in orders.iter() {
+        serializer.write_order(order).await.unwrap();
+    }
+    serializer.finish().await.unwrap();
+    drop(tx);
+    let mut parser = Parser::new(rx);
+    parser.parse().await.unwrap();
+    assert_eq!(
+        orders,
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_set_serializer() {
+    let (mut tx, rx) = duplex(4 * 1024);
+    let mut serializer = Serializer::new(&mut tx);
+    serializer.init().await.unwrap();
+    let orders = vec![
+        Order { dataset: 0, rtype: Type::Set("tags".into(), vec!["red".into(), "green".into(), "blue".into()]), expire: None },
+    ];
+    for order in orders.iter() {
+        serializer.write_order(order).await.unwrap();
+    }
+    serializer.finish().await.unwrap();
+    drop(tx);
+    let mut parser = Parser::new(rx);
+    parser.parse().await.unwrap();
+    assert_eq!(
+        orders,
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_sorted_set_serializer() {
+    let (mut tx, rx) = duplex(4 * 1024);
+    let mut serializer = Serializer::new(&mut tx);
+    serializer.init().await.unwrap();
+    let orders = vec![
+        Order { dataset: 0, rtype: Type::SortedSet("board".into(), vec![("alice".into(), 1.5), ("bob".into(), -3.0), ("carol".into(), f64::INFINITY)]), expire: None },
+    ];
+    for order in orders.iter() {
+        serializer.write_order(order).await.unwrap();
+    }
+    serializer.finish().await.unwrap();
+    drop(tx);
+    let mut parser = Parser::new(rx);
+    parser.parse().await.unwrap();
+    assert_eq!(
+        orders,
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_serializer() {
+    let (mut tx, rx) = duplex(64 * 1024);
+    let mut serializer = Serializer::new(&mut tx);
+    serializer.init().await.unwrap();
+    // enough entries for two nodes, some with other fields than the first, and IDs past 32 bits
+    let mut entries = Vec::new();
+    for seq in 0..150u64 {
+        let fields = match seq % 3 {
+            0 => vec![("temp".into(), seq.to_string().into()), ("unit".into(), "celsius".into())],
+            1 => vec![("temp".into(), "-12.5".into()), ("unit".into(), "kelvin".into())],
+            _ => vec![("alert".into(), "x".repeat(300).into())],
+        };
+        entries.push(((1671963072573 + seq / 10, seq % 10), fields));
+    }
+    let stream = Stream {
+        entries,
+        last_id: (1671963072600, 0),
+        max_deleted_id: (1671963072588, 0),
+        added: 170,
+        groups: vec![StreamGroup {
+            name: "workers".into(),
+            last_id: (1671963072580, 3),
+            entries_read: Some(73),
+            pending: vec![((1671963072573, 1), 1671963080000, 2), ((1671963072574, 0), 1671963081000, 1)],
+            consumers: vec![
+                StreamConsumer { name: "alice".into(), seen: 1671963082000, active: Some(1671963081000), pending: vec![(1671963072573, 1), (1671963072574, 0)] },
+                StreamConsumer { name: "bob".into(), seen: 1671963083000, active: None, pending: vec![] },
+            ],
+        }],
+    };
+    let orders = vec![
+        Order { dataset: 0, rtype: Type::Stream("sensor".into(), stream), expire: None },
+        Order { dataset: 0, rtype: Type::Stream("empty".into(), Stream { last_id: (5, 5), added: 3, ..Stream::default() }), expire: None },
+    ];
+    for order in orders.iter() {
+        serializer.write_order(order).await.unwrap();
+    }
+    serializer.finish().await.unwrap();
+    drop(tx);
+    let mut parser = Parser::new(rx);
+    parser.parse().await.unwrap();
+    assert_eq!(
+        orders,
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_preamble_parser() {
+    // an append only file starts with an RDB file, the logged commands follow its checksum
+    let mut data = Vec::new();
+    let mut serializer = Serializer::new(&mut data);
+    serializer.init().await.unwrap();
+    let order = Order { dataset: 2, rtype: Type::String("key".into(), "value".into()), expire: None };
+    serializer.write_order(&order).await.unwrap();
+    serializer.finish().await.unwrap();
+    let commands = b"*2\r\n$6\r\nSELECT\r\n$1\r\n0\r\n".to_vec();
+    data.extend_from_slice(&commands);
+    let mut parser = Parser::new(Cursor::new(data));
+    parser.parse().await.unwrap();
+    assert_eq!(vec![order], parser.orders().cloned().collect::<Vec<_>>());
+    let input = parser.into_inner();
+    let offset = input.position() as usize;
+    assert_eq!(commands, input.into_inner()[offset..].to_vec());
+}
+
+/// Append a key of the given type, lengths all being below 64.
+fn compact(rdb: &mut Vec<u8>, value_type: u8, key: &str, blob: &[u8]) {
+    rdb.push(value_type);
+    rdb.push(key.len() as u8);
+    rdb.extend_from_slice(key.as_bytes());
+    rdb.push(blob.len() as u8);
+    rdb.extend_from_slice(blob);
+}
+
+#[tokio::test]
+async fn test_compact_encodings_parser() {
+    let mut rdb = b"REDIS0011".to_vec();
+    // the ziplist of 2 and 5 documented in Redis' ziplist.c
+    compact(&mut rdb, 10, "ziplist", &[0x0f, 0, 0, 0, 0x0c, 0, 0, 0, 2, 0, 0x00, 0xf3, 0x02, 0xf6, 0xff]);
+    // a 16 bit intset
+    compact(&mut rdb, 11, "intset", &[2, 0, 0, 0, 3, 0, 0, 0, 0xfe, 0xff, 1, 0, 0x2c, 0x01]);
+    // a zipmap whose second value has 2 free bytes
+    compact(&mut rdb, 9, "zipmap", &[2, 3, b'f', b'o', b'o', 3, 0, b'b', b'a', b'r', 1, b'a', 1, 2, b'b', 0, 0, 0xff]);
+    let listpack = [20, 0, 0, 0, 4, 0, 0x81, b'm', 2, 0x83, b'1', b'.', b'5', 4, 0x81, b'n', 2, 3, 1, 0xff];
+    compact(&mut rdb, 17, "zset", &listpack);
+    compact(&mut rdb, 16, "hash", &listpack);
+    // a quicklist of a listpack holding a and -5000, then a plain node
+    rdb.extend_from_slice(&[18, 9]);
+    rdb.extend_from_slice(b"quicklist");
+    rdb.extend_from_slice(&[2, 2, 14, 14, 0, 0, 0, 2, 0, 0x81, b'a', 2, 0xf1, 0x78, 0xec, 3, 0xff]);
+    rdb.extend_from_slice(&[1, 5]);
+    rdb.extend_from_slice(b"plain");
+    // an idle time and an access frequency before a sorted set with binary scores
+    rdb.extend_from_slice(&[248, 10, 249, 3, 5, 5]);
+    rdb.extend_from_slice(b"zset2");
+    rdb.extend_from_slice(&[1, 1, b'x']);
+    rdb.extend_from_slice(&2.5f64.to_le_bytes());
+    rdb.push(255);
+    rdb.extend_from_slice(&[0; 8]);
+    let mut parser = Parser::new(rdb.as_slice());
+    parser.parse().await.unwrap();
+    let order = |rtype| Order { dataset: 0, rtype, expire: None };
+    assert_eq!(
+        vec![
+            order(Type::List("ziplist".into(), vec!["2".into(), "5".into()])),
+            order(Type::Set("intset".into(), vec!["-2".into(), "1".into(), "300".into()])),
+            order(Type::Hash("zipmap".into(), vec![("foo".into(), "bar".into()), ("a".into(), "b".into())])),
+            order(Type::SortedSet("zset".into(), vec![("m".into(), 1.5), ("n".into(), 3.0)])),
+            order(Type::Hash("hash".into(), vec![("m".into(), "1.5".into()), ("n".into(), "3".into())])),
+            order(Type::List("quicklist".into(), vec!["a".into(), "-5000".into(), "plain".into()])),
+            order(Type::SortedSet("zset2".into(), vec![("x".into(), 2.5)])),
+        ],
+        parser.orders().cloned().collect::<Vec<_>>()
+    );
+}
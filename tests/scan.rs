@@ -0,0 +1,152 @@
+mod common;
+
+use std::collections::{BTreeSet, HashSet};
+use bytes::Bytes;
+use redis::engine::scan::{page, page_of, position};
+use redis::resp::Type;
+
+#[test]
+fn test_page_returns_the_cursor_of_the_first_element_left_out() {
+    let elements = vec![(1, "a"), (3, "b"), (5, "c"), (8, "d")];
+    assert_eq!(page(elements.clone().into_iter(), 2), (5, vec!["a", "b"]));
+    assert_eq!(page(elements.clone().into_iter().skip(2), 2), (0, vec!["c", "d"]));
+    // a count of 0 still makes progress
+    assert_eq!(page(elements.into_iter(), 0), (3, vec!["a"]));
+}
+
+#[test]
+fn test_page_never_splits_equal_positions() {
+    let elements = vec![(1, "a"), (2, "b"), (2, "c"), (2, "d"), (4, "e")];
+    assert_eq!(page(elements.clone().into_iter(), 2), (4, vec!["a", "b", "c", "d"]));
+    assert_eq!(page(elements.into_iter(), 1), (2, vec!["a"]));
+}
+
+#[test]
+fn test_page_of_starts_at_the_cursor() {
+    let members: Vec<Bytes> = (0..100).map(|i| Bytes::from(format!("m{}", i))).collect();
+    let index: BTreeSet<(u64, Bytes)> = members.iter().map(|member| (position(member), member.clone())).collect();
+    let mut cursor = 0;
+    let mut seen = Vec::new();
+    loop {
+        let (next, page) = page_of(&index, cursor, 7);
+        assert!(page.len() <= 7);
+        assert!(page.iter().all(|member| position(member) >= cursor));
+        seen.extend(page.into_iter().cloned());
+        if next == 0 {
+            break;
+        }
+        assert!(next > cursor);
+        cursor = next;
+    }
+    assert_eq!(seen.len(), members.len());
+    assert_eq!(seen.into_iter().collect::<HashSet<_>>(), members.into_iter().collect());
+}
+
+#[tokio::test]
+async fn test_scan_returns_keys_present_for_the_whole_iteration() {
+    let server = common::start("scan-stable").await;
+    let mut db = server.db.clone();
+    let stable: Vec<String> = (0..200).map(|i| format!("stable:{}", i)).collect();
+    for key in stable.iter() {
+        db.set(key.clone(), "v".into(), None, Default::default()).await.unwrap();
+    }
+    for i in 0..100 {
+        db.set(format!("gone:{}", i), "v".into(), None, Default::default()).await.unwrap();
+    }
+    let (mut cursor, mut seen, mut round) = (0, HashSet::new(), 0);
+    loop {
+        let (next, keys) = db.scan(cursor, 10, None).await;
+        seen.extend(keys);
+        // keys come and go between the pages
+        db.set(format!("new:{}", round), "v".into(), None, Default::default()).await.unwrap();
+        db.del(vec![format!("gone:{}", round)]).await;
+        round += 1;
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    assert!(stable.iter().all(|key| seen.contains(key)));
+    assert!(round > 1);
+}
+
+#[tokio::test]
+async fn test_scan_filters_by_type_and_pattern_after_count() {
+    let server = common::start("scan-filter").await;
+    let mut client = server.client().await;
+    for i in 0..20 {
+        client.call(&["SET", &format!("s:{}", i), "v"]).await;
+        client.call(&["RPUSH", &format!("l:{}", i), "v"]).await;
+    }
+    let mut db = server.db.clone();
+    let (mut cursor, mut lists) = (0, Vec::new());
+    loop {
+        let (next, keys) = db.scan(cursor, 5, Some("LIST".to_string())).await;
+        // COUNT bounds the keys looked at, the filter applies afterwards
+        assert!(keys.len() <= 5);
+        lists.extend(keys);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    lists.sort();
+    let mut expected: Vec<String> = (0..20).map(|i| format!("l:{}", i)).collect();
+    expected.sort();
+    assert_eq!(lists, expected);
+    db.del(vec!["l:0".to_string()]).await;
+
+    let (mut cursor, mut matched) = ("0".to_string(), 0);
+    loop {
+        let (next, keys) = match client.call_frame(&["SCAN", &cursor, "MATCH", "s:1*", "COUNT", "3"]).await {
+            Type::Array(mut reply) => match (reply.remove(0), reply.remove(0)) {
+                (next, Type::Array(keys)) => (common::show(&next), keys),
+                other => panic!("unexpected reply {:?}", other),
+            },
+            other => panic!("unexpected reply {:?}", other),
+        };
+        assert!(keys.iter().all(|key| common::show(key).starts_with("s:1")));
+        matched += keys.len();
+        if next == "0" {
+            break;
+        }
+        cursor = next;
+    }
+    // s:1 and s:10 to s:19
+    assert_eq!(matched, 11);
+}
+
+#[tokio::test]
+async fn test_collection_scans_come_back_to_zero() {
+    let server = common::start("scan-collections").await;
+    let mut client = server.client().await;
+    for i in 0..50 {
+        client.call(&["HSET", "h", &format!("f{}", i), "v"]).await;
+        client.call(&["SADD", "s", &format!("m{}", i)]).await;
+        client.call(&["ZADD", "z", &i.to_string(), &format!("m{}", i)]).await;
+    }
+    let db = server.db.clone();
+    let (mut cursor, mut fields) = (0, HashSet::new());
+    loop {
+        let (next, pairs) = db.hscan("h".to_string(), cursor, 8).await.unwrap();
+        fields.extend(pairs.into_iter().map(|(field, _)| field));
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    assert_eq!(fields.len(), 50);
+    let (mut cursor, mut members) = (0, HashSet::new());
+    loop {
+        let (next, page) = db.sscan("s".to_string(), cursor, 8).await.unwrap();
+        members.extend(page);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    assert_eq!(members.len(), 50);
+    let (next, pairs) = db.zscan("z".to_string(), 0, 100).await.unwrap();
+    assert_eq!((next, pairs.len()), (0, 50));
+    assert_eq!(db.sscan("missing".to_string(), 0, 10).await.unwrap(), (0, Vec::new()));
+}
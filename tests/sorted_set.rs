@@ -0,0 +1,99 @@
+mod common;
+
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use bytes::Bytes;
+use redis::engine::sorted_set::{Range, SortedSet};
+
+fn members(pairs: Vec<(Bytes, f64)>) -> Vec<String> {
+    pairs.into_iter().map(|(member, _)| String::from_utf8(member.to_vec()).unwrap()).collect()
+}
+
+fn scored() -> SortedSet {
+    SortedSet::from(vec![
+        (Bytes::from("lo"), f64::NEG_INFINITY),
+        (Bytes::from("a"), 1.0),
+        (Bytes::from("b"), 2.0),
+        (Bytes::from("c"), 2.0),
+        (Bytes::from("d"), 3.0),
+        (Bytes::from("hi"), f64::INFINITY),
+    ])
+}
+
+fn lexical() -> SortedSet {
+    SortedSet::from(["a", "b", "c", "d", "e"].iter().map(|member| (Bytes::from(*member), 0.0)).collect::<Vec<_>>())
+}
+
+#[tokio::test]
+async fn test_score_range_bounds() {
+    let zset = scored();
+    let range = |min, max| Range::Score(min, max);
+    assert_eq!(members(zset.range(&range(Included(2.0), Included(3.0)), false, None).await), vec!["b", "c", "d"]);
+    assert_eq!(members(zset.range(&range(Excluded(2.0), Included(3.0)), false, None).await), vec!["d"]);
+    assert_eq!(members(zset.range(&range(Included(1.0), Excluded(3.0)), false, None).await), vec!["a", "b", "c"]);
+    assert!(zset.range(&range(Excluded(2.0), Excluded(3.0)), false, None).await.is_empty());
+    // the infinite scores are members like any other, only an exclusive infinite bound leaves them out
+    let all = range(Included(f64::NEG_INFINITY), Included(f64::INFINITY));
+    assert_eq!(members(zset.range(&all, false, None).await), vec!["lo", "a", "b", "c", "d", "hi"]);
+    let finite = range(Excluded(f64::NEG_INFINITY), Excluded(f64::INFINITY));
+    assert_eq!(members(zset.range(&finite, false, None).await), vec!["a", "b", "c", "d"]);
+    assert_eq!(members(zset.range(&range(Unbounded, Included(1.0)), false, None).await), vec!["lo", "a"]);
+}
+
+#[tokio::test]
+async fn test_reversed_score_range_with_limit() {
+    let zset = scored();
+    let range = Range::Score(Included(1.0), Included(f64::INFINITY));
+    assert_eq!(members(zset.range(&range, true, None).await), vec!["hi", "d", "c", "b", "a"]);
+    assert_eq!(members(zset.range(&range, true, Some((1, 2))).await), vec!["d", "c"]);
+    assert_eq!(members(zset.range(&range, true, Some((3, -1))).await), vec!["b", "a"]);
+    assert!(zset.range(&range, true, Some((-1, 2))).await.is_empty());
+    let range = Range::Score(Excluded(1.0), Excluded(3.0));
+    assert_eq!(members(zset.range(&range, true, Some((0, 1))).await), vec!["c"]);
+    let range = Range::Score(Excluded(f64::NEG_INFINITY), Excluded(f64::INFINITY));
+    assert_eq!(members(zset.range(&range, true, Some((2, 10))).await), vec!["b", "a"]);
+}
+
+#[tokio::test]
+async fn test_lex_range_bounds() {
+    let zset = lexical();
+    let range = |min: &str, max: &str, exclusive: bool| {
+        let (min, max) = (Bytes::from(min.to_string()), Bytes::from(max.to_string()));
+        if exclusive { Range::Lex(Excluded(min), Excluded(max)) } else { Range::Lex(Included(min), Included(max)) }
+    };
+    assert_eq!(members(zset.range(&range("b", "d", false), false, None).await), vec!["b", "c", "d"]);
+    assert_eq!(members(zset.range(&range("b", "d", true), false, None).await), vec!["c"]);
+    assert_eq!(members(zset.range(&Range::Lex(Unbounded, Unbounded), false, Some((1, 2))).await), vec!["b", "c"]);
+    assert_eq!(members(zset.range(&range("d", "b", false), false, None).await), Vec::<String>::new());
+    assert_eq!(members(zset.range(&range("b", "d", false), true, None).await), vec!["d", "c", "b"]);
+    let range = Range::Lex(Excluded(Bytes::from("a")), Included(Bytes::from("d")));
+    assert_eq!(members(zset.range(&range, true, Some((1, 2))).await), vec!["c", "b"]);
+    let range = Range::Lex(Unbounded, Excluded(Bytes::from("e")));
+    assert_eq!(members(zset.range(&range, true, Some((0, -1))).await), vec!["d", "c", "b", "a"]);
+}
+
+#[tokio::test]
+async fn test_range_commands_parse_bounds() {
+    let server = common::start("zset-range").await;
+    let mut client = server.client().await;
+    client.call(&["ZADD", "z", "-inf", "lo", "1", "a", "2", "b", "3", "c", "+inf", "hi"]).await;
+    assert_eq!(client.call(&["ZRANGEBYSCORE", "z", "(1", "+inf"]).await, "[b, c, hi]");
+    assert_eq!(client.call(&["ZRANGEBYSCORE", "z", "(-inf", "(+inf"]).await, "[a, b, c]");
+    assert_eq!(client.call(&["ZRANGE", "z", "(3", "-inf", "BYSCORE", "REV", "LIMIT", "1", "2"]).await, "[a, lo]");
+    client.call(&["ZADD", "l", "0", "a", "0", "b", "0", "c", "0", "d"]).await;
+    assert_eq!(client.call(&["ZRANGE", "l", "+", "(a", "BYLEX", "REV", "LIMIT", "1", "2"]).await, "[c, b]");
+    assert_eq!(client.call(&["ZRANGEBYLEX", "l", "-", "[b"]).await, "[a, b]");
+}
+
+#[tokio::test]
+async fn test_increments_reply_the_new_score() {
+    let server = common::start("zset-incr").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["ZINCRBY", "z", "2.5", "m"]).await, "2.5");
+    assert_eq!(client.call(&["ZINCRBY", "z", "-1", "m"]).await, "1.5");
+    assert_eq!(client.call(&["ZADD", "z", "INCR", "1", "m"]).await, "2.5");
+    // the options may skip the member, which replies nil instead of a score
+    assert_eq!(client.call(&["ZADD", "z", "NX", "INCR", "1", "m"]).await, "(nil)");
+    assert_eq!(client.call(&["ZADD", "z", "XX", "INCR", "1", "new"]).await, "(nil)");
+    assert_eq!(client.call(&["ZINCRBY", "z", "+inf", "m"]).await, "inf");
+    assert!(client.call(&["ZINCRBY", "z", "-inf", "m"]).await.starts_with("-ERR resulting score is not a number"));
+}
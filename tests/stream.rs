@@ -0,0 +1,77 @@
+use bytes::Bytes;
+use redis::engine::stream::{Stream, Threshold, Trim};
+
+async fn filled(count: u64) -> Stream {
+    let stream = Stream::new();
+    for seq in 1..=count {
+        stream.add_entry(Some((1, Some(seq))), vec![(Bytes::from("f"), Bytes::from("v"))]).await.unwrap();
+    }
+    stream
+}
+
+#[tokio::test]
+async fn test_exact_trim() {
+    let stream = filled(10).await;
+    let trim = Trim { threshold: Threshold::MaxLen(4), approximate: false, limit: None };
+    assert_eq!(stream.trim(&trim).await, 6);
+    assert_eq!(stream.len().await, 4);
+    let trim = Trim { threshold: Threshold::MinID((1, 9)), approximate: false, limit: None };
+    assert_eq!(stream.trim(&trim).await, 2);
+    assert_eq!(stream.range(None, None, None, false).await[0].id(), (1, 9));
+}
+
+#[tokio::test]
+async fn test_approximate_trim_removes_whole_blocks() {
+    let stream = filled(250).await;
+    let trim = Trim { threshold: Threshold::MaxLen(120), approximate: true, limit: None };
+    assert_eq!(stream.trim(&trim).await, 100);
+    assert_eq!(stream.len().await, 150);
+    let trim = Trim { threshold: Threshold::MaxLen(0), approximate: true, limit: Some(100) };
+    assert_eq!(stream.trim(&trim).await, 100);
+    assert_eq!(stream.len().await, 50);
+}
+
+#[tokio::test]
+async fn test_last_id_survives_deletion() {
+    let stream = filled(3).await;
+    assert_eq!(stream.delete(&[(1, 3), (7, 7)]).await, 1);
+    assert_eq!(stream.last_id().await, (1, 3));
+    assert!(stream.add_entry(Some((1, Some(3))), vec![]).await.is_err());
+    assert!(stream.set_id((1, 1), None, None).await.is_err());
+    stream.set_id((5, 0), Some(3), Some((1, 3))).await.unwrap();
+    assert_eq!(stream.ids().await, ((5, 0), 3, (1, 3)));
+}
+
+#[tokio::test]
+async fn test_auto_id_after_future_set_id() {
+    let stream = filled(1).await;
+    stream.set_id((99999999999999, 5), None, None).await.unwrap();
+    assert_eq!(stream.add_entry(None, vec![]).await.unwrap(), (99999999999999, 6));
+    stream.set_id((99999999999999, u64::MAX), None, None).await.unwrap();
+    assert_eq!(stream.add_entry(None, vec![]).await.unwrap(), (100000000000000, 0));
+    assert!(stream.add_entry(Some((100000000000000, None)), vec![]).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_reverse_range() {
+    let stream = filled(5).await;
+    let ids: Vec<(u64, u64)> = stream.range(Some((1, Some(2))), None, Some(2), true).await.iter().map(|entry| entry.id()).collect();
+    assert_eq!(ids, vec![(1, 5), (1, 4)]);
+    assert!(stream.range(Some((1, Some(4))), Some((1, Some(2))), None, true).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_group_lag() {
+    let stream = filled(5).await;
+    stream.create_group("g", Some((0, 0)), None).await.unwrap();
+    assert_eq!(stream.groups_info().await[0].lag, Some(5));
+    stream.read_group("g", "c", None, Some(2), false).await.unwrap();
+    let info = &stream.groups_info().await[0];
+    assert_eq!((info.entries_read, info.lag), (Some(2), Some(3)));
+    // a deletion ahead of the group makes its lag unknown
+    stream.delete(&[(1, 4)]).await;
+    assert_eq!(stream.groups_info().await[0].lag, None);
+    stream.read_group("g", "c", None, None, false).await.unwrap();
+    let info = &stream.groups_info().await[0];
+    assert_eq!((info.entries_read, info.lag), (Some(5), Some(0)));
+}
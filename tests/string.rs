@@ -0,0 +1,206 @@
+mod common;
+
+use std::time::Duration;
+use redis::aof::Fsync;
+use common::{command, find};
+
+#[tokio::test]
+async fn test_set_conditions_with_get() {
+    let server = common::start("string-set-get").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["SET", "k", "v1", "NX", "GET"]).await, "(nil)");
+    assert_eq!(client.call(&["SET", "k", "v2", "NX", "GET"]).await, "v1");
+    assert_eq!(client.call(&["GET", "k"]).await, "v1");
+    assert_eq!(client.call(&["SET", "k", "v3", "XX", "GET"]).await, "v1");
+    assert_eq!(client.call(&["GET", "k"]).await, "v3");
+    assert_eq!(client.call(&["SET", "missing", "v", "XX", "GET"]).await, "(nil)");
+    assert_eq!(client.call(&["EXISTS", "missing"]).await, ":0");
+    assert_eq!(client.call(&["SET", "k", "v4", "GET"]).await, "v3");
+    assert_eq!(client.call(&["SET", "k", "v5", "NX"]).await, "(nil)");
+    assert_eq!(client.call(&["SET", "k", "v5", "XX"]).await, "+OK");
+}
+
+#[tokio::test]
+async fn test_set_get_on_another_type_writes_nothing() {
+    let server = common::start("string-set-wrongtype").await;
+    let mut client = server.client().await;
+    client.call(&["RPUSH", "l", "a"]).await;
+    assert!(client.call(&["SET", "l", "v", "GET"]).await.starts_with("-WRONGTYPE"));
+    assert!(client.call(&["SET", "l", "v", "XX", "GET"]).await.starts_with("-WRONGTYPE"));
+    assert_eq!(client.call(&["LRANGE", "l", "0", "-1"]).await, "[a]");
+    // without GET the value is replaced whatever its type
+    assert_eq!(client.call(&["SET", "l", "v"]).await, "+OK");
+    assert_eq!(client.call(&["GET", "l"]).await, "v");
+}
+
+#[tokio::test]
+async fn test_set_keepttl_keeps_the_deadline() {
+    let server = common::start("string-keepttl").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "k", "v", "EXAT", "4102444800"]).await;
+    assert_eq!(client.call(&["SET", "k", "v2", "KEEPTTL"]).await, "+OK");
+    assert_eq!(client.call(&["EXPIRETIME", "k"]).await, ":4102444800");
+    assert_eq!(client.call(&["GET", "k"]).await, "v2");
+    // any other SET forgets the TTL
+    client.call(&["SET", "k", "v3"]).await;
+    assert_eq!(client.call(&["TTL", "k"]).await, ":-1");
+    assert!(client.call(&["SET", "k", "v", "EX", "10", "KEEPTTL"]).await.starts_with("-ERR"));
+}
+
+#[tokio::test]
+async fn test_set_with_a_deadline_in_the_past() {
+    let server = common::start("string-set-past").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["SET", "a", "v", "EXAT", "1"]).await, "+OK");
+    assert_eq!(client.call(&["EXISTS", "a"]).await, ":0");
+    client.call(&["SET", "b", "old"]).await;
+    assert_eq!(client.call(&["SET", "b", "new", "PXAT", "1000", "GET"]).await, "old");
+    assert_eq!(client.call(&["GET", "b"]).await, "(nil)");
+    assert!(client.call(&["SET", "c", "v", "EX", "0"]).await.starts_with("-ERR invalid expire time"));
+    assert!(client.call(&["SET", "c", "v", "PXAT", "-1"]).await.starts_with("-ERR invalid expire time"));
+}
+
+#[tokio::test]
+async fn test_incr_only_parses_canonical_integers() {
+    let server = common::start("string-incr-parse").await;
+    let mut client = server.client().await;
+    for value in ["01", "007", "-0", "+1", " 1", "1 ", "1.5", "abc", ""] {
+        client.call(&["SET", "k", value]).await;
+        assert_eq!(client.call(&["INCR", "k"]).await, "-ERR value is not an integer or out of range", "{:?}", value);
+        assert_eq!(client.call(&["GET", "k"]).await, value);
+        client.call(&["HSET", "h", "f", value]).await;
+        assert_eq!(client.call(&["HINCRBY", "h", "f", "1"]).await, "-ERR hash value is not an integer", "{:?}", value);
+    }
+    assert!(client.call(&["INCRBY", "n", "01"]).await.starts_with("-ERR value is not an integer"));
+    assert!(client.call(&["DECRBY", "n", "-0"]).await.starts_with("-ERR value is not an integer"));
+    client.call(&["SET", "k", "0"]).await;
+    assert_eq!(client.call(&["INCR", "k"]).await, ":1");
+    client.call(&["SET", "k", "-10"]).await;
+    assert_eq!(client.call(&["INCRBY", "k", "-5"]).await, ":-15");
+    assert_eq!(client.call(&["INCR", "missing"]).await, ":1");
+}
+
+#[tokio::test]
+async fn test_incr_overflow_leaves_the_value() {
+    let server = common::start("string-incr-overflow").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "max", "9223372036854775807"]).await;
+    assert_eq!(client.call(&["INCR", "max"]).await, "-ERR increment or decrement would overflow");
+    assert_eq!(client.call(&["GET", "max"]).await, "9223372036854775807");
+    client.call(&["SET", "min", "-9223372036854775808"]).await;
+    assert_eq!(client.call(&["DECR", "min"]).await, "-ERR increment or decrement would overflow");
+    assert_eq!(client.call(&["INCRBY", "min", "-1"]).await, "-ERR increment or decrement would overflow");
+    assert_eq!(client.call(&["GET", "min"]).await, "-9223372036854775808");
+    // the negation of the smallest decrement does not fit either
+    assert!(client.call(&["DECRBY", "k", "-9223372036854775808"]).await.starts_with("-ERR"));
+    assert!(client.call(&["INCRBY", "k", "9223372036854775808"]).await.starts_with("-ERR value is not an integer"));
+    assert_eq!(client.call(&["EXISTS", "k"]).await, ":0");
+}
+
+#[tokio::test]
+async fn test_setrange_pads_with_zeros() {
+    let server = common::start("string-setrange").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["SETRANGE", "k", "3", "ab"]).await, ":5");
+    assert_eq!(client.call(&["GET", "k"]).await, "\0\0\0ab");
+    client.call(&["SET", "s", "hello"]).await;
+    assert_eq!(client.call(&["SETRANGE", "s", "7", "x"]).await, ":8");
+    assert_eq!(client.call(&["SETRANGE", "s", "1", "EL"]).await, ":8");
+    assert_eq!(client.call(&["GET", "s"]).await, "hELlo\0\0x");
+    // an empty value changes nothing and never creates the key
+    assert_eq!(client.call(&["SETRANGE", "s", "100", ""]).await, ":8");
+    assert_eq!(client.call(&["SETRANGE", "missing", "3", ""]).await, ":0");
+    assert_eq!(client.call(&["EXISTS", "missing"]).await, ":0");
+    assert_eq!(client.call(&["SETRANGE", "s", "-1", "x"]).await, "-ERR offset is out of range");
+}
+
+#[tokio::test]
+async fn test_setrange_past_the_maximum_size() {
+    let server = common::start("string-setrange-max").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "s", "hello"]).await;
+    let error = "-ERR string exceeds maximum allowed size (proto-max-bulk-len)";
+    // 512MB is the largest string, the value would end one byte past it
+    assert_eq!(client.call(&["SETRANGE", "s", "536870911", "ab"]).await, error);
+    assert_eq!(client.call(&["SETRANGE", "s", "536870912", "a"]).await, error);
+    assert!(client.call(&["SETRANGE", "missing", "18446744073709551615", "a"]).await.starts_with("-ERR"));
+    assert_eq!(client.call(&["GET", "s"]).await, "hello");
+    assert_eq!(client.call(&["EXISTS", "missing"]).await, ":0");
+}
+
+#[tokio::test]
+async fn test_getrange_indexes() {
+    let server = common::start("string-getrange").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "k", "Hello World"]).await;
+    assert_eq!(client.call(&["GETRANGE", "k", "0", "4"]).await, "Hello");
+    assert_eq!(client.call(&["GETRANGE", "k", "-5", "-1"]).await, "World");
+    assert_eq!(client.call(&["GETRANGE", "k", "-100", "4"]).await, "Hello");
+    assert_eq!(client.call(&["GETRANGE", "k", "6", "100"]).await, "World");
+    assert_eq!(client.call(&["GETRANGE", "k", "5", "2"]).await, "");
+    assert_eq!(client.call(&["GETRANGE", "k", "-1", "-5"]).await, "");
+    assert_eq!(client.call(&["GETRANGE", "k", "20", "30"]).await, "");
+    // an end before the start of the string is clamped to the first byte, as Redis does
+    assert_eq!(client.call(&["GETRANGE", "k", "0", "-100"]).await, "H");
+    assert_eq!(client.call(&["GETRANGE", "missing", "0", "-1"]).await, "");
+}
+
+#[tokio::test]
+async fn test_getex_updates_the_ttl() {
+    let server = common::start("string-getex").await;
+    let mut client = server.client().await;
+    client.call(&["SET", "k", "v", "EX", "100"]).await;
+    assert_eq!(client.call(&["GETEX", "k"]).await, "v");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":100");
+    assert_eq!(client.call(&["GETEX", "k", "PERSIST"]).await, "v");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":-1");
+    assert_eq!(client.call(&["GETEX", "k", "EX", "50"]).await, "v");
+    assert_eq!(client.call(&["TTL", "k"]).await, ":50");
+    assert_eq!(client.call(&["GETEX", "k", "PXAT", "1000"]).await, "v");
+    assert_eq!(client.call(&["EXISTS", "k"]).await, ":0");
+    assert_eq!(client.call(&["GETEX", "missing", "PERSIST"]).await, "(nil)");
+    client.call(&["RPUSH", "l", "a"]).await;
+    assert!(client.call(&["GETEX", "l", "PERSIST"]).await.starts_with("-WRONGTYPE"));
+    client.call(&["SET", "k", "v"]).await;
+    assert!(client.call(&["GETEX", "k", "EX", "0"]).await.starts_with("-ERR invalid expire time"));
+    assert!(client.call(&["GETEX", "k", "EX", "10", "PERSIST"]).await.starts_with("-ERR"));
+}
+
+#[tokio::test]
+async fn test_msetnx_sets_nothing_when_a_key_exists() {
+    let server = common::start("string-msetnx").await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["MSETNX", "a", "1", "b", "2"]).await, ":1");
+    assert_eq!(client.call(&["MSETNX", "c", "3", "b", "new", "d", "4"]).await, ":0");
+    assert_eq!(client.call(&["MGET", "a", "b", "c", "d"]).await, "[1, 2, (nil), (nil)]");
+    // a key of another type exists as well
+    client.call(&["RPUSH", "l", "x"]).await;
+    assert_eq!(client.call(&["MSETNX", "c", "3", "l", "v"]).await, ":0");
+    assert_eq!(client.call(&["EXISTS", "c"]).await, ":0");
+    // the same key twice counts as one missing key
+    assert_eq!(client.call(&["MSETNX", "c", "3", "c", "5"]).await, ":1");
+    assert_eq!(client.call(&["GET", "c"]).await, "5");
+}
+
+#[tokio::test]
+async fn test_mset_propagates_as_one_command() {
+    let server = common::start_with("string-mset-propagate", Some(Fsync::Always)).await;
+    let mut replica = server.client().await;
+    replica.sync_as_replica().await;
+    let mut client = server.client().await;
+    assert_eq!(client.call(&["MSET", "a", "1", "b", "2"]).await, "+OK");
+    assert_eq!(client.call(&["MSETNX", "c", "3", "d", "4"]).await, ":1");
+    assert_eq!(client.call(&["MSETNX", "a", "5", "e", "6"]).await, ":0");
+    let mset = command(&["MSET", "a", "1", "b", "2"]);
+    let msetnx = command(&["MSET", "c", "3", "d", "4"]);
+    let stream = replica.read_raw(Duration::from_millis(200)).await;
+    let logged = std::fs::read(server.dir.join("appendonly.aof")).unwrap();
+    for data in [stream, logged] {
+        let shown = String::from_utf8_lossy(&data).into_owned();
+        let at = find(&data, &mset).expect(&shown);
+        assert_eq!(find(&data[at..], &msetnx), Some(mset.len()), "{}", shown);
+        // neither as separate SETs, nor anything for the MSETNX that set nothing
+        assert!(find(&data, b"$3\r\nSET\r\n").is_none() && find(&data, b"MSETNX").is_none(), "{}", shown);
+        assert!(find(&data, b"\r\ne\r\n").is_none(), "{}", shown);
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::time::Duration;
+use redis::aof::Fsync;
+use common::{command, find};
+
+#[tokio::test]
+async fn test_exec_aborts_when_a_watched_key_changed() {
+    let server = common::start("multi-watch").await;
+    let mut client = server.client().await;
+    let mut other = server.client().await;
+    client.call(&["SET", "k", "1"]).await;
+    assert_eq!(client.call(&["WATCH", "k"]).await, "+OK");
+    assert_eq!(other.call(&["SET", "k", "2"]).await, "+OK");
+    assert_eq!(client.call(&["MULTI"]).await, "+OK");
+    assert_eq!(client.call(&["SET", "k", "3"]).await, "+QUEUED");
+    assert_eq!(client.call(&["EXEC"]).await, "(nil)");
+    assert_eq!(client.call(&["GET", "k"]).await, "2");
+    // the watch ends with the EXEC
+    other.call(&["SET", "k", "4"]).await;
+    client.call(&["MULTI"]).await;
+    client.call(&["SET", "k", "5"]).await;
+    assert_eq!(client.call(&["EXEC"]).await, "[+OK]");
+}
+
+#[tokio::test]
+async fn test_exec_runs_when_watched_keys_are_unchanged() {
+    let server = common::start("multi-unchanged").await;
+    let mut client = server.client().await;
+    client.call(&["WATCH", "k", "missing"]).await;
+    client.call(&["MULTI"]).await;
+    client.call(&["SET", "k", "1"]).await;
+    client.call(&["INCR", "k"]).await;
+    client.call(&["GET", "k"]).await;
+    assert_eq!(client.call(&["EXEC"]).await, "[+OK, :2, 2]");
+}
+
+#[tokio::test]
+async fn test_queue_errors_discard_the_transaction() {
+    let server = common::start("multi-execabort").await;
+    let mut client = server.client().await;
+    client.call(&["MULTI"]).await;
+    assert_eq!(client.call(&["SET", "a", "1"]).await, "+QUEUED");
+    assert!(client.call(&["SET", "b"]).await.starts_with("-ERR"));
+    assert_eq!(client.call(&["EXEC"]).await, "-EXECABORT Transaction discarded because of previous errors.");
+    assert_eq!(client.call(&["EXISTS", "a"]).await, ":0");
+    assert_eq!(client.call(&["EXEC"]).await, "-ERR EXEC without MULTI");
+}
+
+#[tokio::test]
+async fn test_discard_and_unwatch_forget_the_watched_keys() {
+    let server = common::start("multi-discard").await;
+    let mut client = server.client().await;
+    let mut other = server.client().await;
+    client.call(&["WATCH", "k"]).await;
+    client.call(&["MULTI"]).await;
+    client.call(&["SET", "k", "1"]).await;
+    assert_eq!(client.call(&["DISCARD"]).await, "+OK");
+    other.call(&["SET", "k", "2"]).await;
+    client.call(&["MULTI"]).await;
+    client.call(&["SET", "k", "3"]).await;
+    assert_eq!(client.call(&["EXEC"]).await, "[+OK]");
+
+    client.call(&["WATCH", "k"]).await;
+    assert_eq!(client.call(&["UNWATCH"]).await, "+OK");
+    other.call(&["SET", "k", "4"]).await;
+    client.call(&["MULTI"]).await;
+    client.call(&["GET", "k"]).await;
+    assert_eq!(client.call(&["EXEC"]).await, "[4]");
+    assert_eq!(client.call(&["DISCARD"]).await, "-ERR DISCARD without MULTI");
+}
+
+#[tokio::test]
+async fn test_transactions_propagate_as_one_block() {
+    let server = common::start_with("multi-propagate", Some(Fsync::Always)).await;
+    let mut replica = server.client().await;
+    replica.sync_as_replica().await;
+    let mut client = server.client().await;
+    client.call(&["MULTI"]).await;
+    client.call(&["SET", "a", "1"]).await;
+    client.call(&["GET", "a"]).await;
+    client.call(&["SET", "b", "2"]).await;
+    assert_eq!(client.call(&["EXEC"]).await, "[+OK, 1, +OK]");
+    // the SELECT of the database may come first within the block
+    let block = [command(&["SET", "a", "1"]), command(&["SET", "b", "2"]), command(&["EXEC"])].concat();
+    let stream = replica.read_raw(Duration::from_millis(200)).await;
+    let multi = find(&stream, &command(&["MULTI"])).unwrap();
+    assert!(find(&stream[multi..], &block).is_some(), "{}", String::from_utf8_lossy(&stream));
+    let logged = std::fs::read(server.dir.join("appendonly.aof")).unwrap();
+    let multi = find(&logged, &command(&["MULTI"])).unwrap();
+    assert!(find(&logged[multi..], &block).is_some(), "{}", String::from_utf8_lossy(&logged));
+}